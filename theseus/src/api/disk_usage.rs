@@ -0,0 +1,181 @@
+//! Theseus disk usage reports
+use crate::event::emit::{emit_loading, init_loading};
+use crate::event::LoadingBarType;
+use crate::profile::{get, list, Profile};
+use crate::state::DirectoryInfo;
+use crate::State;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+/// Size of a top-level folder of a profile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FolderUsage {
+    pub name: String,
+    pub size: u64,
+}
+
+/// Disk usage of a profile, by top-level folder
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileUsage {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    /// Largest first. Shared folders are counted in the shared store instead
+    pub folders: Vec<FolderUsage>,
+    /// Files directly in the profile folder, such as options.txt
+    pub files_size: u64,
+}
+
+/// Disk usage of a folder of the launcher's data directory
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StoreUsage {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DiskUsageReport {
+    /// Largest first
+    pub profiles: Vec<ProfileUsage>,
+    pub stores: Vec<StoreUsage>,
+    pub size: u64,
+}
+
+fn stores(dirs: &DirectoryInfo) -> Vec<(&'static str, PathBuf)> {
+    vec![
+        ("versions", dirs.versions_dir()),
+        ("libraries", dirs.libraries_dir()),
+        ("assets_objects", dirs.objects_dir()),
+        ("natives", dirs.natives_dir()),
+        ("java_versions", dirs.java_versions_dir()),
+        ("icons", dirs.caches_icons_dir()),
+        ("snapshots", dirs.snapshots_dir()),
+        ("world_backups", dirs.world_backups_dir()),
+        ("shared", dirs.shared_dir()),
+        ("launcher_logs", dirs.launcher_logs_dir()),
+    ]
+}
+
+// Sums the sizes of every file under a path. Links aren't followed, so
+// shared folders and linked files aren't counted twice
+fn dir_size(path: &Path) -> u64 {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return 0,
+    };
+
+    if !metadata.is_dir() {
+        return if metadata.is_file() {
+            metadata.len()
+        } else {
+            0
+        };
+    }
+
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries.flatten().map(|entry| dir_size(&entry.path())).sum()
+        })
+        .unwrap_or(0)
+}
+
+// Walking a folder is blocking, so it's done off the async runtime
+async fn dir_size_blocking(path: PathBuf) -> crate::Result<u64> {
+    Ok(tokio::task::spawn_blocking(move || dir_size(&path)).await?)
+}
+
+async fn profile_usage(profile: &Profile) -> crate::Result<ProfileUsage> {
+    let path = profile.path.clone();
+    let (folders, files_size) = tokio::task::spawn_blocking(move || {
+        let mut folders = Vec::new();
+        let mut files_size = 0;
+
+        // Entry metadata doesn't follow links, so links to shared folders
+        // are neither folders nor files here
+        for entry in std::fs::read_dir(&path).into_iter().flatten().flatten() {
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if metadata.is_dir() {
+                folders.push(FolderUsage {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    size: dir_size(&entry.path()),
+                });
+            } else if metadata.is_file() {
+                files_size += metadata.len();
+            }
+        }
+
+        folders.sort_by_key(|x| Reverse(x.size));
+        (folders, files_size)
+    })
+    .await?;
+
+    Ok(ProfileUsage {
+        path: profile.path.clone(),
+        name: profile.metadata.name.clone(),
+        size: folders.iter().map(|x| x.size).sum::<u64>() + files_size,
+        folders,
+        files_size,
+    })
+}
+
+/// Gets the disk usage of a profile
+#[tracing::instrument]
+pub async fn get_profile_usage(
+    profile_path: &Path,
+) -> crate::Result<ProfileUsage> {
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+        .as_error()
+    })?;
+
+    profile_usage(&profile).await
+}
+
+/// Gets the disk usage of every profile and launcher store. Each profile
+/// and store is walked in turn, reporting progress through a loading bar
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn get_report() -> crate::Result<DiskUsageReport> {
+    let state = State::get().await?;
+    let profiles = list(Some(true)).await?;
+    let stores = stores(&state.directories);
+
+    let loading_bar = init_loading(
+        LoadingBarType::DiskUsage,
+        (profiles.len() + stores.len()) as f64,
+        "Calculating disk usage",
+    )
+    .await?;
+
+    let mut profile_usages = Vec::new();
+    for profile in profiles.values() {
+        profile_usages.push(profile_usage(profile).await?);
+        emit_loading(&loading_bar, 1.0, None).await?;
+    }
+    profile_usages.sort_by_key(|x| Reverse(x.size));
+
+    let mut store_usages = Vec::new();
+    for (name, path) in stores {
+        store_usages.push(StoreUsage {
+            name: name.to_string(),
+            size: dir_size_blocking(path.clone()).await?,
+            path,
+        });
+        emit_loading(&loading_bar, 1.0, None).await?;
+    }
+
+    Ok(DiskUsageReport {
+        size: profile_usages.iter().map(|x| x.size).sum::<u64>()
+            + store_usages.iter().map(|x| x.size).sum::<u64>(),
+        profiles: profile_usages,
+        stores: store_usages,
+    })
+}
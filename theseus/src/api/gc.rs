@@ -0,0 +1,370 @@
+//! Theseus garbage collection of unused game files
+//! Versions, natives, libraries, assets and icons are shared between
+//! profiles and are never removed when a profile stops using them. This
+//! finds the ones no profile uses anymore, so they can be removed or moved
+//! to a quarantine folder
+use crate::event::emit::{emit_loading, init_loading};
+use crate::event::LoadingBarType;
+use crate::profile::{list, Profile};
+use crate::state::{
+    DirectoryInfo, ProfileInstallStage, ProfileOperation, ProjectMetadata,
+};
+use crate::State;
+use daedalus::get_path_from_artifact;
+use daedalus::minecraft::{AssetsIndex, VersionInfo};
+use dunce::canonicalize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GcKind {
+    Version,
+    Natives,
+    Library,
+    AssetIndex,
+    AssetObject,
+    Icon,
+}
+
+/// A file or folder no profile uses
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GcEntry {
+    pub kind: GcKind,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// What a garbage collection removes, or would remove on a dry run
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GcPlan {
+    pub entries: Vec<GcEntry>,
+    /// The space freed, in bytes
+    pub size: u64,
+    /// Where the entries were moved, if they were quarantined
+    pub quarantine_path: Option<PathBuf>,
+}
+
+// Everything used by at least one profile
+#[derive(Default)]
+struct Referenced {
+    versions: HashSet<String>,
+    /// Relative to the libraries folder
+    libraries: HashSet<PathBuf>,
+    asset_indexes: HashSet<String>,
+    objects: HashSet<String>,
+    icons: HashSet<PathBuf>,
+}
+
+impl Referenced {
+    async fn add_profile(
+        &mut self,
+        dirs: &DirectoryInfo,
+        profile: &Profile,
+    ) -> crate::Result<()> {
+        let icons = profile.metadata.icon.iter().chain(
+            profile.projects.values().filter_map(|x| match &x.metadata {
+                ProjectMetadata::Inferred { icon, .. } => icon.as_ref(),
+                _ => None,
+            }),
+        );
+        self.icons.extend(icons.cloned());
+
+        // A profile that isn't installed downloads what it needs when it is
+        if profile.install_stage != ProfileInstallStage::Installed {
+            return Ok(());
+        }
+
+        let version_id = match &profile.metadata.loader_version {
+            Some(loader) => {
+                format!("{}-{}", profile.metadata.game_version, loader.id)
+            }
+            None => profile.metadata.game_version.clone(),
+        };
+        let version_info = read_json::<VersionInfo>(
+            &dirs
+                .version_dir(&version_id)
+                .join(format!("{version_id}.json")),
+        )
+        .await
+        .map_err(|err| {
+            crate::ErrorKind::LauncherError(format!(
+                "Could not tell which files {} uses, try repairing it: {err}",
+                profile.metadata.name
+            ))
+            .as_error()
+        })?;
+
+        self.versions.insert(version_id);
+
+        let mut artifacts = version_info
+            .libraries
+            .iter()
+            .map(|x| x.name.clone())
+            .collect::<Vec<_>>();
+        for processor in version_info.processors.iter().flatten() {
+            artifacts.push(processor.jar.clone());
+            artifacts.extend(processor.classpath.iter().cloned());
+        }
+        // Files made by processors are data entries in brackets
+        for entry in version_info.data.iter().flat_map(|x| x.values()) {
+            if let Some(artifact) = entry
+                .client
+                .strip_prefix('[')
+                .and_then(|x| x.strip_suffix(']'))
+            {
+                artifacts.push(artifact.to_string());
+            }
+        }
+        for artifact in artifacts {
+            self.libraries
+                .insert(PathBuf::from(get_path_from_artifact(&artifact)?));
+        }
+
+        let index_id = version_info.asset_index.id;
+        if self.asset_indexes.insert(index_id.clone()) {
+            let index = read_json::<AssetsIndex>(
+                &dirs.assets_index_dir().join(format!("{index_id}.json")),
+            )
+            .await?;
+            self.objects
+                .extend(index.objects.into_values().map(|x| x.hash));
+        }
+
+        Ok(())
+    }
+}
+
+async fn read_json<T: serde::de::DeserializeOwned>(
+    path: &Path,
+) -> crate::Result<T> {
+    Ok(serde_json::from_slice(&fs::read(path).await?)?)
+}
+
+// Lists the files under a folder with their sizes, without following links
+fn list_files(dir: &Path, files: &mut Vec<(PathBuf, u64)>) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => {
+                list_files(&entry.path(), files)
+            }
+            Ok(metadata) => files.push((entry.path(), metadata.len())),
+            Err(_) => {}
+        }
+    }
+}
+
+fn folder_size(dir: &Path) -> u64 {
+    let mut files = Vec::new();
+    list_files(dir, &mut files);
+    files.iter().map(|(_, size)| size).sum()
+}
+
+// Finds everything under the launcher's stores that isn't referenced
+fn find_unused(dirs: &DirectoryInfo, referenced: &Referenced) -> Vec<GcEntry> {
+    let mut entries = Vec::new();
+
+    // Versions and natives are kept or removed as whole folders
+    for (kind, dir) in [
+        (GcKind::Version, dirs.versions_dir()),
+        (GcKind::Natives, dirs.natives_dir()),
+    ] {
+        for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !referenced.versions.contains(&name) {
+                entries.push(GcEntry {
+                    kind,
+                    size: folder_size(&entry.path()),
+                    path: entry.path(),
+                });
+            }
+        }
+    }
+
+    let libraries_dir = dirs.libraries_dir();
+    let mut files = Vec::new();
+    list_files(&libraries_dir, &mut files);
+    entries.extend(files.into_iter().filter_map(|(path, size)| {
+        let used = referenced
+            .libraries
+            .contains(path.strip_prefix(&libraries_dir).ok()?);
+        (!used).then_some(GcEntry {
+            kind: GcKind::Library,
+            path,
+            size,
+        })
+    }));
+
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut files = Vec::new();
+    list_files(&dirs.assets_index_dir(), &mut files);
+    entries.extend(files.into_iter().filter_map(|(path, size)| {
+        let id = file_name(&path).strip_suffix(".json")?.to_string();
+        (!referenced.asset_indexes.contains(&id)).then_some(GcEntry {
+            kind: GcKind::AssetIndex,
+            path,
+            size,
+        })
+    }));
+
+    let mut files = Vec::new();
+    list_files(&dirs.objects_dir(), &mut files);
+    entries.extend(files.into_iter().filter_map(|(path, size)| {
+        (!referenced.objects.contains(&file_name(&path))).then_some(GcEntry {
+            kind: GcKind::AssetObject,
+            path,
+            size,
+        })
+    }));
+
+    // Icon paths are stored canonicalized
+    let icons_dir = canonicalize(dirs.caches_icons_dir())
+        .unwrap_or_else(|_| dirs.caches_icons_dir());
+    let mut files = Vec::new();
+    list_files(&icons_dir, &mut files);
+    entries.extend(files.into_iter().filter_map(|(path, size)| {
+        (!referenced.icons.contains(&path)).then_some(GcEntry {
+            kind: GcKind::Icon,
+            path,
+            size,
+        })
+    }));
+
+    entries
+}
+
+async fn find_plan(profiles: &[Profile]) -> crate::Result<GcPlan> {
+    let state = State::get().await?;
+    let mut referenced = Referenced::default();
+    for profile in profiles {
+        referenced.add_profile(&state.directories, profile).await?;
+    }
+
+    let entries = tokio::task::spawn_blocking(move || {
+        find_unused(&state.directories, &referenced)
+    })
+    .await?;
+
+    Ok(GcPlan {
+        size: entries.iter().map(|x| x.size).sum(),
+        entries,
+        quarantine_path: None,
+    })
+}
+
+/// Lists the files no profile uses, and the space removing them would free,
+/// without removing anything
+#[tracing::instrument]
+pub async fn plan() -> crate::Result<GcPlan> {
+    let profiles = list(None).await?.into_values().collect::<Vec<_>>();
+    find_plan(&profiles).await
+}
+
+/// Removes the files no profile uses, or moves them to a new quarantine
+/// folder so they can be restored by hand. Refuses to run while Minecraft is
+/// running or another operation is running on a profile
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn run(quarantine: bool) -> crate::Result<GcPlan> {
+    let state = State::get().await?;
+    // Every profile is locked, so nothing is installed or launched while
+    // files are removed
+    let profiles = list(None).await?.into_values().collect::<Vec<_>>();
+    let _locks = profiles
+        .iter()
+        .map(|x| {
+            state.profile_locks.try_lock(
+                x.uuid,
+                &x.metadata.name,
+                ProfileOperation::CleaningUp,
+            )
+        })
+        .collect::<crate::Result<Vec<_>>>()?;
+
+    if !state.children.read().await.running_keys().await?.is_empty() {
+        return Err(crate::ErrorKind::InputError(
+            "Cannot remove unused files while Minecraft is running".to_string(),
+        )
+        .into());
+    }
+
+    let mut plan = find_plan(&profiles).await?;
+    let quarantine_path = quarantine.then(|| {
+        state
+            .directories
+            .quarantine_dir()
+            .join(chrono::Utc::now().timestamp_millis().to_string())
+    });
+
+    let loading_bar = init_loading(
+        LoadingBarType::GarbageCollection,
+        plan.entries.len().max(1) as f64,
+        "Removing unused files",
+    )
+    .await?;
+
+    for entry in &plan.entries {
+        match &quarantine_path {
+            Some(quarantine_path) => {
+                let relative =
+                    relative_to(&entry.path, &state.directories.config_dir)
+                        .ok_or_else(|| {
+                            crate::ErrorKind::FSError(format!(
+                                "{} is outside the launcher folder",
+                                entry.path.display()
+                            ))
+                        })?;
+                let dest = quarantine_path.join(relative);
+                if let Some(parent) = dest.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                fs::rename(&entry.path, &dest).await?;
+            }
+            None if entry.path.is_dir() => {
+                fs::remove_dir_all(&entry.path).await?
+            }
+            None => fs::remove_file(&entry.path).await?,
+        }
+
+        emit_loading(&loading_bar, 1.0, None).await?;
+    }
+    if plan.entries.is_empty() {
+        emit_loading(&loading_bar, 1.0, None).await?;
+    }
+
+    for dir in [
+        state.directories.libraries_dir(),
+        state.directories.objects_dir(),
+    ] {
+        tokio::task::spawn_blocking(move || remove_empty_dirs(&dir)).await?;
+    }
+
+    plan.quarantine_path = quarantine_path;
+    Ok(plan)
+}
+
+// Gets a path relative to a folder, which may have been canonicalized
+fn relative_to(path: &Path, dir: &Path) -> Option<PathBuf> {
+    path.strip_prefix(dir)
+        .ok()
+        .or_else(|| path.strip_prefix(canonicalize(dir).ok()?).ok())
+        .map(Path::to_path_buf)
+}
+
+// Removes the folders left empty under a folder, keeping the folder itself
+fn remove_empty_dirs(dir: &Path) {
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        if entry.metadata().map(|x| x.is_dir()).unwrap_or(false) {
+            remove_empty_dirs(&entry.path());
+            // Fails unless the folder is empty
+            let _ = std::fs::remove_dir(entry.path());
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! API for interacting with Theseus
 pub mod auth;
+pub mod disk_usage;
+pub mod gc;
 pub mod handler;
 pub mod jre;
 pub mod logs;
@@ -9,14 +11,19 @@ pub mod process;
 pub mod profile;
 pub mod profile_create;
 pub mod safety;
+pub mod servers;
 pub mod settings;
+pub mod snapshot;
 pub mod tags;
+pub mod worlds;
 
 pub mod data {
     pub use crate::state::{
-        DirectoryInfo, Hooks, JavaSettings, MemorySettings, ModLoader,
-        ModrinthProject, ModrinthTeamMember, ModrinthUser, ModrinthVersion,
-        ProfileMetadata, ProjectMetadata, Settings, Theme, WindowSize,
+        BrokenProfile, DirectoryInfo, Hooks, JavaSettings, MemorySettings,
+        ModLoader, ModrinthProject, ModrinthTeamMember, ModrinthUser,
+        ModrinthVersion, OptionCategory, Playtime, ProfileMetadata,
+        ProfileOperation, ProjectMetadata, Session, Settings,
+        SnapshotRetention, Theme, WindowSize,
     };
 }
 
@@ -24,12 +31,13 @@ pub mod prelude {
     pub use crate::{
         auth::{self, Credentials},
         data::*,
+        disk_usage,
         event::CommandPayload,
-        jre, metadata, pack, process,
-        profile::{self, Profile},
-        profile_create, settings,
+        gc, jre, metadata, pack, process,
+        profile::{self, LaunchOptions, Profile, QuickPlay},
+        profile_create, servers, settings, snapshot,
         state::JavaGlobals,
         util::jre::JavaVersion,
-        State,
+        worlds, State,
     };
 }
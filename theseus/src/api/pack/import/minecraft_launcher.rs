@@ -0,0 +1,401 @@
+//! Importing profiles from the official Minecraft launcher
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::event::emit::{init_loading, loading_try_for_each_concurrent};
+use crate::event::LoadingBarType;
+use crate::prelude::ModLoader;
+use crate::profile_create::profile_create;
+use crate::state::{JavaSettings, MemorySettings, WindowSize};
+use crate::util::fetch::copy;
+use crate::State;
+
+const LAUNCHER_PROFILES_JSON: &str = "launcher_profiles.json";
+
+/// The official launcher's profile list (launcher_profiles.json)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LauncherProfiles {
+    pub profiles: HashMap<String, LauncherProfile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LauncherProfile {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub profile_type: Option<String>,
+    pub last_version_id: Option<String>,
+    pub game_dir: Option<PathBuf>,
+    pub java_args: Option<String>,
+    pub resolution: Option<LauncherResolution>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct LauncherResolution {
+    pub width: u16,
+    pub height: u16,
+}
+
+// The parts of a version JSON (versions/<id>/<id>.json) needed to find the
+// game version and mod loader. Fabric, Quilt and Forge installers all create
+// one of these that inherits from the vanilla version
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct VersionJson {
+    id: String,
+    inherits_from: Option<String>,
+    #[serde(default)]
+    libraries: Vec<VersionJsonLibrary>,
+}
+
+#[derive(Deserialize, Debug)]
+struct VersionJsonLibrary {
+    name: String,
+}
+
+/// Gets the default location of the official launcher's game directory
+pub fn get_default_dotminecraft() -> Option<PathBuf> {
+    let path = if cfg!(target_os = "windows") {
+        dirs::config_dir()?.join(".minecraft")
+    } else if cfg!(target_os = "macos") {
+        dirs::data_dir()?.join("minecraft")
+    } else {
+        dirs::home_dir()?.join(".minecraft")
+    };
+
+    Some(path).filter(|x| x.join(LAUNCHER_PROFILES_JSON).exists())
+}
+
+/// Lists the profiles of the official launcher, keyed by their ID
+#[tracing::instrument]
+pub async fn get_importable_profiles(
+    dotminecraft: PathBuf,
+) -> crate::Result<HashMap<String, LauncherProfile>> {
+    let launcher_profiles =
+        fs::read(dotminecraft.join(LAUNCHER_PROFILES_JSON)).await?;
+    let launcher_profiles =
+        serde_json::from_slice::<LauncherProfiles>(&launcher_profiles)?;
+
+    Ok(launcher_profiles.profiles)
+}
+
+/// Imports profiles from the official launcher
+/// dotminecraft is the launcher's game directory, and profile_ids are the
+/// keys of the profiles to import (see get_importable_profiles)
+/// Libraries and assets already downloaded by the launcher are copied over
+/// first, so they don't need to be downloaded again
+/// Returns the paths of the created profiles
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn import_minecraft_launcher(
+    dotminecraft: PathBuf,
+    profile_ids: Vec<String>,
+) -> crate::Result<Vec<PathBuf>> {
+    let launcher_profiles =
+        get_importable_profiles(dotminecraft.clone()).await?;
+
+    seed_launcher_files(&dotminecraft).await?;
+
+    let mut profile_paths = Vec::new();
+    for profile_id in profile_ids {
+        let launcher_profile =
+            launcher_profiles.get(&profile_id).ok_or_else(|| {
+                crate::ErrorKind::InputError(format!(
+                    "No launcher profile with ID {profile_id}"
+                ))
+            })?;
+
+        profile_paths.push(
+            import_launcher_profile(&dotminecraft, launcher_profile).await?,
+        );
+    }
+
+    State::sync().await?;
+
+    Ok(profile_paths)
+}
+
+/// Copies the libraries and asset objects of the official launcher into
+/// theseus' own stores, skipping any files that already exist
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn seed_launcher_files(dotminecraft: &Path) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    let mut files = Vec::new();
+    for (src_dir, dest_dir) in [
+        (
+            dotminecraft.join("libraries"),
+            state.directories.libraries_dir(),
+        ),
+        (
+            dotminecraft.join("assets").join("objects"),
+            state.directories.objects_dir(),
+        ),
+    ] {
+        if !src_dir.is_dir() {
+            continue;
+        }
+
+        let mut path_list = Vec::new();
+        crate::api::profile::build_folder(&src_dir, &mut path_list).await?;
+
+        for path in path_list {
+            let dest = dest_dir.join(path.strip_prefix(&src_dir)?);
+            if !dest.exists() {
+                files.push((path, dest));
+            }
+        }
+    }
+
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let loading_bar = init_loading(
+        LoadingBarType::CopyLauncherFiles {
+            import_location: dotminecraft.to_path_buf(),
+        },
+        100.0,
+        "Copying launcher files",
+    )
+    .await?;
+
+    let num_files = files.len();
+    use futures::StreamExt;
+    loading_try_for_each_concurrent(
+        futures::stream::iter(files).map(Ok::<_, crate::Error>),
+        None,
+        Some(&loading_bar),
+        100.0,
+        num_files,
+        None,
+        |(src, dest)| {
+            let state = &state;
+            async move {
+                copy(&src, &dest, &state.io_semaphore).await?;
+
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn import_launcher_profile(
+    dotminecraft: &Path,
+    launcher_profile: &LauncherProfile,
+) -> crate::Result<PathBuf> {
+    let version_id = match (
+        launcher_profile.profile_type.as_deref(),
+        launcher_profile.last_version_id.as_deref(),
+    ) {
+        (Some("latest-snapshot"), _) | (_, Some("latest-snapshot")) => {
+            get_latest_version(true).await?
+        }
+        (Some("latest-release"), _)
+        | (_, Some("latest-release"))
+        | (_, None) => get_latest_version(false).await?,
+        (_, Some(version_id)) => version_id.to_string(),
+    };
+
+    let (game_version, mod_loader, loader_version) =
+        get_versions(dotminecraft, &version_id).await?;
+
+    let name = match launcher_profile.name.as_deref() {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => match launcher_profile.profile_type.as_deref() {
+            Some("latest-snapshot") => "Latest snapshot".to_string(),
+            Some("latest-release") => "Latest release".to_string(),
+            _ => version_id.clone(),
+        },
+    };
+
+    let profile_path = profile_create(
+        name,
+        game_version,
+        mod_loader,
+        loader_version,
+        None,
+        None,
+        None,
+        Some(true),
+    )
+    .await?;
+
+    let result = async {
+        // The launcher passes its own -Xmx, so it is moved to the memory
+        // settings rather than being passed twice
+        let mut memory = None;
+        let mut java_args = Vec::new();
+        for arg in launcher_profile
+            .java_args
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+        {
+            match arg.strip_prefix("-Xmx").and_then(parse_memory) {
+                Some(maximum) => memory = Some(MemorySettings { maximum }),
+                None => java_args.push(arg.to_string()),
+            }
+        }
+
+        crate::api::profile::edit(&profile_path, |prof| {
+            if memory.is_some() {
+                prof.memory = memory;
+            }
+
+            if !java_args.is_empty() {
+                prof.java = Some(JavaSettings {
+                    override_version: None,
+                    extra_arguments: Some(java_args.clone()),
+                    custom_env_args: None,
+                });
+            }
+
+            if let Some(resolution) = launcher_profile.resolution {
+                prof.resolution =
+                    Some(WindowSize(resolution.width, resolution.height));
+            }
+
+            async { Ok(()) }
+        })
+        .await?;
+
+        let game_dir = launcher_profile
+            .game_dir
+            .clone()
+            .unwrap_or_else(|| dotminecraft.to_path_buf());
+        if game_dir.is_dir() {
+            super::copy_dotminecraft(&profile_path, &game_dir).await?;
+        }
+
+        if let Some(profile) =
+            crate::api::profile::get(&profile_path, None).await?
+        {
+            crate::launcher::install_minecraft(&profile, None).await?;
+        }
+
+        Ok::<PathBuf, crate::Error>(profile_path.clone())
+    }
+    .await;
+
+    match result {
+        Ok(profile_path) => Ok(profile_path),
+        Err(err) => {
+            let _ = crate::api::profile::remove(&profile_path).await;
+
+            Err(err)
+        }
+    }
+}
+
+async fn get_latest_version(snapshot: bool) -> crate::Result<String> {
+    let state = State::get().await?;
+    let metadata = state.metadata.read().await;
+
+    Ok(if snapshot {
+        metadata.minecraft.latest.snapshot.clone()
+    } else {
+        metadata.minecraft.latest.release.clone()
+    })
+}
+
+// Finds the game version, mod loader and loader version of a version ID by
+// reading its version JSON. Versions without one are treated as vanilla
+async fn get_versions(
+    dotminecraft: &Path,
+    version_id: &str,
+) -> crate::Result<(String, ModLoader, Option<String>)> {
+    let version_json_path = dotminecraft
+        .join("versions")
+        .join(version_id)
+        .join(format!("{version_id}.json"));
+
+    if !version_json_path.exists() {
+        return Ok((version_id.to_string(), ModLoader::Vanilla, None));
+    }
+
+    let version_json = fs::read(&version_json_path).await?;
+    let version_json = serde_json::from_slice::<VersionJson>(&version_json)?;
+    let game_version = version_json
+        .inherits_from
+        .clone()
+        .unwrap_or_else(|| version_json.id.clone());
+
+    for library in &version_json.libraries {
+        let mut parts = library.name.split(':');
+        let (mod_loader, loader_version) =
+            match (parts.next(), parts.next(), parts.next()) {
+                (
+                    Some("net.fabricmc"),
+                    Some("fabric-loader"),
+                    Some(version),
+                ) => (ModLoader::Fabric, version),
+                (Some("org.quiltmc"), Some("quilt-loader"), Some(version)) => {
+                    (ModLoader::Quilt, version)
+                }
+                // Forge versions are prefixed with the game version
+                (
+                    Some("net.minecraftforge"),
+                    Some("forge" | "fmlloader"),
+                    Some(version),
+                ) => (
+                    ModLoader::Forge,
+                    version
+                        .strip_prefix(&format!("{game_version}-"))
+                        .unwrap_or(version),
+                ),
+                _ => continue,
+            };
+
+        return Ok((
+            game_version,
+            mod_loader,
+            Some(loader_version.to_string()),
+        ));
+    }
+
+    Ok((game_version, ModLoader::Vanilla, None))
+}
+
+// Parses a JVM memory size (ie: 2G, 2048M) into megabytes
+fn parse_memory(size: &str) -> Option<u32> {
+    let (index, unit) = size.char_indices().last()?;
+    let number = &size[..index];
+    match unit {
+        'G' | 'g' => number.parse::<u32>().ok()?.checked_mul(1024),
+        'M' | 'm' => number.parse().ok(),
+        'K' | 'k' => Some(number.parse::<u32>().ok()? / 1024),
+        _ => size.parse::<u32>().ok().map(|x| x / (1024 * 1024)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_memory_sizes() {
+        assert_eq!(parse_memory("4G"), Some(4096));
+        assert_eq!(parse_memory("4g"), Some(4096));
+        assert_eq!(parse_memory("2048M"), Some(2048));
+        assert_eq!(parse_memory("2097152k"), Some(2048));
+        assert_eq!(parse_memory("2147483648"), Some(2048));
+        assert_eq!(parse_memory("5000000G"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_memory_sizes() {
+        assert_eq!(parse_memory(""), None);
+        assert_eq!(parse_memory("G"), None);
+        assert_eq!(parse_memory("4é"), None);
+        assert_eq!(parse_memory("é"), None);
+        assert_eq!(parse_memory("4GB"), None);
+    }
+}
@@ -0,0 +1,301 @@
+//! Importing MultiMC / Prism Launcher instances
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::prelude::ModLoader;
+use crate::profile_create::profile_create;
+use crate::state::{JavaSettings, MemorySettings, WindowSize};
+use crate::State;
+
+const INSTANCE_CFG: &str = "instance.cfg";
+const MMC_PACK_JSON: &str = "mmc-pack.json";
+
+/// The component list of an instance (mmc-pack.json)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MMCPack {
+    pub components: Vec<MMCComponent>,
+    pub format_version: u32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MMCComponent {
+    pub uid: String,
+    pub version: Option<String>,
+    pub cached_version: Option<String>,
+}
+
+impl MMCPack {
+    /// Maps the component list to a game version, mod loader and loader version
+    pub fn get_versions(
+        &self,
+    ) -> crate::Result<(String, ModLoader, Option<String>)> {
+        let mut game_version = None;
+        let mut mod_loader = ModLoader::Vanilla;
+        let mut loader_version = None;
+
+        for component in &self.components {
+            let version = component
+                .version
+                .clone()
+                .or_else(|| component.cached_version.clone());
+
+            match component.uid.as_str() {
+                "net.minecraft" => game_version = version,
+                "net.minecraftforge" => {
+                    mod_loader = ModLoader::Forge;
+                    loader_version = version;
+                }
+                "net.fabricmc.fabric-loader" => {
+                    mod_loader = ModLoader::Fabric;
+                    loader_version = version;
+                }
+                "org.quiltmc.quilt-loader" => {
+                    mod_loader = ModLoader::Quilt;
+                    loader_version = version;
+                }
+                _ => {}
+            }
+        }
+
+        let game_version = game_version.ok_or_else(|| {
+            crate::ErrorKind::InputError(
+                "Instance did not specify a Minecraft version".to_string(),
+            )
+        })?;
+
+        Ok((game_version, mod_loader, loader_version))
+    }
+}
+
+/// The subset of an instance's settings (instance.cfg) that maps to a profile
+#[derive(Debug, Default)]
+pub struct MMCInstance {
+    pub name: Option<String>,
+    pub icon_key: Option<String>,
+
+    pub override_memory: bool,
+    pub max_mem_alloc: Option<u32>,
+
+    pub override_java_args: bool,
+    pub jvm_args: Option<String>,
+
+    pub override_window: bool,
+    pub minecraft_win_width: Option<u16>,
+    pub minecraft_win_height: Option<u16>,
+}
+
+impl MMCInstance {
+    /// Parses an instance.cfg file
+    /// These are Qt INI files- MultiMC instances have no sections, Prism
+    /// instances place everything under [General]
+    pub fn parse(cfg: &str) -> Self {
+        let values = cfg
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| {
+                !line.is_empty()
+                    && !line.starts_with('[')
+                    && !line.starts_with(';')
+                    && !line.starts_with('#')
+            })
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| {
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|x| x.strip_suffix('"'))
+                    .unwrap_or(value);
+
+                (key.trim().to_string(), value.to_string())
+            })
+            .collect::<HashMap<String, String>>();
+
+        let get_bool =
+            |key: &str| values.get(key).map(|x| x == "true").unwrap_or(false);
+
+        Self {
+            name: values.get("name").cloned(),
+            icon_key: values.get("iconKey").cloned(),
+            override_memory: get_bool("OverrideMemory"),
+            max_mem_alloc: values
+                .get("MaxMemAlloc")
+                .and_then(|x| x.parse().ok()),
+            override_java_args: get_bool("OverrideJavaArgs"),
+            jvm_args: values.get("JvmArgs").cloned(),
+            override_window: get_bool("OverrideWindow"),
+            minecraft_win_width: values
+                .get("MinecraftWinWidth")
+                .and_then(|x| x.parse().ok()),
+            minecraft_win_height: values
+                .get("MinecraftWinHeight")
+                .and_then(|x| x.parse().ok()),
+        }
+    }
+}
+
+/// Lists the instance folders that can be imported from a MultiMC or
+/// Prism Launcher data directory
+#[tracing::instrument]
+pub async fn get_importable_instances(
+    base_path: PathBuf,
+) -> crate::Result<Vec<String>> {
+    let instances_dir = base_path.join("instances");
+    let mut instances = Vec::new();
+
+    let mut entries = fs::read_dir(&instances_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.join(INSTANCE_CFG).exists() && path.join(MMC_PACK_JSON).exists()
+        {
+            if let Some(name) = path.file_name() {
+                instances.push(name.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    instances.sort();
+    Ok(instances)
+}
+
+/// Imports a MultiMC or Prism Launcher instance as a new profile
+/// base_path is the launcher's data directory, and instance_folder is the
+/// name of the instance's folder within its `instances` directory
+/// Returns the path of the created profile
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn import_mmc(
+    base_path: PathBuf,
+    instance_folder: String,
+) -> crate::Result<PathBuf> {
+    let instance_path = base_path.join("instances").join(&instance_folder);
+
+    let instance_cfg = fs::read_to_string(instance_path.join(INSTANCE_CFG))
+        .await
+        .map_err(|err| {
+            crate::ErrorKind::InputError(format!(
+                "Unable to read {INSTANCE_CFG} of instance {instance_folder}: {err}"
+            ))
+        })?;
+    let instance = MMCInstance::parse(&instance_cfg);
+
+    let mmc_pack = fs::read(instance_path.join(MMC_PACK_JSON)).await?;
+    let mmc_pack = serde_json::from_slice::<MMCPack>(&mmc_pack)?;
+    let (game_version, mod_loader, loader_version) = mmc_pack.get_versions()?;
+
+    let icon = if let Some(ref icon_key) = instance.icon_key {
+        find_icon(&base_path, &instance_path, icon_key).await?
+    } else {
+        None
+    };
+
+    let profile_path = profile_create(
+        instance
+            .name
+            .clone()
+            .unwrap_or_else(|| instance_folder.clone()),
+        game_version,
+        mod_loader,
+        loader_version,
+        icon,
+        None,
+        None,
+        Some(true),
+    )
+    .await?;
+
+    let result = async {
+        crate::api::profile::edit(&profile_path, |prof| {
+            if instance.override_memory {
+                if let Some(maximum) = instance.max_mem_alloc {
+                    prof.memory = Some(MemorySettings { maximum });
+                }
+            }
+
+            if instance.override_java_args {
+                if let Some(ref jvm_args) = instance.jvm_args {
+                    prof.java = Some(JavaSettings {
+                        override_version: None,
+                        extra_arguments: Some(
+                            jvm_args
+                                .split_whitespace()
+                                .map(|x| x.to_string())
+                                .collect(),
+                        ),
+                        custom_env_args: None,
+                    });
+                }
+            }
+
+            if instance.override_window {
+                if let (Some(width), Some(height)) = (
+                    instance.minecraft_win_width,
+                    instance.minecraft_win_height,
+                ) {
+                    prof.resolution = Some(WindowSize(width, height));
+                }
+            }
+
+            async { Ok(()) }
+        })
+        .await?;
+
+        // Older MultiMC instances use `minecraft` rather than `.minecraft`
+        let dotminecraft = [".minecraft", "minecraft"]
+            .iter()
+            .map(|x| instance_path.join(x))
+            .find(|x| x.is_dir());
+        if let Some(dotminecraft) = dotminecraft {
+            super::copy_dotminecraft(&profile_path, &dotminecraft).await?;
+        }
+
+        if let Some(profile) =
+            crate::api::profile::get(&profile_path, None).await?
+        {
+            crate::launcher::install_minecraft(&profile, None).await?;
+        }
+        State::sync().await?;
+
+        Ok::<PathBuf, crate::Error>(profile_path.clone())
+    }
+    .await;
+
+    match result {
+        Ok(profile_path) => Ok(profile_path),
+        Err(err) => {
+            let _ = crate::api::profile::remove(&profile_path).await;
+
+            Err(err)
+        }
+    }
+}
+
+// Icons are stored by key, either in the instance folder or in the
+// launcher's icons directory, with an arbitrary image extension
+async fn find_icon(
+    base_path: &Path,
+    instance_path: &Path,
+    icon_key: &str,
+) -> crate::Result<Option<PathBuf>> {
+    for dir in [instance_path.to_path_buf(), base_path.join("icons")] {
+        if !dir.is_dir() {
+            continue;
+        }
+
+        let mut entries = fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.is_file() && path.file_stem() == Some(OsStr::new(icon_key))
+            {
+                return Ok(Some(path));
+            }
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,132 @@
+//! Importing instances from other launchers
+use std::path::Path;
+
+use crate::event::emit::{
+    emit_loading, init_loading, loading_try_for_each_concurrent,
+};
+use crate::event::LoadingBarType;
+use crate::util::fetch::copy;
+use crate::State;
+
+pub mod minecraft_launcher;
+pub mod mmc;
+
+// Top level entries of a game directory that belong to the launcher rather
+// than the instance. The official launcher keeps these alongside the game
+// files in `.minecraft`, and theseus stores them itself
+const LAUNCHER_OWNED_FILES: [&str; 7] = [
+    "assets",
+    "bin",
+    "libraries",
+    "runtime",
+    "versions",
+    "webcache",
+    "webcache2",
+];
+
+fn is_launcher_owned(relative_path: &Path) -> bool {
+    relative_path
+        .components()
+        .next()
+        .and_then(|x| x.as_os_str().to_str())
+        .map(|x| {
+            LAUNCHER_OWNED_FILES.contains(&x) || x.starts_with("launcher_")
+        })
+        .unwrap_or(false)
+}
+
+/// Copies an instance's game directory (ie: `.minecraft`) into a profile,
+/// then infers the projects that were copied so they show up in the profile
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn copy_dotminecraft(
+    profile_path: &Path,
+    dotminecraft: &Path,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = crate::api::profile::get(profile_path, None)
+        .await?
+        .ok_or_else(|| {
+            crate::ErrorKind::UnmanagedProfileError(
+                profile_path.display().to_string(),
+            )
+        })?;
+
+    let mut path_list = Vec::new();
+    crate::api::profile::build_folder(dotminecraft, &mut path_list).await?;
+    path_list.retain(|path| {
+        path.strip_prefix(dotminecraft)
+            .map(|x| !is_launcher_owned(x))
+            .unwrap_or(false)
+    });
+
+    let loading_bar = init_loading(
+        LoadingBarType::CopyProfile {
+            import_location: dotminecraft.to_path_buf(),
+            profile_name: profile.metadata.name.clone(),
+        },
+        100.0,
+        "Copying instance files",
+    )
+    .await?;
+
+    let num_files = path_list.len();
+    use futures::StreamExt;
+    loading_try_for_each_concurrent(
+        futures::stream::iter(path_list).map(Ok::<_, crate::Error>),
+        None,
+        Some(&loading_bar),
+        80.0,
+        num_files,
+        None,
+        |path| {
+            let state = &state;
+            let profile_base_path = &profile.path;
+            async move {
+                let relative_path = path.strip_prefix(dotminecraft)?;
+                copy(
+                    &path,
+                    &profile_base_path.join(relative_path),
+                    &state.io_semaphore,
+                )
+                .await?;
+
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    emit_loading(&loading_bar, 0.0, Some("Inferring projects")).await?;
+
+    // Project inference requires the Modrinth API, so a failure here
+    // shouldn't abort the import- the file watcher will pick them up later
+    let paths = profile.get_profile_project_paths()?;
+    match crate::state::infer_data_from_files(
+        profile.clone(),
+        paths,
+        state.directories.caches_dir(),
+        &state.io_semaphore,
+        &state.fetch_semaphore,
+    )
+    .await
+    {
+        Ok(projects) => {
+            crate::api::profile::edit(profile_path, |prof| {
+                prof.projects = projects.clone();
+
+                async { Ok(()) }
+            })
+            .await?;
+        }
+        Err(err) => {
+            tracing::warn!(
+                "Unable to infer projects for imported profile: {err}"
+            );
+        }
+    }
+
+    emit_loading(&loading_bar, 20.0, Some("Finished copying instance")).await?;
+
+    Ok(())
+}
@@ -2,16 +2,21 @@ use crate::data::ModLoader;
 use crate::event::emit::{
     emit_loading, init_or_edit_loading, loading_try_for_each_concurrent,
 };
-use crate::event::LoadingBarType;
+use crate::event::{LoadingBarId, LoadingBarType};
 use crate::pack::install_from::{EnvType, PackFile, PackFileHash};
-use crate::state::{LinkedData, ProfileInstallStage, SideType};
+use crate::state::{
+    LinkedData, ProfileInstallStage, ProfileOperation, SideType,
+};
 use crate::util::fetch::{fetch_mirrors, write};
 use crate::State;
 use async_zip::tokio::read::seek::ZipFileReader;
 
 use std::io::Cursor;
-use std::path::{Component, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
+use super::install_curseforge::{
+    install_curseforge_pack, CurseForgeApiResolver,
+};
 use super::install_from::{
     generate_pack_from_file, generate_pack_from_version_id,
     CreatePackDescription, CreatePackLocation, PackDependency, PackFormat,
@@ -22,6 +27,17 @@ pub async fn install_pack(
     location: CreatePackLocation,
     profile: PathBuf,
 ) -> crate::Result<PathBuf> {
+    // Lock the profile before anything is downloaded, so the whole install
+    // (including CurseForge packs) runs alone
+    let _lock = match crate::api::profile::get(&profile, None).await? {
+        Some(prof) => Some(State::get().await?.profile_locks.try_lock(
+            prof.uuid,
+            &prof.metadata.name,
+            ProfileOperation::InstallingPack,
+        )?),
+        None => None,
+    };
+
     // Get file from description
     let description: CreatePackDescription = match location {
         CreatePackLocation::FromVersionId {
@@ -38,6 +54,18 @@ pub async fn install_pack(
         CreatePackLocation::FromFile { path } => {
             generate_pack_from_file(path, profile).await?
         }
+        CreatePackLocation::FromCurseForgeFile { path } => {
+            let resolver = match CurseForgeApiResolver::from_settings().await {
+                Ok(resolver) => resolver,
+                Err(err) => {
+                    let _ = crate::api::profile::remove(&profile).await;
+
+                    return Err(err);
+                }
+            };
+
+            return install_curseforge_pack(path, profile, &resolver).await;
+        }
     };
 
     let file = description.file;
@@ -171,50 +199,7 @@ pub async fn install_pack(
                     |project| {
                         let profile = profile.clone();
                         async move {
-                            //TODO: Future update: prompt user for optional files in a modpack
-                            if let Some(env) = project.env {
-                                if env
-                                    .get(&EnvType::Client)
-                                    .map(|x| x == &SideType::Unsupported)
-                                    .unwrap_or(false)
-                                {
-                                    return Ok(());
-                                }
-                            }
-
-                            let file = fetch_mirrors(
-                                &project
-                                    .downloads
-                                    .iter()
-                                    .map(|x| &**x)
-                                    .collect::<Vec<&str>>(),
-                                project
-                                    .hashes
-                                    .get(&PackFileHash::Sha1)
-                                    .map(|x| &**x),
-                                &state.fetch_semaphore,
-                            )
-                            .await?;
-
-                            let path = std::path::Path::new(&project.path)
-                                .components()
-                                .next();
-                            if let Some(path) = path {
-                                match path {
-                                    Component::CurDir
-                                    | Component::Normal(_) => {
-                                        let path = profile.join(project.path);
-                                        write(
-                                            &path,
-                                            &file,
-                                            &state.io_semaphore,
-                                        )
-                                        .await?;
-                                    }
-                                    _ => {}
-                                };
-                            }
-                            Ok(())
+                            download_pack_file(project, &profile, state).await
                         }
                     },
                 )
@@ -223,66 +208,15 @@ pub async fn install_pack(
                 emit_loading(&loading_bar, 0.0, Some("Extracting overrides"))
                     .await?;
 
-                let mut total_len = 0;
-
-                for index in 0..zip_reader.file().entries().len() {
-                    let file =
-                        zip_reader.file().entries().get(index).unwrap().entry();
-
-                    if (file.filename().starts_with("overrides")
-                        || file.filename().starts_with("client_overrides"))
-                        && !file.filename().ends_with('/')
-                    {
-                        total_len += 1;
-                    }
-                }
-
-                for index in 0..zip_reader.file().entries().len() {
-                    let file = zip_reader
-                        .file()
-                        .entries()
-                        .get(index)
-                        .unwrap()
-                        .entry()
-                        .clone();
-
-                    let file_path = PathBuf::from(file.filename());
-                    if (file.filename().starts_with("overrides")
-                        || file.filename().starts_with("client_overrides"))
-                        && !file.filename().ends_with('/')
-                    {
-                        // Reads the file into the 'content' variable
-                        let mut content = Vec::new();
-                        let mut reader = zip_reader.entry(index).await?;
-                        reader.read_to_end_checked(&mut content, &file).await?;
-
-                        let mut new_path = PathBuf::new();
-                        let components = file_path.components().skip(1);
-
-                        for component in components {
-                            new_path.push(component);
-                        }
-
-                        if new_path.file_name().is_some() {
-                            write(
-                                &profile.join(new_path),
-                                &content,
-                                &state.io_semaphore,
-                            )
-                            .await?;
-                        }
-
-                        emit_loading(
-                            &loading_bar,
-                            30.0 / total_len as f64,
-                            Some(&format!(
-                                "Extracting override {}/{}",
-                                index, total_len
-                            )),
-                        )
-                        .await?;
-                    }
-                }
+                extract_overrides(
+                    &mut zip_reader,
+                    &["overrides", "client_overrides"],
+                    &profile,
+                    &loading_bar,
+                    30.0,
+                    state,
+                )
+                .await?;
 
                 if let Some(profile_val) =
                     crate::api::profile::get(&profile, None).await?
@@ -325,3 +259,110 @@ pub async fn install_pack(
         }
     }
 }
+
+/// Downloads a file of a modpack into a profile, skipping files that aren't
+/// supported on the client
+pub(crate) async fn download_pack_file(
+    project: PackFile,
+    profile: &Path,
+    state: &State,
+) -> crate::Result<()> {
+    //TODO: Future update: prompt user for optional files in a modpack
+    if let Some(env) = project.env {
+        if env
+            .get(&EnvType::Client)
+            .map(|x| x == &SideType::Unsupported)
+            .unwrap_or(false)
+        {
+            return Ok(());
+        }
+    }
+
+    let file = fetch_mirrors(
+        &project
+            .downloads
+            .iter()
+            .map(|x| &**x)
+            .collect::<Vec<&str>>(),
+        project.hashes.get(&PackFileHash::Sha1).map(|x| &**x),
+        &state.fetch_semaphore,
+    )
+    .await?;
+
+    let path = std::path::Path::new(&project.path).components().next();
+    if let Some(path) = path {
+        match path {
+            Component::CurDir | Component::Normal(_) => {
+                let path = profile.join(project.path);
+                write(&path, &file, &state.io_semaphore).await?;
+            }
+            _ => {}
+        };
+    }
+    Ok(())
+}
+
+/// Extracts the files in the given override folders of a modpack zip into a
+/// profile, reporting progress to the loading bar
+pub(crate) async fn extract_overrides(
+    zip_reader: &mut ZipFileReader<Cursor<&bytes::Bytes>>,
+    override_dirs: &[&str],
+    profile: &Path,
+    loading_bar: &LoadingBarId,
+    loading_amount: f64,
+    state: &State,
+) -> crate::Result<()> {
+    let is_override = |filename: &str| {
+        override_dirs.iter().any(|dir| filename.starts_with(dir))
+            && !filename.ends_with('/')
+    };
+
+    let mut total_len = 0;
+
+    for index in 0..zip_reader.file().entries().len() {
+        let file = zip_reader.file().entries().get(index).unwrap().entry();
+
+        if is_override(file.filename()) {
+            total_len += 1;
+        }
+    }
+
+    for index in 0..zip_reader.file().entries().len() {
+        let file = zip_reader
+            .file()
+            .entries()
+            .get(index)
+            .unwrap()
+            .entry()
+            .clone();
+
+        let file_path = PathBuf::from(file.filename());
+        if is_override(file.filename()) {
+            // Reads the file into the 'content' variable
+            let mut content = Vec::new();
+            let mut reader = zip_reader.entry(index).await?;
+            reader.read_to_end_checked(&mut content, &file).await?;
+
+            let mut new_path = PathBuf::new();
+            let components = file_path.components().skip(1);
+
+            for component in components {
+                new_path.push(component);
+            }
+
+            if new_path.file_name().is_some() {
+                write(&profile.join(new_path), &content, &state.io_semaphore)
+                    .await?;
+            }
+
+            emit_loading(
+                loading_bar,
+                loading_amount / total_len as f64,
+                Some(&format!("Extracting override {}/{}", index, total_len)),
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,582 @@
+//! Installing CurseForge modpack zips
+use crate::config::CURSEFORGE_API_URL;
+use crate::data::ModLoader;
+use crate::event::emit::{
+    emit_loading, init_or_edit_loading, loading_try_for_each_concurrent,
+};
+use crate::event::LoadingBarType;
+use crate::pack::install::{download_pack_file, extract_overrides};
+use crate::pack::install_from::{PackFile, PackFileHash};
+use crate::state::ProfileInstallStage;
+use crate::util::fetch::{fetch_advanced, FetchSemaphore};
+use crate::State;
+use async_zip::tokio::read::seek::ZipFileReader;
+use futures::future::BoxFuture;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use std::io::Cursor;
+use std::path::PathBuf;
+use tokio::fs;
+
+const CURSEFORGE_MANIFEST_JSON: &str = "manifest.json";
+
+/// A CurseForge modpack manifest (manifest.json)
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeManifest {
+    pub minecraft: CurseForgeMinecraft,
+    pub manifest_type: String,
+    pub manifest_version: i32,
+    pub name: String,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    pub files: Vec<CurseForgeFile>,
+    pub overrides: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeMinecraft {
+    pub version: String,
+    #[serde(default)]
+    pub mod_loaders: Vec<CurseForgeModLoader>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CurseForgeModLoader {
+    pub id: String, // ie: forge-43.2.0
+    #[serde(default)]
+    pub primary: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CurseForgeFile {
+    #[serde(rename = "projectID")]
+    pub project_id: u32,
+    #[serde(rename = "fileID")]
+    pub file_id: u32,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+impl CurseForgeManifest {
+    /// Gets the mod loader and loader version of the pack's primary loader
+    pub fn get_mod_loader(&self) -> crate::Result<(ModLoader, Option<String>)> {
+        let mod_loader = self
+            .minecraft
+            .mod_loaders
+            .iter()
+            .find(|x| x.primary)
+            .or_else(|| self.minecraft.mod_loaders.first());
+
+        let mod_loader = if let Some(mod_loader) = mod_loader {
+            mod_loader
+        } else {
+            return Ok((ModLoader::Vanilla, None));
+        };
+
+        let (loader, loader_version) =
+            mod_loader.id.split_once('-').ok_or_else(|| {
+                crate::ErrorKind::InputError(format!(
+                    "Invalid mod loader {}",
+                    mod_loader.id
+                ))
+            })?;
+
+        let loader = match loader {
+            "forge" => ModLoader::Forge,
+            "fabric" => ModLoader::Fabric,
+            "quilt" => ModLoader::Quilt,
+            _ => {
+                return Err(crate::ErrorKind::InputError(format!(
+                    "Unsupported mod loader {loader}"
+                ))
+                .into())
+            }
+        };
+
+        Ok((loader, Some(loader_version.to_string())))
+    }
+}
+
+/// Resolves the files of a CurseForge modpack to downloads
+/// The default implementation uses the CurseForge API, but any source that
+/// can map a project and file ID to a download can be used instead
+pub trait CurseForgeResolver: Send + Sync {
+    /// Resolves a file to its download, relative to the profile directory
+    fn resolve(
+        &self,
+        project_id: u32,
+        file_id: u32,
+    ) -> BoxFuture<'_, crate::Result<PackFile>>;
+}
+
+/// Resolves files using the CurseForge API, which requires an API key
+#[derive(Debug, Clone)]
+pub struct CurseForgeApiResolver {
+    pub api_url: String,
+    pub api_key: String,
+}
+
+#[derive(Deserialize)]
+struct CurseForgeApiResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeApiFile {
+    file_name: String,
+    download_url: Option<String>,
+    #[serde(default)]
+    hashes: Vec<CurseForgeApiFileHash>,
+    file_length: u32,
+}
+
+#[derive(Deserialize)]
+struct CurseForgeApiFileHash {
+    value: String,
+    algo: u32, // 1 is sha1, 2 is md5
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeApiProject {
+    class_id: Option<u32>,
+}
+
+impl CurseForgeApiResolver {
+    /// Creates a resolver using the API key in the launcher settings
+    pub async fn from_settings() -> crate::Result<Self> {
+        let state = State::get().await?;
+        let settings = state.settings.read().await;
+
+        let api_key = settings.curseforge_api_key.clone().ok_or_else(|| {
+            crate::ErrorKind::InputError(
+                "Installing CurseForge modpacks requires an API key"
+                    .to_string(),
+            )
+        })?;
+
+        Ok(Self {
+            api_url: CURSEFORGE_API_URL.to_string(),
+            api_key,
+        })
+    }
+
+    async fn fetch<T: serde::de::DeserializeOwned>(
+        &self,
+        route: &str,
+        semaphore: &FetchSemaphore,
+    ) -> crate::Result<T> {
+        let bytes = fetch_advanced(
+            Method::GET,
+            &format!("{}{}", self.api_url, route),
+            None,
+            None,
+            Some(("x-api-key", &self.api_key)),
+            None,
+            semaphore,
+        )
+        .await?;
+
+        Ok(serde_json::from_slice::<CurseForgeApiResponse<T>>(&bytes)?.data)
+    }
+}
+
+impl CurseForgeResolver for CurseForgeApiResolver {
+    fn resolve(
+        &self,
+        project_id: u32,
+        file_id: u32,
+    ) -> BoxFuture<'_, crate::Result<PackFile>> {
+        Box::pin(async move {
+            let state = State::get().await?;
+
+            let file_route = format!("mods/{project_id}/files/{file_id}");
+            let project_route = format!("mods/{project_id}");
+            let (file, project) = tokio::try_join!(
+                self.fetch::<CurseForgeApiFile>(
+                    &file_route,
+                    &state.fetch_semaphore
+                ),
+                self.fetch::<CurseForgeApiProject>(
+                    &project_route,
+                    &state.fetch_semaphore
+                ),
+            )?;
+
+            // Authors can opt out of third party downloads, in which case
+            // the file has to be downloaded from the CurseForge website
+            let download_url = if let Some(url) = file.download_url {
+                url
+            } else {
+                return Err(crate::ErrorKind::InputError(format!(
+                    "{} does not allow downloads outside of CurseForge",
+                    file.file_name
+                ))
+                .into());
+            };
+
+            let folder = match project.class_id {
+                Some(12) => "resourcepacks",
+                Some(6552) => "shaderpacks",
+                _ => "mods",
+            };
+
+            let mut hashes = HashMap::new();
+            if let Some(sha1) = file.hashes.into_iter().find(|x| x.algo == 1) {
+                hashes.insert(PackFileHash::Sha1, sha1.value);
+            }
+
+            Ok(PackFile {
+                path: format!("{folder}/{}", file.file_name),
+                hashes,
+                env: None,
+                downloads: vec![download_url],
+                file_size: file.file_length,
+            })
+        })
+    }
+}
+
+/// Resolves files from a fixed table instead of the CurseForge API, such as
+/// files served by a local mirror
+#[derive(Default, Clone)]
+pub struct CurseForgeTableResolver {
+    files: HashMap<(u32, u32), PackFile>,
+}
+
+impl CurseForgeTableResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the download a file resolves to
+    pub fn insert(&mut self, project_id: u32, file_id: u32, file: PackFile) {
+        self.files.insert((project_id, file_id), file);
+    }
+}
+
+impl CurseForgeResolver for CurseForgeTableResolver {
+    fn resolve(
+        &self,
+        project_id: u32,
+        file_id: u32,
+    ) -> BoxFuture<'_, crate::Result<PackFile>> {
+        Box::pin(async move {
+            self.files
+                .get(&(project_id, file_id))
+                .cloned()
+                .ok_or_else(|| {
+                    crate::ErrorKind::InputError(format!(
+                    "No download for file {file_id} of project {project_id}"
+                ))
+                    .into()
+                })
+        })
+    }
+}
+
+/// Resolves the files of a pack that get installed to their downloads. Only
+/// required files are installed, so a required file that can't be resolved
+/// fails the install, while optional files are never resolved
+pub async fn resolve_pack_files(
+    pack: &CurseForgeManifest,
+    resolver: &dyn CurseForgeResolver,
+) -> crate::Result<Vec<PackFile>> {
+    //TODO: Future update: prompt user for optional files in a modpack
+    futures::future::try_join_all(
+        pack.files
+            .iter()
+            .filter(|x| x.required)
+            .map(|file| resolver.resolve(file.project_id, file.file_id)),
+    )
+    .await
+}
+
+/// Installs a CurseForge modpack zip into an existing profile, resolving
+/// its files with the given resolver
+#[tracing::instrument(skip(resolver))]
+#[theseus_macros::debug_pin]
+pub async fn install_curseforge_pack(
+    path: PathBuf,
+    profile: PathBuf,
+    resolver: &dyn CurseForgeResolver,
+) -> crate::Result<PathBuf> {
+    let state = &State::get().await?;
+
+    let result = async {
+        let file = bytes::Bytes::from(fs::read(&path).await?);
+        let reader: Cursor<&bytes::Bytes> = Cursor::new(&file);
+
+        // Create zip reader around file
+        let mut zip_reader =
+            ZipFileReader::new(reader).await.map_err(|_| {
+                crate::Error::from(crate::ErrorKind::InputError(
+                    "Failed to read input modpack zip".to_string(),
+                ))
+            })?;
+
+        let zip_index = zip_reader
+            .file()
+            .entries()
+            .iter()
+            .position(|f| f.entry().filename() == CURSEFORGE_MANIFEST_JSON)
+            .ok_or_else(|| {
+                crate::ErrorKind::InputError(
+                    "No pack manifest found in CurseForge modpack".to_string(),
+                )
+            })?;
+
+        let mut manifest = String::new();
+        let entry = zip_reader
+            .file()
+            .entries()
+            .get(zip_index)
+            .unwrap()
+            .entry()
+            .clone();
+        let mut reader = zip_reader.entry(zip_index).await?;
+        reader.read_to_string_checked(&mut manifest, &entry).await?;
+
+        let pack: CurseForgeManifest = serde_json::from_str(&manifest)?;
+
+        if &*pack.manifest_type != "minecraftModpack" {
+            return Err(crate::ErrorKind::InputError(
+                "Pack is not a Minecraft modpack".to_string(),
+            )
+            .into());
+        }
+
+        let game_version = pack.minecraft.version.clone();
+        let (mod_loader, loader_version) = pack.get_mod_loader()?;
+
+        let loader_version = if mod_loader != ModLoader::Vanilla {
+            crate::profile_create::get_loader_version_from_loader(
+                game_version.clone(),
+                mod_loader,
+                loader_version,
+            )
+            .await?
+        } else {
+            None
+        };
+        crate::api::profile::edit(&profile, |prof| {
+            prof.metadata.name = pack.name.clone();
+            prof.install_stage = ProfileInstallStage::PackInstalling;
+            prof.metadata.linked_data = None;
+            prof.metadata.game_version = game_version.clone();
+            prof.metadata.loader_version = loader_version.clone();
+            prof.metadata.loader = mod_loader;
+
+            async { Ok(()) }
+        })
+        .await?;
+
+        let loading_bar = init_or_edit_loading(
+            None,
+            LoadingBarType::PackDownload {
+                profile_path: profile.clone(),
+                pack_name: pack.name.clone(),
+                icon: None,
+                pack_id: None,
+                pack_version: pack.version.clone(),
+            },
+            100.0,
+            "Downloading modpack",
+        )
+        .await?;
+
+        let files = resolve_pack_files(&pack, resolver).await?;
+
+        let num_files = files.len();
+        use futures::StreamExt;
+        loading_try_for_each_concurrent(
+            futures::stream::iter(files.into_iter())
+                .map(Ok::<PackFile, crate::Error>),
+            None,
+            Some(&loading_bar),
+            70.0,
+            num_files,
+            None,
+            |file| {
+                let profile = profile.clone();
+                async move { download_pack_file(file, &profile, state).await }
+            },
+        )
+        .await?;
+
+        emit_loading(&loading_bar, 0.0, Some("Extracting overrides")).await?;
+
+        extract_overrides(
+            &mut zip_reader,
+            &[pack.overrides.as_deref().unwrap_or("overrides")],
+            &profile,
+            &loading_bar,
+            30.0,
+            state,
+        )
+        .await?;
+
+        if let Some(profile_val) =
+            crate::api::profile::get(&profile, None).await?
+        {
+            crate::launcher::install_minecraft(&profile_val, Some(loading_bar))
+                .await?;
+
+            State::sync().await?;
+        }
+
+        Ok::<PathBuf, crate::Error>(profile.clone())
+    }
+    .await;
+
+    match result {
+        Ok(profile) => Ok(profile),
+        Err(err) => {
+            let _ = crate::api::profile::remove(&profile).await;
+
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{
+        "minecraft": {
+            "version": "1.19.2",
+            "modLoaders": [
+                { "id": "fabric-0.14.21", "primary": false },
+                { "id": "forge-43.2.0", "primary": true }
+            ]
+        },
+        "manifestType": "minecraftModpack",
+        "manifestVersion": 1,
+        "name": "Test Pack",
+        "version": "1.0.0",
+        "author": "someone",
+        "files": [
+            { "projectID": 238222, "fileID": 4371807, "required": true },
+            { "projectID": 32274, "fileID": 3980000 }
+        ],
+        "overrides": "overrides"
+    }"#;
+
+    fn pack_file(path: &str) -> PackFile {
+        PackFile {
+            path: path.to_string(),
+            hashes: HashMap::new(),
+            env: None,
+            downloads: vec![format!("http://localhost/{path}")],
+            file_size: 0,
+        }
+    }
+
+    #[test]
+    fn parses_manifest() {
+        let manifest: CurseForgeManifest =
+            serde_json::from_str(MANIFEST).unwrap();
+
+        assert_eq!(manifest.minecraft.version, "1.19.2");
+        assert_eq!(manifest.files.len(), 2);
+        assert_eq!(manifest.files[0].project_id, 238222);
+        assert_eq!(manifest.files[0].file_id, 4371807);
+        assert!(manifest.files[1].required);
+    }
+
+    #[test]
+    fn primary_mod_loader() {
+        let manifest: CurseForgeManifest =
+            serde_json::from_str(MANIFEST).unwrap();
+
+        let (loader, version) = manifest.get_mod_loader().unwrap();
+        assert_eq!(loader, ModLoader::Forge);
+        assert_eq!(version.as_deref(), Some("43.2.0"));
+    }
+
+    #[test]
+    fn vanilla_without_mod_loaders() {
+        let mut manifest: CurseForgeManifest =
+            serde_json::from_str(MANIFEST).unwrap();
+        manifest.minecraft.mod_loaders.clear();
+
+        let (loader, version) = manifest.get_mod_loader().unwrap();
+        assert_eq!(loader, ModLoader::Vanilla);
+        assert_eq!(version, None);
+    }
+
+    #[test]
+    fn unsupported_mod_loader() {
+        let mut manifest: CurseForgeManifest =
+            serde_json::from_str(MANIFEST).unwrap();
+        manifest.minecraft.mod_loaders = vec![CurseForgeModLoader {
+            id: "rift-1.0".to_string(),
+            primary: true,
+        }];
+
+        assert!(manifest.get_mod_loader().is_err());
+    }
+
+    #[tokio::test]
+    async fn table_resolver() {
+        let mut resolver = CurseForgeTableResolver::new();
+        resolver.insert(238222, 4371807, pack_file("mods/jei.jar"));
+
+        let file = resolver.resolve(238222, 4371807).await.unwrap();
+        assert_eq!(file, pack_file("mods/jei.jar"));
+        assert!(resolver.resolve(238222, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn resolves_required_files() {
+        let mut manifest: CurseForgeManifest =
+            serde_json::from_str(MANIFEST).unwrap();
+        let mut resolver = CurseForgeTableResolver::new();
+        resolver.insert(238222, 4371807, pack_file("mods/jei.jar"));
+        resolver.insert(32274, 3980000, pack_file("mods/journeymap.jar"));
+
+        let files = resolve_pack_files(&manifest, &resolver).await.unwrap();
+        assert_eq!(
+            files,
+            vec![pack_file("mods/jei.jar"), pack_file("mods/journeymap.jar")]
+        );
+
+        // Optional files aren't installed, even when they can be resolved
+        manifest.files[1].required = false;
+        let files = resolve_pack_files(&manifest, &resolver).await.unwrap();
+        assert_eq!(files, vec![pack_file("mods/jei.jar")]);
+    }
+
+    #[tokio::test]
+    async fn missing_optional_file() {
+        let mut manifest: CurseForgeManifest =
+            serde_json::from_str(MANIFEST).unwrap();
+        manifest.files[1].required = false;
+        let mut resolver = CurseForgeTableResolver::new();
+        resolver.insert(238222, 4371807, pack_file("mods/jei.jar"));
+
+        let files = resolve_pack_files(&manifest, &resolver).await.unwrap();
+        assert_eq!(files, vec![pack_file("mods/jei.jar")]);
+    }
+
+    #[tokio::test]
+    async fn missing_required_file() {
+        let manifest: CurseForgeManifest =
+            serde_json::from_str(MANIFEST).unwrap();
+        let mut resolver = CurseForgeTableResolver::new();
+        resolver.insert(238222, 4371807, pack_file("mods/jei.jar"));
+
+        assert!(resolve_pack_files(&manifest, &resolver).await.is_err());
+    }
+}
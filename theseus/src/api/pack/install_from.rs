@@ -27,7 +27,7 @@ pub struct PackFormat {
     pub dependencies: HashMap<PackDependency, String>,
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PackFile {
     pub path: String,
@@ -37,7 +37,7 @@ pub struct PackFile {
     pub file_size: u32,
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 #[serde(rename_all = "camelCase", from = "String")]
 pub enum PackFileHash {
     Sha1,
@@ -55,7 +55,7 @@ impl From<String> for PackFileHash {
     }
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum EnvType {
     Client,
@@ -83,6 +83,9 @@ pub enum CreatePackLocation {
     FromFile {
         path: PathBuf,
     },
+    FromCurseForgeFile {
+        path: PathBuf,
+    },
 }
 
 #[derive(Serialize, Deserialize)]
@@ -130,7 +133,8 @@ pub fn get_profile_from_pack(
             }),
             skip_install_profile: Some(true),
         },
-        CreatePackLocation::FromFile { path } => {
+        CreatePackLocation::FromFile { path }
+        | CreatePackLocation::FromCurseForgeFile { path } => {
             let file_name = path
                 .file_name()
                 .unwrap_or_default()
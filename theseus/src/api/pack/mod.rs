@@ -1,2 +1,4 @@
+pub mod import;
 pub mod install;
+pub mod install_curseforge;
 pub mod install_from;
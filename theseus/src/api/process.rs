@@ -51,6 +51,14 @@ pub async fn get_all_running_profile_paths() -> crate::Result<Vec<PathBuf>> {
     children.running_profile_paths().await
 }
 
+// Gets the Profile UUIDs of each *running* stored process in the state
+#[tracing::instrument]
+pub async fn get_all_running_profile_uuids() -> crate::Result<Vec<Uuid>> {
+    let state = State::get().await?;
+    let children = state.children.read().await;
+    children.running_profile_uuids().await
+}
+
 // Gets the Profiles (cloned) of each *running* stored process in the state
 #[tracing::instrument]
 pub async fn get_all_running_profiles() -> crate::Result<Vec<Profile>> {
@@ -63,10 +71,23 @@ pub async fn get_all_running_profiles() -> crate::Result<Vec<Profile>> {
 #[tracing::instrument]
 pub async fn get_uuids_by_profile_path(
     profile_path: &Path,
+) -> crate::Result<Vec<Uuid>> {
+    let profile_uuid = crate::api::profile::get_uuid(profile_path).await?;
+    if let Some(profile_uuid) = profile_uuid {
+        get_uuids_by_profile_uuid(&profile_uuid).await
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+// Gets the UUID of each stored process in the state by profile UUID
+#[tracing::instrument]
+pub async fn get_uuids_by_profile_uuid(
+    profile_uuid: &Uuid,
 ) -> crate::Result<Vec<Uuid>> {
     let state = State::get().await?;
     let children = state.children.read().await;
-    children.running_keys_with_profile(profile_path).await
+    children.running_keys_with_profile(profile_uuid).await
 }
 
 // Gets output of a child process stored in the state by UUID, as a string
@@ -1,4 +1,5 @@
 //! Theseus profile management interface
+use crate::config::MODRINTH_API_URL;
 use crate::event::emit::{
     emit_loading, init_loading, loading_try_for_each_concurrent,
 };
@@ -6,21 +7,39 @@ use crate::event::LoadingBarType;
 use crate::pack::install_from::{
     EnvType, PackDependency, PackFile, PackFileHash, PackFormat,
 };
-use crate::prelude::JavaVersion;
-use crate::state::ProjectMetadata;
+use crate::prelude::{JavaVersion, ModLoader};
+use crate::state::{
+    is_profile_json, is_shared_link, loaders_support, read_sessions,
+    validate_shared_folder, BrokenProfile, Dependency, DependencyType,
+    GameOptions, ModDependency, ModrinthVersion, OptionCategory, Playtime,
+    ProfileInstallStage, ProfileMetadata, ProfileOperation, Project,
+    ProjectMetadata, ProjectType, Session,
+};
+use crate::util::fetch::fetch_json;
+use crate::util::fetch::{copy, link_or_copy};
 
 use crate::{
     auth::{self, refresh},
-    event::{emit::emit_profile, ProfilePayloadType},
+    event::{
+        emit::{emit_profile, emit_profiles},
+        ProfilePayloadType,
+    },
     state::MinecraftChild,
 };
 pub use crate::{
+    launcher::{
+        verify::{FileMismatch, VerifiedFileKind, VerifyReport},
+        LaunchOptions, QuickPlay,
+    },
     state::{JavaSettings, Profile},
     State,
 };
 use async_zip::tokio::write::ZipFileWriter;
 use async_zip::{Compression, ZipEntryBuilder};
-use std::collections::HashMap;
+use daedalus::modded::LoaderVersion;
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::{
     future::Future,
     path::{Path, PathBuf},
@@ -29,9 +48,9 @@ use std::{
 use tokio::io::AsyncReadExt;
 use tokio::{
     fs::{self, File},
-    process::Command,
     sync::RwLock,
 };
+use uuid::Uuid;
 
 /// Remove a profile
 #[tracing::instrument]
@@ -52,6 +71,41 @@ pub async fn remove(path: &Path) -> crate::Result<()> {
     Ok(())
 }
 
+/// Remove a profile by UUID
+#[tracing::instrument]
+pub async fn remove_by_uuid(uuid: &Uuid) -> crate::Result<()> {
+    let state = State::get().await?;
+    let mut profiles = state.profiles.write().await;
+
+    if let Some(profile) = profiles.remove_by_uuid(uuid).await? {
+        emit_profile(
+            profile.uuid,
+            profile.path.clone(),
+            &profile.metadata.name,
+            ProfilePayloadType::Removed,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Gets the UUID of the profile at a path, if it is managed
+#[tracing::instrument]
+pub async fn get_uuid(path: &Path) -> crate::Result<Option<Uuid>> {
+    let state = State::get().await?;
+    let profiles = state.profiles.read().await;
+    Ok(profiles.get_uuid(path))
+}
+
+/// Gets the path of a profile by UUID
+#[tracing::instrument]
+pub async fn get_path_by_uuid(uuid: &Uuid) -> crate::Result<Option<PathBuf>> {
+    let state = State::get().await?;
+    let profiles = state.profiles.read().await;
+    Ok(profiles.get_by_uuid(uuid).map(|x| x.path.clone()))
+}
+
 /// Get a profile by path,
 #[tracing::instrument]
 pub async fn get(
@@ -60,7 +114,26 @@ pub async fn get(
 ) -> crate::Result<Option<Profile>> {
     let state = State::get().await?;
     let profiles = state.profiles.read().await;
-    let mut profile = profiles.0.get(path).cloned();
+    let mut profile = profiles.get(path).cloned();
+
+    if clear_projects.unwrap_or(false) {
+        if let Some(profile) = &mut profile {
+            profile.projects = HashMap::new();
+        }
+    }
+
+    Ok(profile)
+}
+
+/// Get a profile by UUID
+#[tracing::instrument]
+pub async fn get_by_uuid(
+    uuid: &Uuid,
+    clear_projects: Option<bool>,
+) -> crate::Result<Option<Profile>> {
+    let state = State::get().await?;
+    let profiles = state.profiles.read().await;
+    let mut profile = profiles.get_by_uuid(uuid).cloned();
 
     if clear_projects.unwrap_or(false) {
         if let Some(profile) = &mut profile {
@@ -76,13 +149,28 @@ pub async fn edit<Fut>(
     path: &Path,
     action: impl Fn(&mut Profile) -> Fut,
 ) -> crate::Result<()>
+where
+    Fut: Future<Output = crate::Result<()>>,
+{
+    let uuid = get_uuid(path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+
+    edit_by_uuid(&uuid, action).await
+}
+
+/// Edit a profile by UUID using a given asynchronous closure
+pub async fn edit_by_uuid<Fut>(
+    uuid: &Uuid,
+    action: impl Fn(&mut Profile) -> Fut,
+) -> crate::Result<()>
 where
     Fut: Future<Output = crate::Result<()>>,
 {
     let state = State::get().await?;
     let mut profiles = state.profiles.write().await;
 
-    match profiles.0.get_mut(path) {
+    match profiles.get_mut_by_uuid(uuid) {
         Some(ref mut profile) => {
             action(profile).await?;
 
@@ -96,10 +184,8 @@ where
 
             Ok(())
         }
-        None => Err(crate::ErrorKind::UnmanagedProfileError(
-            path.display().to_string(),
-        )
-        .as_error()),
+        None => Err(crate::ErrorKind::UnmanagedProfileError(uuid.to_string())
+            .as_error()),
     }
 }
 
@@ -115,7 +201,7 @@ pub async fn edit_icon(
 
         let mut profiles = state.profiles.write().await;
 
-        match profiles.0.get_mut(path) {
+        match profiles.get_mut(path) {
             Some(ref mut profile) => {
                 profile
                     .set_icon(
@@ -154,6 +240,167 @@ pub async fn edit_icon(
     }
 }
 
+/// Duplicates a profile under a new name
+/// Configs, saves and other files are copied, while projects are hard linked
+/// to the original files where possible, as they are never edited in place
+/// Returns the path of the new profile
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn duplicate(
+    path: &Path,
+    new_name: String,
+) -> crate::Result<PathBuf> {
+    let state = State::get().await?;
+    let source = get(path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+
+    let uuid = Uuid::new_v4();
+    let new_path = state.directories.profiles_dir().join(uuid.to_string());
+    fs::create_dir_all(&new_path).await?;
+    let new_path = dunce::canonicalize(&new_path)?;
+
+    let result = async {
+        let mut profile = Profile::new(
+            uuid,
+            new_name,
+            source.metadata.game_version.clone(),
+            new_path.clone(),
+        )
+        .await?;
+        // A copy of a profile that is still installing is installed again
+        // when launched, rather than copying its unfinished install state
+        profile.install_stage =
+            if source.install_stage == ProfileInstallStage::Installed {
+                ProfileInstallStage::Installed
+            } else {
+                ProfileInstallStage::NotInstalled
+            };
+        profile.metadata = ProfileMetadata {
+            name: profile.metadata.name,
+            date_created: profile.metadata.date_created,
+            date_modified: profile.metadata.date_modified,
+            last_played: None,
+            ..source.metadata.clone()
+        };
+        profile.java = source.java.clone();
+        profile.memory = source.memory;
+        profile.resolution = source.resolution;
+        profile.hooks = source.hooks.clone();
+        profile.shared_folders = source.shared_folders.clone();
+
+        // Identical projects are found by their stored hash rather than
+        // hashing every file again
+        let mut project_files: HashMap<&str, &Path> = HashMap::new();
+        for (project_path, project) in &source.projects {
+            project_files
+                .entry(&project.sha512)
+                .or_insert(project_path.as_path());
+        }
+
+        // Shared folders are linked rather than copied, once the profile is
+        // added below
+        let mut shared_links = Vec::new();
+        let mut entries = fs::read_dir(&source.path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if is_shared_link(&state.directories, &entry.path()) {
+                shared_links.push(entry.path());
+            }
+        }
+
+        let mut path_list = Vec::new();
+        build_folder(&source.path, &mut path_list).await?;
+        let logs_dir = state.directories.profile_logs_dir(source.uuid);
+        path_list.retain(|x| {
+            !x.starts_with(&logs_dir)
+                && !shared_links.iter().any(|link| x.starts_with(link))
+                && !x
+                    .strip_prefix(&source.path)
+                    .map(is_profile_json)
+                    .unwrap_or(false)
+        });
+
+        let loading_bar = init_loading(
+            LoadingBarType::CopyProfile {
+                import_location: source.path.clone(),
+                profile_name: profile.metadata.name.clone(),
+            },
+            100.0,
+            "Duplicating profile",
+        )
+        .await?;
+
+        let num_files = path_list.len();
+        use futures::StreamExt;
+        loading_try_for_each_concurrent(
+            futures::stream::iter(path_list).map(Ok::<_, crate::Error>),
+            None,
+            Some(&loading_bar),
+            100.0,
+            num_files,
+            None,
+            |file_path| {
+                let state = &state;
+                let source = &source;
+                let project_files = &project_files;
+                let new_path = &new_path;
+                async move {
+                    let dest =
+                        new_path.join(file_path.strip_prefix(&source.path)?);
+
+                    let linked = source
+                        .projects
+                        .get(&file_path)
+                        .and_then(|x| project_files.get(&*x.sha512));
+                    if let Some(linked) = linked {
+                        link_or_copy(linked, &dest, &state.io_semaphore)
+                            .await?;
+                    } else {
+                        copy(&file_path, &dest, &state.io_semaphore).await?;
+                    }
+
+                    Ok(())
+                }
+            },
+        )
+        .await?;
+
+        for (project_path, project) in &source.projects {
+            if let Ok(relative_path) = project_path.strip_prefix(&source.path) {
+                profile
+                    .projects
+                    .insert(new_path.join(relative_path), project.clone());
+            }
+        }
+
+        emit_profile(
+            uuid,
+            new_path.clone(),
+            &profile.metadata.name,
+            ProfilePayloadType::Created,
+        )
+        .await?;
+
+        {
+            let mut profiles = state.profiles.write().await;
+            profiles.insert(profile).await?;
+        }
+        State::sync().await?;
+
+        Ok::<PathBuf, crate::Error>(new_path.clone())
+    }
+    .await;
+
+    match result {
+        Ok(new_path) => Ok(new_path),
+        Err(err) => {
+            let _ = remove(&new_path).await;
+
+            Err(err)
+        }
+    }
+}
+
 // Gets the optimal JRE key for the given profile, using Daedalus
 // Generally this would be used for profile_create, to get the optimal JRE key
 // this can be overwritten by the user a profile-by-profile basis
@@ -211,23 +458,416 @@ pub async fn list(
     let state = State::get().await?;
     let profiles = state.profiles.read().await;
     Ok(profiles
-        .0
-        .clone()
-        .into_iter()
+        .values()
+        .cloned()
+        .map(|mut x| {
+            if clear_projects.unwrap_or(false) {
+                x.projects = HashMap::new();
+            }
+
+            (x.path.clone(), x)
+        })
+        .collect())
+}
+
+/// Get a copy of the profile set, keyed by UUID
+#[tracing::instrument]
+pub async fn list_by_uuid(
+    clear_projects: Option<bool>,
+) -> crate::Result<HashMap<Uuid, Profile>> {
+    let state = State::get().await?;
+    let profiles = state.profiles.read().await;
+    Ok(profiles
+        .values()
+        .cloned()
         .map(|mut x| {
             if clear_projects.unwrap_or(false) {
-                x.1.projects = HashMap::new();
+                x.projects = HashMap::new();
             }
 
-            x
+            (x.uuid, x)
         })
         .collect())
 }
 
+/// Get a copy of the profiles in a group
+#[tracing::instrument]
+pub async fn list_by_group(
+    group: &str,
+    clear_projects: Option<bool>,
+) -> crate::Result<HashMap<PathBuf, Profile>> {
+    let mut profiles = list(clear_projects).await?;
+    profiles.retain(|_, x| x.metadata.groups.iter().any(|x| x == group));
+    Ok(profiles)
+}
+
+/// Lists every group used by a profile, with the number of profiles in it
+#[tracing::instrument]
+pub async fn list_groups() -> crate::Result<HashMap<String, usize>> {
+    let state = State::get().await?;
+    let profiles = state.profiles.read().await;
+
+    let mut groups = HashMap::new();
+    for profile in profiles.values() {
+        for group in &profile.metadata.groups {
+            *groups.entry(group.clone()).or_insert(0) += 1;
+        }
+    }
+
+    Ok(groups)
+}
+
+/// Renames a group in every profile
+/// Profiles already in a group with the new name are left in it once
+#[tracing::instrument]
+pub async fn rename_group(old_name: &str, new_name: &str) -> crate::Result<()> {
+    let new_name = validate_group_name(new_name)?;
+
+    edit_groups(None, |groups| {
+        if !groups.iter().any(|x| x == old_name) {
+            return false;
+        }
+
+        let mut renamed = Vec::with_capacity(groups.len());
+        for group in groups.drain(..) {
+            let group = if group == old_name {
+                new_name.clone()
+            } else {
+                group
+            };
+
+            if !renamed.contains(&group) {
+                renamed.push(group);
+            }
+        }
+        *groups = renamed;
+
+        true
+    })
+    .await
+}
+
+/// Removes a group from every profile. The profiles themselves are kept
+#[tracing::instrument]
+pub async fn remove_group(name: &str) -> crate::Result<()> {
+    edit_groups(None, |groups| {
+        let len = groups.len();
+        groups.retain(|x| x != name);
+        groups.len() != len
+    })
+    .await
+}
+
+/// Adds profiles to a group
+#[tracing::instrument]
+pub async fn add_to_group(paths: &[PathBuf], group: &str) -> crate::Result<()> {
+    let group = validate_group_name(group)?;
+
+    edit_groups(Some(paths), |groups| {
+        if groups.contains(&group) {
+            return false;
+        }
+
+        groups.push(group.clone());
+        true
+    })
+    .await
+}
+
+/// Removes profiles from a group
+#[tracing::instrument]
+pub async fn remove_from_group(
+    paths: &[PathBuf],
+    group: &str,
+) -> crate::Result<()> {
+    edit_groups(Some(paths), |groups| {
+        let len = groups.len();
+        groups.retain(|x| x != group);
+        groups.len() != len
+    })
+    .await
+}
+
+fn validate_group_name(name: &str) -> crate::Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(crate::ErrorKind::InputError(
+            "Group names cannot be empty".to_string(),
+        )
+        .into());
+    }
+
+    Ok(name.to_string())
+}
+
+// Edits the groups of the given profiles, or of every profile if none are
+// given, under a single lock so the change is applied to all of them or none
+// action returns whether it changed the groups. A single Edited event is sent
+// once every profile has been changed and saved
+async fn edit_groups(
+    paths: Option<&[PathBuf]>,
+    action: impl Fn(&mut Vec<String>) -> bool,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+
+    // The groups each edited profile had before, to undo the edit if it
+    // can't be saved
+    let previous = {
+        let mut profiles = state.profiles.write().await;
+
+        let uuids = if let Some(paths) = paths {
+            let mut uuids = Vec::with_capacity(paths.len());
+            for path in paths {
+                uuids.push(profiles.get_uuid(path).ok_or_else(|| {
+                    crate::ErrorKind::UnmanagedProfileError(
+                        path.display().to_string(),
+                    )
+                })?);
+            }
+            Some(uuids)
+        } else {
+            None
+        };
+
+        let mut previous = Vec::new();
+        for profile in profiles.values_mut() {
+            if let Some(ref uuids) = uuids {
+                if !uuids.contains(&profile.uuid) {
+                    continue;
+                }
+            }
+
+            let groups = profile.metadata.groups.clone();
+            if action(&mut profile.metadata.groups) {
+                previous.push((
+                    profile.uuid,
+                    groups,
+                    profile.metadata.date_modified,
+                ));
+                profile.metadata.date_modified = chrono::Utc::now();
+            }
+        }
+
+        previous
+    };
+
+    if previous.is_empty() {
+        return Ok(());
+    }
+
+    if let Err(err) = State::sync().await {
+        let mut profiles = state.profiles.write().await;
+        for (uuid, groups, date_modified) in previous {
+            if let Some(profile) = profiles.get_mut_by_uuid(&uuid) {
+                profile.metadata.groups = groups;
+                profile.metadata.date_modified = date_modified;
+            }
+        }
+
+        return Err(err);
+    }
+
+    emit_profiles(
+        previous.into_iter().map(|(uuid, _, _)| uuid).collect(),
+        ProfilePayloadType::Edited,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Gets the recorded play sessions of a profile, oldest first
+#[tracing::instrument]
+pub async fn get_sessions(path: &Path) -> crate::Result<Vec<Session>> {
+    let state = State::get().await?;
+    let uuid = get_uuid(path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+
+    read_sessions(&state.directories, uuid).await
+}
+
+/// Gets the total playtime of a profile, the playtime of the last 7 days and
+/// the playtime of each account
+#[tracing::instrument]
+pub async fn get_playtime(path: &Path) -> crate::Result<Playtime> {
+    Ok(Playtime::from_sessions(&get_sessions(path).await?))
+}
+
+/// Gets the operation currently running on a profile, if any
+#[tracing::instrument]
+pub async fn get_operation(
+    path: &Path,
+) -> crate::Result<Option<ProfileOperation>> {
+    let state = State::get().await?;
+    let uuid = get_uuid(path).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+
+    Ok(state.profile_locks.get_operation(uuid))
+}
+
+/// Sets the top-level folders a profile shares with other profiles, or
+/// `None` to use the ones from the settings. Newly shared folders have their
+/// content moved to the shared folder
+#[tracing::instrument]
+pub async fn set_shared_folders(
+    path: &Path,
+    shared_folders: Option<Vec<String>>,
+) -> crate::Result<()> {
+    for folder in shared_folders.iter().flatten() {
+        validate_shared_folder(folder)?;
+    }
+
+    edit(path, |prof| {
+        prof.shared_folders = shared_folders.clone();
+        prof.metadata.date_modified = chrono::Utc::now();
+
+        async { Ok(()) }
+    })
+    .await?;
+
+    let profile = get(path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+    let shared = {
+        let state = State::get().await?;
+        let settings = state.settings.read().await;
+        profile.shared_folders(&settings).to_vec()
+    };
+    profile.apply_shared_folders(&shared).await?;
+
+    State::sync().await?;
+    Ok(())
+}
+
+/// Gets the game options of a profile from its options.txt
+#[tracing::instrument]
+pub async fn get_options(
+    path: &Path,
+) -> crate::Result<HashMap<String, String>> {
+    let profile = get(path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+
+    Ok(GameOptions::read(&profile.path).await?.to_map())
+}
+
+/// Sets game options of a profile. Options that aren't given are kept
+#[tracing::instrument]
+pub async fn set_options(
+    path: &Path,
+    options: HashMap<String, String>,
+) -> crate::Result<()> {
+    let profile = get(path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+    check_options_not_running(&profile).await?;
+
+    let mut game_options = GameOptions::read(&profile.path).await?;
+    for (key, value) in &options {
+        game_options.set(key, value);
+    }
+    game_options.write(&profile.path).await
+}
+
+/// Copies the game options in the given categories from one profile to
+/// another, leaving the other options of the destination as they are
+#[tracing::instrument]
+pub async fn copy_options(
+    path: &Path,
+    dest_path: &Path,
+    categories: &[OptionCategory],
+) -> crate::Result<()> {
+    let (profile, dest) =
+        match (get(path, None).await?, get(dest_path, None).await?) {
+            (Some(profile), Some(dest)) => (profile, dest),
+            (None, _) => {
+                return Err(crate::ErrorKind::UnmanagedProfileError(
+                    path.display().to_string(),
+                )
+                .into())
+            }
+            (_, None) => {
+                return Err(crate::ErrorKind::UnmanagedProfileError(
+                    dest_path.display().to_string(),
+                )
+                .into())
+            }
+        };
+    check_options_not_running(&dest).await?;
+
+    let source = GameOptions::read(&profile.path).await?;
+    let mut game_options = GameOptions::read(&dest.path).await?;
+    game_options.merge_categories(&source, categories);
+    game_options.write(&dest.path).await
+}
+
+// Minecraft writes options.txt whenever an option changes in game, which
+// would undo edits made while it's running
+async fn check_options_not_running(profile: &Profile) -> crate::Result<()> {
+    let state = State::get().await?;
+    if state
+        .children
+        .read()
+        .await
+        .running_profile_uuids()
+        .await?
+        .contains(&profile.uuid)
+    {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Cannot change the options of {} while it is running",
+            profile.metadata.name
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Lists the profiles that failed to load
+#[tracing::instrument]
+pub async fn list_broken() -> crate::Result<Vec<BrokenProfile>> {
+    let state = State::get().await?;
+    let profiles = state.profiles.read().await;
+    Ok(profiles.broken.clone())
+}
+
+/// Tries to load a broken profile again, returning its path if it loaded
+#[tracing::instrument]
+pub async fn retry_broken(name: &str) -> crate::Result<Option<PathBuf>> {
+    let state = State::get().await?;
+    let profile = {
+        let mut profiles = state.profiles.write().await;
+        profiles.retry_broken(&state.directories, name).await?
+    };
+
+    if let Some(ref profile) = profile {
+        State::sync().await?;
+        Profile::sync_projects_task(profile.path.clone());
+    }
+
+    Ok(profile.map(|x| x.path))
+}
+
+/// Deletes a broken profile
+#[tracing::instrument]
+pub async fn remove_broken(name: &str) -> crate::Result<()> {
+    let state = State::get().await?;
+    let mut profiles = state.profiles.write().await;
+    profiles.remove_broken(name).await
+}
+
 /// Installs/Repairs a profile
 #[tracing::instrument]
 pub async fn install(path: &Path) -> crate::Result<()> {
     if let Some(profile) = get(path, None).await? {
+        let state = State::get().await?;
+        let _lock = state.profile_locks.try_lock(
+            profile.uuid,
+            &profile.metadata.name,
+            ProfileOperation::Installing,
+        )?;
         crate::launcher::install_minecraft(&profile, None).await?;
     } else {
         return Err(crate::ErrorKind::UnmanagedProfileError(
@@ -239,12 +879,64 @@ pub async fn install(path: &Path) -> crate::Result<()> {
     Ok(())
 }
 
+/// Checks the game files of an installed profile against their hashes.
+/// Files that are missing or don't match are reported, and downloaded again
+/// if `repair` is set
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn verify(path: &Path, repair: bool) -> crate::Result<VerifyReport> {
+    let state = State::get().await?;
+    let profile = get(path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+            .as_error()
+    })?;
+
+    if profile.install_stage != ProfileInstallStage::Installed {
+        return Err(crate::ErrorKind::InputError(format!(
+            "{} is not installed, install it instead",
+            profile.metadata.name
+        ))
+        .into());
+    }
+
+    let _lock = state.profile_locks.try_lock(
+        profile.uuid,
+        &profile.metadata.name,
+        ProfileOperation::Verifying,
+    )?;
+
+    if repair
+        && state
+            .children
+            .read()
+            .await
+            .running_profile_uuids()
+            .await?
+            .contains(&profile.uuid)
+    {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Cannot repair {} while it is running",
+            profile.metadata.name
+        ))
+        .into());
+    }
+
+    crate::launcher::verify_minecraft(&profile, repair).await
+}
+
 #[tracing::instrument]
 #[theseus_macros::debug_pin]
 pub async fn update_all(
     profile_path: &Path,
 ) -> crate::Result<HashMap<PathBuf, PathBuf>> {
     if let Some(profile) = get(profile_path, None).await? {
+        let state = State::get().await?;
+        let _lock = state.profile_locks.try_lock(
+            profile.uuid,
+            &profile.metadata.name,
+            ProfileOperation::Updating,
+        )?;
+
         let loading_bar = init_loading(
             LoadingBarType::ProfileUpdate {
                 profile_path: profile.path.clone(),
@@ -285,9 +977,12 @@ pub async fn update_all(
                 let map = map.clone();
 
                 async move {
-                    let new_path =
-                        update_project(profile_path, &project, Some(true))
-                            .await?;
+                    let new_path = update_project_unlocked(
+                        profile_path,
+                        &project,
+                        Some(true),
+                    )
+                    .await?;
 
                     map.write().await.insert(project, new_path);
 
@@ -322,6 +1017,30 @@ pub async fn update_project(
     profile_path: &Path,
     project_path: &Path,
     skip_send_event: Option<bool>,
+) -> crate::Result<PathBuf> {
+    if let Some(profile) = get(profile_path, None).await? {
+        let state = State::get().await?;
+        let _lock = state.profile_locks.try_lock(
+            profile.uuid,
+            &profile.metadata.name,
+            ProfileOperation::Updating,
+        )?;
+
+        update_project_unlocked(profile_path, project_path, skip_send_event)
+            .await
+    } else {
+        Err(crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+        .as_error())
+    }
+}
+
+// Updates a project of a profile the caller has already locked
+async fn update_project_unlocked(
+    profile_path: &Path,
+    project_path: &Path,
+    skip_send_event: Option<bool>,
 ) -> crate::Result<PathBuf> {
     if let Some(profile) = get(profile_path, None).await? {
         if let Some(project) = profile.projects.get(project_path) {
@@ -338,46 +1057,356 @@ pub async fn update_project(
                     profile.remove_project(project_path, Some(true)).await?;
                 }
 
-                let state = State::get().await?;
-                let mut profiles = state.profiles.write().await;
-                if let Some(profile) = profiles.0.get_mut(project_path) {
-                    let value = profile.projects.remove(project_path);
-                    if let Some(mut project) = value {
-                        if let ProjectMetadata::Modrinth {
-                            ref mut version,
-                            ..
-                        } = project.metadata
-                        {
-                            *version = Box::new(new_version);
-                        }
-                        profile.projects.insert(path.clone(), project);
-                    }
-                }
+                let state = State::get().await?;
+                let mut profiles = state.profiles.write().await;
+                if let Some(profile) = profiles.get_mut_by_uuid(&profile.uuid) {
+                    let value = profile.projects.remove(project_path);
+                    if let Some(mut project) = value {
+                        if let ProjectMetadata::Modrinth {
+                            ref mut version,
+                            ..
+                        } = project.metadata
+                        {
+                            *version = Box::new(new_version);
+                        }
+                        profile.projects.insert(path.clone(), project);
+                    }
+                }
+
+                if !skip_send_event.unwrap_or(false) {
+                    emit_profile(
+                        profile.uuid,
+                        profile.path,
+                        &profile.metadata.name,
+                        ProfilePayloadType::Edited,
+                    )
+                    .await?;
+                    State::sync().await?;
+                }
+
+                return Ok(path);
+            }
+        }
+
+        Err(crate::ErrorKind::InputError(
+            "This project cannot be updated!".to_string(),
+        )
+        .as_error())
+    } else {
+        Err(crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+        .as_error())
+    }
+}
+
+/// A plan for moving a profile to a different game version or mod loader
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MigrationPlan {
+    pub profile_path: PathBuf,
+    pub game_version: String,
+    pub loader: ModLoader,
+    pub loader_version: Option<LoaderVersion>,
+    /// Projects that will be swapped for a compatible version
+    pub upgrades: Vec<ProjectMigration>,
+    /// Projects whose current version is already compatible
+    pub unchanged: Vec<PathBuf>,
+    /// Modrinth projects with no compatible version. These are left as is
+    pub no_compatible_version: Vec<PathBuf>,
+    /// Projects that aren't from Modrinth, so can't be checked
+    pub unknown: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProjectMigration {
+    pub path: PathBuf,
+    pub title: String,
+    pub from: Box<ModrinthVersion>,
+    pub to: Box<ModrinthVersion>,
+}
+
+/// Plans moving a profile to a different game version and/or mod loader
+/// Looks up the newest version of every Modrinth project that supports the
+/// new game version and loader. Nothing is changed until the plan is passed
+/// to apply_migration
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn migrate(
+    path: &Path,
+    game_version: String,
+    loader: ModLoader,
+    loader_version: Option<String>,
+) -> crate::Result<MigrationPlan> {
+    let state = State::get().await?;
+    let profile = get(path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(path.display().to_string())
+    })?;
+
+    let loader_version = if loader != ModLoader::Vanilla {
+        crate::profile_create::get_loader_version_from_loader(
+            game_version.clone(),
+            loader,
+            loader_version,
+        )
+        .await?
+    } else {
+        None
+    };
+
+    let mut plan = MigrationPlan {
+        profile_path: profile.path.clone(),
+        game_version: game_version.clone(),
+        loader,
+        loader_version,
+        upgrades: Vec::new(),
+        unchanged: Vec::new(),
+        no_compatible_version: Vec::new(),
+        unknown: Vec::new(),
+    };
+
+    // Mods need to support the new loader, but resource packs, shaders and
+    // data packs keep the loaders they already have. Projects are grouped by
+    // the loaders to search with, so each group is a single request
+    let mut requests: HashMap<Vec<String>, Vec<(&PathBuf, &Project)>> =
+        HashMap::new();
+    for (project_path, project) in &profile.projects {
+        if let ProjectMetadata::Modrinth { version, .. } = &project.metadata {
+            let loaders =
+                match ProjectType::get_from_loaders(version.loaders.clone()) {
+                    Some(ProjectType::Mod) => {
+                        vec![loader.as_api_str().to_string()]
+                    }
+                    _ => version.loaders.clone(),
+                };
+
+            requests
+                .entry(loaders)
+                .or_default()
+                .push((project_path, project));
+        } else {
+            plan.unknown.push(project_path.clone());
+        }
+    }
+
+    let updates_url = format!("{MODRINTH_API_URL}version_files/update");
+    let responses = futures::future::try_join_all(requests.iter().map(
+        |(loaders, projects)| {
+            fetch_json::<HashMap<String, ModrinthVersion>>(
+                Method::POST,
+                &updates_url,
+                None,
+                Some(serde_json::json!({
+                    "hashes": projects
+                        .iter()
+                        .map(|(_, x)| &x.sha512)
+                        .collect::<Vec<_>>(),
+                    "algorithm": "sha512",
+                    "loaders": loaders,
+                    "game_versions": [game_version],
+                })),
+                &state.fetch_semaphore,
+            )
+        },
+    ))
+    .await?;
+
+    for ((_, projects), versions) in requests.iter().zip(responses) {
+        for (project_path, project) in projects {
+            let (title, version) = if let ProjectMetadata::Modrinth {
+                project: modrinth_project,
+                version,
+                ..
+            } = &project.metadata
+            {
+                (&modrinth_project.title, version)
+            } else {
+                continue;
+            };
+
+            match versions.get(&project.sha512) {
+                Some(new_version) if new_version.id == version.id => {
+                    plan.unchanged.push((*project_path).clone())
+                }
+                Some(new_version) => plan.upgrades.push(ProjectMigration {
+                    path: (*project_path).clone(),
+                    title: title.clone(),
+                    from: version.clone(),
+                    to: Box::new(new_version.clone()),
+                }),
+                None => {
+                    plan.no_compatible_version.push((*project_path).clone())
+                }
+            }
+        }
+    }
+
+    Ok(plan)
+}
+
+/// Applies a plan from migrate, swapping each upgraded project's file for
+/// its compatible version and reinstalling Minecraft for the new version.
+/// If a file can't be downloaded or swapped, the profile is left on its old
+/// version with its old files
+#[tracing::instrument(skip(plan))]
+#[theseus_macros::debug_pin]
+pub async fn apply_migration(plan: MigrationPlan) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = get(&plan.profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            plan.profile_path.display().to_string(),
+        )
+    })?;
+
+    if state
+        .children
+        .read()
+        .await
+        .running_profile_uuids()
+        .await?
+        .contains(&profile.uuid)
+    {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Cannot migrate {} while it is running",
+            profile.metadata.name
+        ))
+        .into());
+    }
+
+    let _lock = state.profile_locks.try_lock(
+        profile.uuid,
+        &profile.metadata.name,
+        ProfileOperation::Migrating,
+    )?;
+
+    // The plan may be stale if a project was removed since
+    let upgrades = plan
+        .upgrades
+        .iter()
+        .filter_map(|upgrade| {
+            profile.projects.get(&upgrade.path).map(|x| (upgrade, x))
+        })
+        .collect::<Vec<_>>();
+
+    // Every new file is downloaded before anything is changed, so a failed
+    // download leaves the profile as it was
+    let files = futures::future::try_join_all(
+        upgrades
+            .iter()
+            .map(|(upgrade, _)| Profile::fetch_version_file(&upgrade.to)),
+    )
+    .await?;
+
+    let mut swapped = Vec::new();
+    let swap_result = async {
+        for ((upgrade, project), bytes) in upgrades.iter().zip(files) {
+            let old_bytes = fs::read(&upgrade.path).await?;
+            let new_path = profile
+                .add_project_version_bytes(&upgrade.to, bytes)
+                .await?;
+            swapped.push(SwappedProject {
+                old_path: upgrade.path.clone(),
+                old_bytes,
+                old_project: (*project).clone(),
+                new_path: new_path.clone(),
+            });
+
+            if new_path != upgrade.path {
+                profile.remove_project(&upgrade.path, None).await?;
+            }
+            if project.disabled {
+                let disabled_path =
+                    profile.toggle_disable_project(&new_path).await?;
+                if let Some(swapped) = swapped.last_mut() {
+                    swapped.new_path = disabled_path;
+                }
+            }
+        }
+
+        Ok::<(), crate::Error>(())
+    }
+    .await;
+    if let Err(err) = swap_result {
+        restore_swapped_projects(&profile, swapped).await;
+        return Err(err);
+    }
+
+    // The profile only moves to the new version once its files have been
+    // swapped
+    edit(&profile.path, |prof| {
+        prof.metadata.game_version = plan.game_version.clone();
+        prof.metadata.loader = plan.loader;
+        prof.metadata.loader_version = plan.loader_version.clone();
+        prof.metadata.date_modified = chrono::Utc::now();
+
+        async { Ok(()) }
+    })
+    .await?;
+
+    if let Some(profile) = get(&plan.profile_path, None).await? {
+        crate::launcher::install_minecraft(&profile, None).await?;
+
+        emit_profile(
+            profile.uuid,
+            profile.path.clone(),
+            &profile.metadata.name,
+            ProfilePayloadType::Edited,
+        )
+        .await?;
+
+        // Refreshes project metadata for the new game version
+        Profile::sync_projects_task(profile.path);
+    }
+    State::sync().await?;
+
+    Ok(())
+}
+
+// A project file a migration replaced, kept so the swap can be undone
+struct SwappedProject {
+    old_path: PathBuf,
+    old_bytes: Vec<u8>,
+    old_project: Project,
+    new_path: PathBuf,
+}
 
-                if !skip_send_event.unwrap_or(false) {
-                    emit_profile(
-                        profile.uuid,
-                        profile.path,
-                        &profile.metadata.name,
-                        ProfilePayloadType::Edited,
-                    )
-                    .await?;
-                    State::sync().await?;
-                }
+// Puts back the files a migration swapped before it failed, newest first
+async fn restore_swapped_projects(
+    profile: &Profile,
+    swapped: Vec<SwappedProject>,
+) {
+    let state = match State::get().await {
+        Ok(state) => state,
+        Err(err) => {
+            tracing::warn!("Unable to undo migration: {err}");
+            return;
+        }
+    };
 
-                return Ok(path);
+    for swapped in swapped.into_iter().rev() {
+        if swapped.new_path != swapped.old_path {
+            if let Err(err) = fs::remove_file(&swapped.new_path).await {
+                tracing::warn!(
+                    "Unable to remove {}: {err}",
+                    swapped.new_path.display()
+                );
             }
         }
+        if let Err(err) = fs::write(&swapped.old_path, swapped.old_bytes).await
+        {
+            tracing::warn!(
+                "Unable to restore {}: {err}",
+                swapped.old_path.display()
+            );
+            continue;
+        }
 
-        Err(crate::ErrorKind::InputError(
-            "This project cannot be updated!".to_string(),
-        )
-        .as_error())
-    } else {
-        Err(crate::ErrorKind::UnmanagedProfileError(
-            profile_path.display().to_string(),
-        )
-        .as_error())
+        let mut profiles = state.profiles.write().await;
+        if let Some(profile) = profiles.get_mut_by_uuid(&profile.uuid) {
+            profile.projects.remove(&swapped.new_path);
+            profile
+                .projects
+                .insert(swapped.old_path, swapped.old_project);
+        }
     }
 }
 
@@ -408,6 +1437,581 @@ pub async fn add_project_from_version(
     }
 }
 
+/// A version to add to a profile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlannedProject {
+    pub version: Box<ModrinthVersion>,
+    /// The id of the version that requires this one, or None for the version
+    /// that was asked for
+    pub required_by: Option<String>,
+}
+
+/// Everything adding a project to a profile installs, with its required
+/// dependencies
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AddProjectPlan {
+    pub profile_path: PathBuf,
+    /// The version that was asked for first, then its dependencies
+    pub projects: Vec<PlannedProject>,
+    /// Project ids of dependencies the profile already has
+    pub already_installed: Vec<String>,
+    /// Dependencies with no version for the profile's game version and loader
+    pub unresolved: Vec<Dependency>,
+}
+
+// Finds the version a dependency points to. Dependencies on a whole project
+// get its newest version for the profile's game version and loader
+async fn resolve_dependency(
+    profile: &Profile,
+    dependent: &ModrinthVersion,
+    dependency: &Dependency,
+) -> crate::Result<Option<ModrinthVersion>> {
+    let state = State::get().await?;
+
+    if let Some(version_id) = &dependency.version_id {
+        return Ok(Some(
+            fetch_json(
+                Method::GET,
+                &format!("{MODRINTH_API_URL}version/{version_id}"),
+                None,
+                None,
+                &state.fetch_semaphore,
+            )
+            .await?,
+        ));
+    }
+
+    let project_id = match &dependency.project_id {
+        Some(project_id) => project_id,
+        None => return Ok(None),
+    };
+
+    // Mods need the profile's loader, other projects share the loaders of
+    // the project that needs them
+    let loaders = match ProjectType::get_from_loaders(dependent.loaders.clone())
+    {
+        Some(ProjectType::Mod) => {
+            vec![profile.metadata.loader.as_api_str().to_string()]
+        }
+        _ => dependent.loaders.clone(),
+    };
+    let versions = fetch_json::<Vec<ModrinthVersion>>(
+        Method::GET,
+        &format!(
+            "{MODRINTH_API_URL}project/{project_id}/version?loaders={}&game_versions={}",
+            serde_json::to_string(&loaders)?,
+            serde_json::to_string(&[&profile.metadata.game_version])?,
+        ),
+        None,
+        None,
+        &state.fetch_semaphore,
+    )
+    .await?;
+
+    Ok(versions.into_iter().next())
+}
+
+/// Plans adding a version to a profile with its required dependencies,
+/// resolved recursively. Dependencies the profile already has, by file hash
+/// or by project, are skipped. Nothing is downloaded until the plan is passed
+/// to apply_add_project
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn plan_add_project_from_version(
+    profile_path: &Path,
+    version_id: String,
+) -> crate::Result<AddProjectPlan> {
+    let state = State::get().await?;
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+    })?;
+
+    let version = fetch_json::<ModrinthVersion>(
+        Method::GET,
+        &format!("{MODRINTH_API_URL}version/{version_id}"),
+        None,
+        None,
+        &state.fetch_semaphore,
+    )
+    .await?;
+
+    let installed_hashes = profile
+        .projects
+        .values()
+        .map(|x| x.sha512.clone())
+        .collect::<HashSet<_>>();
+    let installed_projects = profile
+        .projects
+        .values()
+        .filter_map(|x| match &x.metadata {
+            ProjectMetadata::Modrinth { project, .. } => {
+                Some(project.id.clone())
+            }
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+
+    let mut plan = AddProjectPlan {
+        profile_path: profile.path.clone(),
+        projects: Vec::new(),
+        already_installed: Vec::new(),
+        unresolved: Vec::new(),
+    };
+    let mut planned_projects = HashSet::from([version.project_id.clone()]);
+
+    // Dependencies are resolved breadth first, so each project is planned
+    // once however many projects need it
+    let mut queue = VecDeque::from([(version, None)]);
+    while let Some((version, required_by)) = queue.pop_front() {
+        for dependency in &version.dependencies {
+            if !matches!(dependency.dependency_type, DependencyType::Required) {
+                continue;
+            }
+
+            if let Some(project_id) = &dependency.project_id {
+                if installed_projects.contains(project_id) {
+                    plan.already_installed.push(project_id.clone());
+                    continue;
+                }
+                if planned_projects.contains(project_id) {
+                    continue;
+                }
+            }
+
+            let resolved =
+                match resolve_dependency(&profile, &version, dependency).await?
+                {
+                    Some(resolved) => resolved,
+                    None => {
+                        plan.unresolved.push(dependency.clone());
+                        continue;
+                    }
+                };
+
+            let installed = installed_projects.contains(&resolved.project_id)
+                || resolved
+                    .primary_file()
+                    .and_then(|x| x.hashes.get("sha512"))
+                    .is_some_and(|x| installed_hashes.contains(x));
+            if installed {
+                plan.already_installed.push(resolved.project_id.clone());
+            } else if planned_projects.insert(resolved.project_id.clone()) {
+                queue.push_back((resolved, Some(version.id.clone())));
+            }
+        }
+
+        plan.projects.push(PlannedProject {
+            version: Box::new(version),
+            required_by,
+        });
+    }
+
+    plan.already_installed.sort();
+    plan.already_installed.dedup();
+
+    Ok(plan)
+}
+
+/// Downloads every project of a plan from plan_add_project_from_version into
+/// its profile, returning the paths of the added files
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn apply_add_project(
+    plan: AddProjectPlan,
+) -> crate::Result<Vec<PathBuf>> {
+    let profile = get(&plan.profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            plan.profile_path.display().to_string(),
+        )
+    })?;
+
+    // Every file is downloaded before any is added, so a failed download
+    // leaves the profile as it was
+    let files = futures::future::try_join_all(plan.projects.iter().map(
+        |project| async move {
+            let bytes = Profile::fetch_version_file(&project.version).await?;
+            Ok::<_, crate::Error>((&project.version, bytes))
+        },
+    ))
+    .await?;
+
+    let mut paths = Vec::new();
+    for (version, bytes) in files {
+        match profile.add_project_version_bytes(version, bytes).await {
+            Ok(path) => paths.push(path),
+            Err(err) => {
+                remove_added_projects(&profile, &paths).await;
+                return Err(err);
+            }
+        }
+    }
+
+    emit_profile(
+        profile.uuid,
+        profile.path,
+        &profile.metadata.name,
+        ProfilePayloadType::Edited,
+    )
+    .await?;
+    State::sync().await?;
+
+    Ok(paths)
+}
+
+// Removes the files added to a profile by a plan that failed part way
+async fn remove_added_projects(profile: &Profile, paths: &[PathBuf]) {
+    for path in paths {
+        if let Err(err) = fs::remove_file(path).await {
+            tracing::warn!("Unable to remove {}: {err}", path.display());
+        }
+    }
+
+    if let Ok(state) = State::get().await {
+        let mut profiles = state.profiles.write().await;
+        if let Some(profile) = profiles.get_mut_by_uuid(&profile.uuid) {
+            for path in paths {
+                profile.projects.remove(path);
+            }
+        }
+    }
+}
+
+/// A problem with a profile's projects
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthIssue {
+    /// A required dependency isn't in the profile
+    MissingDependency {
+        path: PathBuf,
+        dependency: Dependency,
+    },
+    /// A required dependency is in the profile, but disabled
+    DisabledDependency {
+        path: PathBuf,
+        dependency_path: PathBuf,
+    },
+    /// One of the projects declares it doesn't work with the other
+    Incompatible { path: PathBuf, other_path: PathBuf },
+    /// The project's version doesn't list the profile's loader
+    UnsupportedLoader { path: PathBuf, loaders: Vec<String> },
+    /// The project's version doesn't list the profile's game version
+    UnsupportedGameVersion {
+        path: PathBuf,
+        game_versions: Vec<String>,
+    },
+    /// A mod the file's metadata needs isn't in the profile. Used for
+    /// projects that aren't from Modrinth
+    MissingModDependency {
+        path: PathBuf,
+        dependency: ModDependency,
+    },
+    /// Several enabled files ship the same mod id, whether they're from
+    /// Modrinth or not. Files without mod ids go by their Modrinth project
+    DuplicateMod { id: String, paths: Vec<PathBuf> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HealthReport {
+    pub profile_path: PathBuf,
+    pub issues: Vec<HealthIssue>,
+}
+
+/// Checks a profile's projects for unmet or disabled required dependencies,
+/// incompatible pairs, versions that don't support the profile's loader or
+/// game version, and mods shipped by more than one file. Projects that
+/// aren't from Modrinth are checked with the metadata read from their jars.
+/// Disabled projects aren't loaded, so only their use as a dependency is
+/// checked
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn check_health(profile_path: &Path) -> crate::Result<HealthReport> {
+    let state = State::get().await?;
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+    })?;
+
+    let mut by_project: HashMap<&str, Vec<(&PathBuf, &Project)>> =
+        HashMap::new();
+    let mut by_version = HashMap::new();
+    let mut by_file_name = HashMap::new();
+    // Every mod id each file ships, including those of its nested jars
+    let mut provided: HashMap<String, Vec<(&PathBuf, &Project)>> =
+        HashMap::new();
+    for (path, project) in &profile.projects {
+        let file_name = project
+            .file_name
+            .strip_suffix(".disabled")
+            .unwrap_or(&project.file_name);
+        by_file_name.insert(file_name, (path, project));
+
+        if let ProjectMetadata::Modrinth { version, .. } = &project.metadata {
+            by_project
+                .entry(&*version.project_id)
+                .or_default()
+                .push((path, project));
+            by_version.insert(&*version.id, (path, project));
+        }
+
+        let (ids, nested_ids) = match file_mod_ids(&project.metadata) {
+            Some(ids) => ids,
+            None => continue,
+        };
+        for id in ids.into_iter().chain(nested_ids) {
+            let found = provided.entry(id).or_default();
+            if !found.iter().any(|(x, _)| *x == path) {
+                found.push((path, project));
+            }
+        }
+    }
+
+    let versions = profile
+        .projects
+        .values()
+        .filter(|x| !x.disabled)
+        .filter_map(|x| match &x.metadata {
+            ProjectMetadata::Modrinth { version, .. } => Some(version),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Dependencies on a version the profile doesn't have may still be met by
+    // another version of the same project, so those versions' projects are
+    // looked up
+    let unknown_versions = versions
+        .iter()
+        .flat_map(|x| &x.dependencies)
+        .filter(|x| x.project_id.is_none())
+        .filter_map(|x| x.version_id.as_deref())
+        .filter(|x| !by_version.contains_key(x))
+        .collect::<HashSet<_>>();
+    let version_projects = if unknown_versions.is_empty() {
+        HashMap::new()
+    } else {
+        fetch_json::<Vec<ModrinthVersion>>(
+            Method::GET,
+            &format!(
+                "{MODRINTH_API_URL}versions?ids={}",
+                serde_json::to_string(&unknown_versions)?
+            ),
+            None,
+            None,
+            &state.fetch_semaphore,
+        )
+        .await?
+        .into_iter()
+        .map(|x| (x.id, x.project_id))
+        .collect::<HashMap<_, _>>()
+    };
+
+    // Finds the projects of the profile that meet a dependency
+    let find_dependency = |dependency: &Dependency| {
+        if let Some(project_id) = &dependency.project_id {
+            by_project.get(&**project_id).cloned().unwrap_or_default()
+        } else if let Some(version_id) = &dependency.version_id {
+            match by_version.get(&**version_id) {
+                Some(project) => vec![*project],
+                None => version_projects
+                    .get(version_id)
+                    .and_then(|x| by_project.get(&**x))
+                    .cloned()
+                    .unwrap_or_default(),
+            }
+        } else if let Some(file_name) = &dependency.file_name {
+            by_file_name
+                .get(&**file_name)
+                .into_iter()
+                .copied()
+                .collect()
+        } else {
+            Vec::new()
+        }
+    };
+
+    let mut issues = Vec::new();
+    let mut incompatible_pairs = HashSet::new();
+    for (path, project) in &profile.projects {
+        let version = match &project.metadata {
+            ProjectMetadata::Modrinth { version, .. } if !project.disabled => {
+                version
+            }
+            // Projects that aren't from Modrinth are checked with what their
+            // jars declare
+            ProjectMetadata::Inferred {
+                loaders,
+                dependencies,
+                ..
+            } if !project.disabled => {
+                if !loaders.is_empty()
+                    && !loaders_support(loaders, profile.metadata.loader)
+                {
+                    issues.push(HealthIssue::UnsupportedLoader {
+                        path: path.clone(),
+                        loaders: loaders.clone(),
+                    });
+                }
+
+                // Jars made for several loaders only need the dependencies
+                // of the profile's loader
+                let needed = dependencies.iter().filter(|x| {
+                    x.mandatory
+                        && match &x.loader {
+                            Some(loader) => loaders_support(
+                                std::slice::from_ref(loader),
+                                profile.metadata.loader,
+                            ),
+                            None => true,
+                        }
+                });
+                for dependency in needed {
+                    match provided.get(&dependency.mod_id) {
+                        None => {
+                            issues.push(HealthIssue::MissingModDependency {
+                                path: path.clone(),
+                                dependency: dependency.clone(),
+                            })
+                        }
+                        Some(found)
+                            if found.iter().all(|(_, x)| x.disabled) =>
+                        {
+                            issues.push(HealthIssue::DisabledDependency {
+                                path: path.clone(),
+                                dependency_path: found[0].0.clone(),
+                            })
+                        }
+                        Some(_) => {}
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        if !version.supports_loader(profile.metadata.loader) {
+            issues.push(HealthIssue::UnsupportedLoader {
+                path: path.clone(),
+                loaders: version.loaders.clone(),
+            });
+        }
+        if !version.supports_game_version(&profile.metadata.game_version) {
+            issues.push(HealthIssue::UnsupportedGameVersion {
+                path: path.clone(),
+                game_versions: version.game_versions.clone(),
+            });
+        }
+
+        for dependency in &version.dependencies {
+            let found = find_dependency(dependency);
+            match dependency.dependency_type {
+                DependencyType::Required => {
+                    if found.is_empty() {
+                        issues.push(HealthIssue::MissingDependency {
+                            path: path.clone(),
+                            dependency: dependency.clone(),
+                        });
+                    } else if found.iter().all(|(_, x)| x.disabled) {
+                        issues.push(HealthIssue::DisabledDependency {
+                            path: path.clone(),
+                            dependency_path: found[0].0.clone(),
+                        });
+                    }
+                }
+                DependencyType::Incompatible => {
+                    for (other_path, other) in found {
+                        // Pairs are reported once, whichever side declares
+                        // them
+                        let pair = if path < other_path {
+                            (path, other_path)
+                        } else {
+                            (other_path, path)
+                        };
+                        if !other.disabled && incompatible_pairs.insert(pair) {
+                            issues.push(HealthIssue::Incompatible {
+                                path: path.clone(),
+                                other_path: other_path.clone(),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    issues.extend(duplicate_mods(&profile.projects));
+
+    Ok(HealthReport {
+        profile_path: profile.path.clone(),
+        issues,
+    })
+}
+
+// Mod ids a file ships itself and those of its nested jars, or None for
+// files that aren't known. Files with no mod ids, like resource packs, go by
+// their Modrinth project instead
+fn file_mod_ids(
+    metadata: &ProjectMetadata,
+) -> Option<(Vec<String>, Vec<String>)> {
+    let (mod_ids, nested_jars) = match metadata {
+        ProjectMetadata::Modrinth {
+            version, mod_ids, ..
+        } if mod_ids.is_empty() => {
+            return Some((vec![version.project_id.clone()], Vec::new()))
+        }
+        ProjectMetadata::Modrinth {
+            mod_ids,
+            nested_jars,
+            ..
+        }
+        | ProjectMetadata::Inferred {
+            mod_ids,
+            nested_jars,
+            ..
+        } => (mod_ids, nested_jars),
+        ProjectMetadata::Unknown => return None,
+    };
+
+    Some((
+        mod_ids.clone(),
+        nested_jars
+            .iter()
+            .flat_map(|x| x.mod_ids.iter().cloned())
+            .collect(),
+    ))
+}
+
+// Enabled files that ship the same mod id, whether they're from Modrinth or
+// not. Nested jars aren't counted, as loaders pick one of those themselves
+fn duplicate_mods(projects: &HashMap<PathBuf, Project>) -> Vec<HealthIssue> {
+    let mut mod_ids: HashMap<String, Vec<&PathBuf>> = HashMap::new();
+    for (path, project) in projects.iter().filter(|(_, x)| !x.disabled) {
+        if let Some((ids, _)) = file_mod_ids(&project.metadata) {
+            for id in ids {
+                mod_ids.entry(id).or_default().push(path);
+            }
+        }
+    }
+
+    let mut duplicates = mod_ids
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .collect::<Vec<_>>();
+    duplicates.sort();
+    duplicates
+        .into_iter()
+        .map(|(id, mut paths)| {
+            paths.sort();
+            HealthIssue::DuplicateMod {
+                id,
+                paths: paths.into_iter().cloned().collect(),
+            }
+        })
+        .collect()
+}
+
 /// Add a project from an FS path
 #[tracing::instrument]
 pub async fn add_project_from_path(
@@ -666,7 +2270,10 @@ pub async fn get_potential_override_folders(
 /// Run Minecraft using a profile and the default credentials, logged in credentials,
 /// failing with an error if no credentials are available
 #[tracing::instrument]
-pub async fn run(path: &Path) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
+pub async fn run(
+    path: &Path,
+    options: &LaunchOptions,
+) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
     let state = State::get().await?;
 
     // Get default account and refresh credentials (preferred way to log in)
@@ -683,7 +2290,34 @@ pub async fn run(path: &Path) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
             return Err(crate::ErrorKind::NoCredentialsError.as_error());
         }
     };
-    run_credentials(path, &credentials).await
+    run_credentials(path, &credentials, options).await
+}
+
+/// Run Minecraft using a profile by UUID and the default credentials
+#[tracing::instrument]
+pub async fn run_by_uuid(
+    uuid: &Uuid,
+    options: &LaunchOptions,
+) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
+    let path = get_path_by_uuid(uuid).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(uuid.to_string())
+    })?;
+
+    run(&path, options).await
+}
+
+/// Run Minecraft using a profile by UUID, and credentials for authentication
+#[tracing::instrument(skip(credentials))]
+pub async fn run_credentials_by_uuid(
+    uuid: &Uuid,
+    credentials: &auth::Credentials,
+    options: &LaunchOptions,
+) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
+    let path = get_path_by_uuid(uuid).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(uuid.to_string())
+    })?;
+
+    run_credentials(&path, credentials, options).await
 }
 
 /// Run Minecraft using a profile, and credentials for authentication
@@ -693,6 +2327,7 @@ pub async fn run(path: &Path) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
 pub async fn run_credentials(
     path: &Path,
     credentials: &auth::Credentials,
+    options: &LaunchOptions,
 ) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
     let state = State::get().await?;
     let settings = state.settings.read().await;
@@ -703,28 +2338,14 @@ pub async fn run_credentials(
         ))
     })?;
 
-    let pre_launch_hooks =
-        &profile.hooks.as_ref().unwrap_or(&settings.hooks).pre_launch;
-    if let Some(hook) = pre_launch_hooks {
-        // TODO: hook parameters
-        let mut cmd = hook.split(' ');
-        if let Some(command) = cmd.next() {
-            let result = Command::new(command)
-                .args(&cmd.collect::<Vec<&str>>())
-                .current_dir(path)
-                .spawn()?
-                .wait()
-                .await?;
+    // Held until Minecraft has been spawned
+    let _lock = state.profile_locks.try_lock(
+        profile.uuid,
+        &profile.metadata.name,
+        ProfileOperation::Launching,
+    )?;
 
-            if !result.success() {
-                return Err(crate::ErrorKind::LauncherError(format!(
-                    "Non-zero exit code for pre-launch hook: {}",
-                    result.code().unwrap_or(-1)
-                ))
-                .as_error());
-            }
-        }
-    }
+    let hooks = profile.hooks.as_ref().unwrap_or(&settings.hooks);
 
     let java_args = profile
         .java
@@ -732,11 +2353,6 @@ pub async fn run_credentials(
         .and_then(|it| it.extra_arguments.as_ref())
         .unwrap_or(&settings.custom_java_args);
 
-    let wrapper = profile
-        .hooks
-        .as_ref()
-        .map_or(&settings.hooks.wrapper, |it| &it.wrapper);
-
     let memory = profile.memory.unwrap_or(settings.memory);
     let resolution = profile.resolution.unwrap_or(settings.game_resolution);
 
@@ -746,32 +2362,15 @@ pub async fn run_credentials(
         .and_then(|x| x.custom_env_args.as_ref())
         .unwrap_or(&settings.custom_env_args);
 
-    // Post post exit hooks
-    let post_exit_hook =
-        &profile.hooks.as_ref().unwrap_or(&settings.hooks).post_exit;
-
-    let post_exit_hook = if let Some(hook) = post_exit_hook {
-        let mut cmd = hook.split(' ');
-        if let Some(command) = cmd.next() {
-            let mut command = Command::new(command);
-            command.args(&cmd.collect::<Vec<&str>>()).current_dir(path);
-            Some(command)
-        } else {
-            None
-        }
-    } else {
-        None
-    };
-
     let mc_process = crate::launcher::launch_minecraft(
         java_args,
         env_args,
-        wrapper,
+        hooks,
         &memory,
         &resolution,
         credentials,
-        post_exit_hook,
         &profile,
+        options,
     )
     .await?;
     Ok(mc_process)
@@ -936,3 +2535,174 @@ pub async fn build_folder(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modrinth_project(
+        project_id: &str,
+        mod_ids: &[&str],
+        disabled: bool,
+    ) -> Project {
+        let project = serde_json::json!({
+            "id": project_id,
+            "slug": null,
+            "project_type": "mod",
+            "team": "team",
+            "title": project_id,
+            "description": "",
+            "body": "",
+            "published": "2023-01-01T00:00:00Z",
+            "updated": "2023-01-01T00:00:00Z",
+            "client_side": "required",
+            "server_side": "optional",
+            "downloads": 0,
+            "followers": 0,
+            "categories": [],
+            "additional_categories": [],
+            "game_versions": ["1.20.1"],
+            "loaders": ["fabric"],
+            "versions": [],
+            "icon_url": null
+        });
+        let version = serde_json::json!({
+            "id": format!("{project_id}-version"),
+            "project_id": project_id,
+            "author_id": "author",
+            "featured": false,
+            "name": "1.0.0",
+            "version_number": "1.0.0",
+            "changelog": "",
+            "changelog_url": null,
+            "date_published": "2023-01-01T00:00:00Z",
+            "downloads": 0,
+            "version_type": "release",
+            "files": [],
+            "dependencies": [],
+            "game_versions": ["1.20.1"],
+            "loaders": ["fabric"]
+        });
+        serde_json::from_value(serde_json::json!({
+            "sha512": "",
+            "disabled": disabled,
+            "file_name": format!("{project_id}.jar"),
+            "metadata": {
+                "type": "modrinth",
+                "project": project,
+                "version": version,
+                "members": [],
+                "update_version": null,
+                "incompatible": false,
+                "mod_ids": mod_ids
+            }
+        }))
+        .unwrap()
+    }
+
+    fn inferred_project(
+        mod_ids: &[&str],
+        nested_mod_ids: &[&str],
+        disabled: bool,
+    ) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "sha512": "",
+            "disabled": disabled,
+            "file_name": "local.jar",
+            "metadata": {
+                "type": "inferred",
+                "title": null,
+                "description": null,
+                "authors": [],
+                "version": null,
+                "icon": null,
+                "project_type": "mod",
+                "mod_ids": mod_ids,
+                "nested_jars": [
+                    { "path": "META-INF/jars/lib.jar", "mod_ids": nested_mod_ids }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn duplicate_ids(projects: Vec<(&str, Project)>) -> Vec<(String, usize)> {
+        let projects = projects
+            .into_iter()
+            .map(|(path, project)| (PathBuf::from(path), project))
+            .collect();
+        duplicate_mods(&projects)
+            .into_iter()
+            .map(|x| match x {
+                HealthIssue::DuplicateMod { id, paths } => (id, paths.len()),
+                other => panic!("unexpected issue {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn modrinth_and_local_builds_of_a_mod() {
+        let duplicates = duplicate_ids(vec![
+            (
+                "mods/sodium.jar",
+                modrinth_project("AANobbMI", &["sodium"], false),
+            ),
+            (
+                "mods/sodium-dev.jar",
+                inferred_project(&["sodium"], &[], false),
+            ),
+        ]);
+        assert_eq!(duplicates, vec![("sodium".to_string(), 2)]);
+    }
+
+    #[test]
+    fn same_project_with_different_mod_ids() {
+        // Versions of one Modrinth project can ship different mods, which
+        // load fine side by side
+        let duplicates = duplicate_ids(vec![
+            (
+                "mods/api.jar",
+                modrinth_project("P7dR8mSH", &["fabric-api"], false),
+            ),
+            (
+                "mods/api-base.jar",
+                modrinth_project("P7dR8mSH", &["fabric-api-base"], false),
+            ),
+        ]);
+        assert!(duplicates.is_empty());
+    }
+
+    #[test]
+    fn files_without_mod_ids_go_by_project() {
+        let duplicates = duplicate_ids(vec![
+            (
+                "resourcepacks/a.zip",
+                modrinth_project("faithful", &[], false),
+            ),
+            (
+                "resourcepacks/b.zip",
+                modrinth_project("faithful", &[], false),
+            ),
+        ]);
+        assert_eq!(duplicates, vec![("faithful".to_string(), 2)]);
+    }
+
+    #[test]
+    fn skips_disabled_and_nested_mods() {
+        let duplicates = duplicate_ids(vec![
+            (
+                "mods/sodium.jar",
+                modrinth_project("AANobbMI", &["sodium"], false),
+            ),
+            (
+                "mods/sodium-dev.jar",
+                inferred_project(&["sodium"], &[], true),
+            ),
+            (
+                "mods/bundle.jar",
+                inferred_project(&["bundle"], &["sodium"], false),
+            ),
+        ]);
+        assert!(duplicates.is_empty());
+    }
+}
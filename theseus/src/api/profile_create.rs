@@ -1,5 +1,5 @@
 //! Theseus profile management interface
-use crate::state::LinkedData;
+use crate::state::{GameOptions, LinkedData, ProfileOperation};
 use crate::{
     event::{emit::emit_profile, ProfilePayloadType},
     prelude::ModLoader,
@@ -99,6 +99,16 @@ pub async fn profile_create(
 
         profile.metadata.linked_data = linked_data;
 
+        let default_options =
+            state.settings.read().await.default_options.clone();
+        if !default_options.is_empty() {
+            let mut options = GameOptions::default();
+            for (key, value) in &default_options {
+                options.set(key, value);
+            }
+            options.write(&path).await?;
+        }
+
         emit_profile(
             uuid,
             path.clone(),
@@ -113,6 +123,11 @@ pub async fn profile_create(
         }
 
         if !skip_install_profile.unwrap_or(false) {
+            let _lock = state.profile_locks.try_lock(
+                uuid,
+                &profile.metadata.name,
+                ProfileOperation::Installing,
+            )?;
             crate::launcher::install_minecraft(&profile, None).await?;
         }
         State::sync().await?;
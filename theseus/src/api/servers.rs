@@ -0,0 +1,242 @@
+//! Theseus profile server lists
+use crate::profile::{get, list_by_group, Profile};
+use crate::util::io::{read_with_backup, write_atomic};
+use crate::util::nbt::{self, Nbt, TAG_COMPOUND};
+use crate::worlds::check_not_running;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const SERVERS_DAT: &str = "servers.dat";
+
+/// An entry of a profile's multiplayer server list
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Server {
+    pub name: String,
+    /// Address of the server, with an optional port
+    pub ip: String,
+    /// The server's icon as a base64 encoded PNG, as Minecraft stores it
+    pub icon: Option<String>,
+    /// Whether the server's resource pack is used. `None` asks the player
+    /// when they join
+    pub accept_textures: Option<bool>,
+    /// Hidden servers are added by Quick Play, and aren't shown in the list
+    #[serde(default)]
+    pub hidden: bool,
+}
+
+impl Server {
+    fn from_nbt(entry: &Nbt) -> Self {
+        let get_bool = |key| entry.get(key).and_then(Nbt::as_i64);
+        Self {
+            name: entry
+                .get("name")
+                .and_then(Nbt::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            ip: entry
+                .get("ip")
+                .and_then(Nbt::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            icon: entry.get("icon").and_then(Nbt::as_str).map(String::from),
+            accept_textures: get_bool("acceptTextures").map(|x| x != 0),
+            hidden: get_bool("hidden").unwrap_or(0) != 0,
+        }
+    }
+
+    // Writes the server into an entry, leaving tags it doesn't know about
+    // untouched. An entry's icon is only replaced by another icon, so
+    // pushing a server without one keeps the icon Minecraft downloaded
+    fn apply_to(&self, entry: &mut Nbt) {
+        entry.insert("name", Nbt::String(self.name.clone()));
+        entry.insert("ip", Nbt::String(self.ip.clone()));
+        if let Some(icon) = &self.icon {
+            entry.insert("icon", Nbt::String(icon.clone()));
+        }
+        match self.accept_textures {
+            Some(accept) => {
+                entry.insert("acceptTextures", Nbt::Byte(accept as i8))
+            }
+            None => entry.remove("acceptTextures"),
+        }
+        if self.hidden {
+            entry.insert("hidden", Nbt::Byte(1));
+        } else {
+            entry.remove("hidden");
+        }
+    }
+}
+
+async fn servers_path(profile_path: &Path) -> crate::Result<PathBuf> {
+    Ok(get_profile(profile_path).await?.path.join(SERVERS_DAT))
+}
+
+// Gets the servers.dat of a profile to change it. Minecraft writes its
+// server list back as it closes, so running profiles can't be changed
+async fn editable_servers_path(profile_path: &Path) -> crate::Result<PathBuf> {
+    let profile = get_profile(profile_path).await?;
+    check_not_running(&profile, "change the server list").await?;
+    Ok(profile.path.join(SERVERS_DAT))
+}
+
+async fn get_profile(profile_path: &Path) -> crate::Result<Profile> {
+    get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+        .as_error()
+    })
+}
+
+// Reads the raw entries of servers.dat, so they can be written back with
+// everything Minecraft stored in them
+async fn read_entries(path: &Path) -> crate::Result<Vec<Nbt>> {
+    // A profile that was never launched has no server list yet
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    read_with_backup(path, |bytes| {
+        let (_, root) = nbt::read(bytes)?;
+        Ok(root
+            .get("servers")
+            .and_then(Nbt::as_list)
+            .map(|x| x.to_vec())
+            .unwrap_or_default())
+    })
+    .await
+}
+
+async fn write_entries(path: &Path, entries: Vec<Nbt>) -> crate::Result<()> {
+    let root = Nbt::Compound(vec![(
+        String::from("servers"),
+        Nbt::List(TAG_COMPOUND, entries),
+    )]);
+    write_atomic(path, &nbt::write("", &root)?).await
+}
+
+fn check_index(
+    entries: &[Nbt],
+    index: usize,
+    inclusive: bool,
+) -> crate::Result<()> {
+    let len = entries.len() + inclusive as usize;
+    if index >= len {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Server index {index} is out of range, the list has {} servers",
+            entries.len()
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Lists the servers of a profile, in the order Minecraft shows them
+#[tracing::instrument]
+pub async fn list(profile_path: &Path) -> crate::Result<Vec<Server>> {
+    let path = servers_path(profile_path).await?;
+    Ok(read_entries(&path)
+        .await?
+        .iter()
+        .map(Server::from_nbt)
+        .collect())
+}
+
+/// Adds a server to a profile, at the given index or else at the end
+#[tracing::instrument]
+pub async fn add(
+    profile_path: &Path,
+    server: Server,
+    index: Option<usize>,
+) -> crate::Result<()> {
+    let path = editable_servers_path(profile_path).await?;
+    let mut entries = read_entries(&path).await?;
+
+    let mut entry = Nbt::Compound(Vec::new());
+    server.apply_to(&mut entry);
+
+    match index {
+        Some(index) => {
+            check_index(&entries, index, true)?;
+            entries.insert(index, entry);
+        }
+        None => entries.push(entry),
+    }
+
+    write_entries(&path, entries).await
+}
+
+/// Removes the server at an index from a profile
+#[tracing::instrument]
+pub async fn remove(profile_path: &Path, index: usize) -> crate::Result<()> {
+    let path = editable_servers_path(profile_path).await?;
+    let mut entries = read_entries(&path).await?;
+    check_index(&entries, index, false)?;
+    entries.remove(index);
+    write_entries(&path, entries).await
+}
+
+/// Moves a server of a profile from one index to another
+#[tracing::instrument]
+pub async fn reorder(
+    profile_path: &Path,
+    from: usize,
+    to: usize,
+) -> crate::Result<()> {
+    let path = editable_servers_path(profile_path).await?;
+    let mut entries = read_entries(&path).await?;
+    check_index(&entries, from, false)?;
+    check_index(&entries, to, false)?;
+
+    let entry = entries.remove(from);
+    entries.insert(to, entry);
+    write_entries(&path, entries).await
+}
+
+/// Pushes a server to several profiles. A profile that already lists a
+/// server with the same address has that entry updated in place, otherwise
+/// the server is added at the end, so pushing again never duplicates it
+#[tracing::instrument]
+pub async fn add_to_profiles(
+    profile_paths: &[PathBuf],
+    server: Server,
+) -> crate::Result<()> {
+    // Every profile is checked first, so a running one stops the push
+    // before any server list is changed
+    let mut paths = Vec::with_capacity(profile_paths.len());
+    for profile_path in profile_paths {
+        paths.push(editable_servers_path(profile_path).await?);
+    }
+
+    for path in paths {
+        let mut entries = read_entries(&path).await?;
+
+        match entries.iter_mut().find(|x| {
+            x.get("ip")
+                .and_then(Nbt::as_str)
+                .is_some_and(|ip| ip.eq_ignore_ascii_case(&server.ip))
+        }) {
+            Some(entry) => server.apply_to(entry),
+            None => {
+                let mut entry = Nbt::Compound(Vec::new());
+                server.apply_to(&mut entry);
+                entries.push(entry);
+            }
+        }
+
+        write_entries(&path, entries).await?;
+    }
+
+    Ok(())
+}
+
+/// Pushes a server to every profile in a group, as add_to_profiles does
+#[tracing::instrument]
+pub async fn add_to_group(group: &str, server: Server) -> crate::Result<()> {
+    let profile_paths = list_by_group(group, Some(true))
+        .await?
+        .into_keys()
+        .collect::<Vec<_>>();
+    add_to_profiles(&profile_paths, server).await
+}
@@ -7,6 +7,8 @@ pub use crate::{
     State,
 };
 
+use crate::state::validate_shared_folder;
+
 /// Gets entire settings
 #[tracing::instrument]
 pub async fn get() -> crate::Result<Settings> {
@@ -19,11 +21,16 @@ pub async fn get() -> crate::Result<Settings> {
 #[tracing::instrument]
 pub async fn set(settings: Settings) -> crate::Result<()> {
     let state = State::get().await?;
-    let (reset_io, reset_fetch) = async {
+    for folder in &settings.shared_folders {
+        validate_shared_folder(folder)?;
+    }
+
+    let (reset_io, reset_fetch, reset_shared) = async {
         let read = state.settings.read().await;
         (
             settings.max_concurrent_writes != read.max_concurrent_writes,
             settings.max_concurrent_downloads != read.max_concurrent_downloads,
+            settings.shared_folders != read.shared_folders,
         )
     }
     .await;
@@ -38,6 +45,21 @@ pub async fn set(settings: Settings) -> crate::Result<()> {
     if reset_fetch {
         state.reset_fetch_semaphore().await;
     }
+    if reset_shared {
+        let shared_folders = state.settings.read().await.shared_folders.clone();
+        // Profiles with their own shared folders aren't affected
+        let profiles = state
+            .profiles
+            .read()
+            .await
+            .values()
+            .filter(|x| x.shared_folders.is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+        for profile in profiles {
+            profile.apply_shared_folders(&shared_folders).await?;
+        }
+    }
 
     State::sync().await?;
     Ok(())
@@ -0,0 +1,688 @@
+//! Theseus profile snapshots
+use crate::event::emit::{emit_loading, emit_profile, init_loading};
+use crate::event::{LoadingBarId, LoadingBarType, ProfilePayloadType};
+use crate::profile::{build_folder, get, Profile};
+use crate::state::{
+    is_profile_json, is_shared_link, read_profile_json, ProfileOperation,
+    PROFILE_JSON_PATH,
+};
+use crate::util::fetch::write;
+use crate::State;
+use async_zip::tokio::read::fs::ZipFileReader;
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Component, Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+const SAVES_FOLDER: &str = "saves";
+const LOGS_FOLDER: &str = "modrinth_logs";
+
+/// A point-in-time archive of a profile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Snapshot {
+    pub id: String,
+    pub profile_uuid: Uuid,
+    pub profile_name: String,
+    pub created: DateTime<Utc>,
+    pub size: u64,
+    // top level folders of the profile left out of the archive, which are
+    // left untouched when restoring in place
+    pub excluded: Vec<String>,
+}
+
+// Whether a snapshot ID names a file directly in the snapshots folder, so
+// IDs from callers or snapshot files can't reach outside it
+fn is_valid_id(id: &str) -> bool {
+    let mut components = Path::new(id).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+fn check_id(id: &str) -> crate::Result<()> {
+    if !is_valid_id(id) {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Invalid snapshot ID: {id}"
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+fn archive_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.zip"))
+}
+
+fn metadata_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+/// Creates a snapshot of a profile's directory and profile.json
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn create(
+    profile_path: &Path,
+    exclude_saves: bool,
+    exclude_logs: bool,
+) -> crate::Result<Snapshot> {
+    let state = State::get().await?;
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+    })?;
+
+    let created = Utc::now();
+    let id = created.timestamp_millis().to_string();
+    let snapshots_dir = state.directories.profile_snapshots_dir(profile.uuid);
+    fs::create_dir_all(&snapshots_dir).await?;
+
+    let mut excluded = Vec::new();
+    if exclude_saves {
+        excluded.push(SAVES_FOLDER.to_string());
+    }
+    if exclude_logs {
+        excluded.push(LOGS_FOLDER.to_string());
+    }
+
+    // Shared folders belong to every profile sharing them, so snapshots
+    // leave them out
+    let mut entries = fs::read_dir(&profile.path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        if is_shared_link(&state.directories, &entry.path()) {
+            excluded.push(entry.file_name().to_string_lossy().to_string());
+        }
+    }
+
+    let archive = archive_path(&snapshots_dir, &id);
+    let result = async {
+        let io_semaphore = state.io_semaphore.0.read().await;
+        let _permit = io_semaphore.acquire().await?;
+
+        let mut path_list = Vec::new();
+        build_folder(&profile.path, &mut path_list).await?;
+
+        let loading_bar = init_loading(
+            LoadingBarType::SnapshotCreate {
+                profile_path: profile.path.clone(),
+                profile_name: profile.metadata.name.clone(),
+            },
+            path_list.len() as f64 + 1.0,
+            "Creating snapshot",
+        )
+        .await?;
+
+        let mut file = File::create(&archive).await?;
+        let mut writer = ZipFileWriter::new(&mut file);
+
+        for path in path_list {
+            emit_loading(&loading_bar, 1.0, None).await?;
+
+            let relative_path = path.strip_prefix(&profile.path)?;
+            let top_level = relative_path
+                .iter()
+                .next()
+                .map(|x| x.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            // profile.json is written from the loaded profile below, as the
+            // one on disk may be out of date
+            if excluded.contains(&top_level) || is_profile_json(relative_path) {
+                continue;
+            }
+
+            let relative_path =
+                relative_path.to_string_lossy().replace('\\', "/");
+
+            let mut data = Vec::new();
+            File::open(&path).await?.read_to_end(&mut data).await?;
+            let builder =
+                ZipEntryBuilder::new(relative_path, Compression::Deflate);
+            writer.write_entry_whole(builder, &data).await?;
+        }
+
+        let data = serde_json::to_vec(&profile)?;
+        let builder = ZipEntryBuilder::new(
+            PROFILE_JSON_PATH.to_string(),
+            Compression::Deflate,
+        );
+        writer.write_entry_whole(builder, &data).await?;
+        writer.close().await?;
+        emit_loading(&loading_bar, 1.0, None).await?;
+
+        let snapshot = Snapshot {
+            id: id.clone(),
+            profile_uuid: profile.uuid,
+            profile_name: profile.metadata.name.clone(),
+            created,
+            size: fs::metadata(&archive).await?.len(),
+            excluded: excluded.clone(),
+        };
+        fs::write(
+            metadata_path(&snapshots_dir, &id),
+            serde_json::to_vec(&snapshot)?,
+        )
+        .await?;
+
+        Ok::<Snapshot, crate::Error>(snapshot)
+    }
+    .await;
+
+    match result {
+        Ok(snapshot) => {
+            apply_retention(profile.uuid).await?;
+
+            Ok(snapshot)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&archive).await;
+
+            Err(err)
+        }
+    }
+}
+
+/// Lists the snapshots of a profile, newest first
+#[tracing::instrument]
+pub async fn list(profile_path: &Path) -> crate::Result<Vec<Snapshot>> {
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+    })?;
+
+    list_by_uuid(profile.uuid).await
+}
+
+async fn list_by_uuid(profile_uuid: Uuid) -> crate::Result<Vec<Snapshot>> {
+    let state = State::get().await?;
+    let snapshots_dir = state.directories.profile_snapshots_dir(profile_uuid);
+
+    let mut snapshots = Vec::new();
+    if !snapshots_dir.exists() {
+        return Ok(snapshots);
+    }
+
+    let mut entries = fs::read_dir(&snapshots_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension() != Some(OsStr::new("json")) {
+            continue;
+        }
+
+        let snapshot = async {
+            let json = fs::read(&path).await?;
+            Ok::<_, crate::Error>(serde_json::from_slice::<Snapshot>(&json)?)
+        }
+        .await;
+
+        match snapshot {
+            Ok(snapshot)
+                if is_valid_id(&snapshot.id)
+                    && archive_path(&snapshots_dir, &snapshot.id).exists() =>
+            {
+                snapshots.push(snapshot)
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(
+                    "Unable to read snapshot {}: {err}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    snapshots.sort_by_key(|x| Reverse(x.created));
+    Ok(snapshots)
+}
+
+/// Removes a snapshot of a profile
+#[tracing::instrument]
+pub async fn remove(
+    profile_path: &Path,
+    snapshot_id: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+    })?;
+
+    check_id(snapshot_id)?;
+    let snapshots_dir = state.directories.profile_snapshots_dir(profile.uuid);
+    remove_files(&snapshots_dir, snapshot_id).await
+}
+
+async fn remove_files(snapshots_dir: &Path, id: &str) -> crate::Result<()> {
+    for path in [
+        archive_path(snapshots_dir, id),
+        metadata_path(snapshots_dir, id),
+    ] {
+        if path.exists() {
+            fs::remove_file(path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Restores a snapshot into the profile it was taken from, replacing its
+/// contents. Folders excluded from the snapshot are kept as they are
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn restore(
+    profile_path: &Path,
+    snapshot_id: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+    })?;
+
+    if state
+        .children
+        .read()
+        .await
+        .running_profile_uuids()
+        .await?
+        .contains(&profile.uuid)
+    {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Cannot restore a snapshot of {} while it is running",
+            profile.metadata.name
+        ))
+        .into());
+    }
+
+    let _lock = state.profile_locks.try_lock(
+        profile.uuid,
+        &profile.metadata.name,
+        ProfileOperation::RestoringSnapshot,
+    )?;
+
+    let snapshots_dir = state.directories.profile_snapshots_dir(profile.uuid);
+    let snapshot = read_snapshot(&snapshots_dir, snapshot_id).await?;
+
+    let loading_bar = init_loading(
+        LoadingBarType::SnapshotRestore {
+            profile_path: profile.path.clone(),
+            profile_name: profile.metadata.name.clone(),
+        },
+        100.0,
+        "Restoring snapshot",
+    )
+    .await?;
+
+    // The snapshot is extracted next to the profile first, so a broken
+    // archive leaves the profile as it was
+    let staging_dir = sibling_dir(&profile.path, "restore");
+    let old_dir = sibling_dir(&profile.path, "old");
+    if old_dir.exists() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Files left by a failed restore are still in {}",
+            old_dir.display()
+        ))
+        .into());
+    }
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir).await?;
+    }
+    fs::create_dir_all(&staging_dir).await?;
+
+    emit_loading(&loading_bar, 0.0, Some("Extracting snapshot")).await?;
+    let restored = match extract(
+        &archive_path(&snapshots_dir, &snapshot.id),
+        &staging_dir,
+        &loading_bar,
+        90.0,
+    )
+    .await
+    {
+        Ok(restored) => restored,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&staging_dir).await;
+            return Err(err);
+        }
+    };
+
+    let mut replaced = Vec::new();
+    let mut entries = fs::read_dir(&profile.path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if snapshot.excluded.contains(&name)
+            || is_profile_json(Path::new(&name))
+            || is_shared_link(&state.directories, &path)
+        {
+            continue;
+        }
+        replaced.push(entry.file_name());
+    }
+
+    let mut restored_entries = Vec::new();
+    let mut entries = fs::read_dir(&staging_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        restored_entries.push(entry.file_name());
+    }
+
+    let swapped = swap_entries(
+        &profile.path,
+        &staging_dir,
+        &old_dir,
+        &replaced,
+        &restored_entries,
+    )
+    .await;
+    let _ = fs::remove_dir_all(&staging_dir).await;
+    swapped?;
+    if let Err(err) = fs::remove_dir_all(&old_dir).await {
+        tracing::warn!(
+            "Unable to remove replaced files in {}: {err}",
+            old_dir.display()
+        );
+    }
+    emit_loading(&loading_bar, 10.0, None).await?;
+
+    // Removing the watched folders drops their watches, so they are
+    // watched again now that they've been restored
+    {
+        let mut file_watcher = state.file_watcher.write().await;
+        Profile::watch_fs(&profile.path, &mut file_watcher).await?;
+    }
+
+    crate::api::profile::edit(&profile.path, |prof| {
+        let mut restored = restored.clone();
+        restored.uuid = prof.uuid;
+        restored.path = prof.path.clone();
+        *prof = restored;
+
+        async { Ok(()) }
+    })
+    .await?;
+    State::sync().await?;
+
+    Ok(())
+}
+
+/// Restores a snapshot into a new profile, leaving the original untouched
+/// Returns the path of the new profile
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn restore_as_new(
+    profile_path: &Path,
+    snapshot_id: &str,
+    new_name: String,
+) -> crate::Result<PathBuf> {
+    let state = State::get().await?;
+    let profile = get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+    })?;
+
+    let snapshots_dir = state.directories.profile_snapshots_dir(profile.uuid);
+    let snapshot = read_snapshot(&snapshots_dir, snapshot_id).await?;
+
+    let uuid = Uuid::new_v4();
+    let new_path = state.directories.profiles_dir().join(uuid.to_string());
+    fs::create_dir_all(&new_path).await?;
+    let new_path = dunce::canonicalize(&new_path)?;
+
+    let result = async {
+        let loading_bar = init_loading(
+            LoadingBarType::SnapshotRestore {
+                profile_path: new_path.clone(),
+                profile_name: new_name.clone(),
+            },
+            100.0,
+            "Restoring snapshot",
+        )
+        .await?;
+
+        let mut restored = extract(
+            &archive_path(&snapshots_dir, &snapshot.id),
+            &new_path,
+            &loading_bar,
+            100.0,
+        )
+        .await?;
+
+        let old_path = restored.path.clone();
+        restored.uuid = uuid;
+        restored.path = new_path.clone();
+        restored.metadata.name = new_name.clone();
+        restored.metadata.date_created = Utc::now();
+        restored.metadata.date_modified = Utc::now();
+        restored.metadata.last_played = None;
+        restored.projects = restored
+            .projects
+            .into_iter()
+            .filter_map(|(path, project)| {
+                let relative_path = path.strip_prefix(&old_path).ok()?;
+                Some((new_path.join(relative_path), project))
+            })
+            .collect::<HashMap<_, _>>();
+
+        emit_profile(
+            uuid,
+            new_path.clone(),
+            &restored.metadata.name,
+            ProfilePayloadType::Created,
+        )
+        .await?;
+
+        {
+            let mut profiles = state.profiles.write().await;
+            profiles.insert(restored).await?;
+        }
+        State::sync().await?;
+
+        Ok::<PathBuf, crate::Error>(new_path.clone())
+    }
+    .await;
+
+    match result {
+        Ok(new_path) => Ok(new_path),
+        Err(err) => {
+            let _ = crate::api::profile::remove(&new_path).await;
+
+            Err(err)
+        }
+    }
+}
+
+// A folder next to a profile's folder, used while restoring it
+fn sibling_dir(profile_path: &Path, suffix: &str) -> PathBuf {
+    let name = profile_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    profile_path.with_file_name(format!(".{name}.{suffix}"))
+}
+
+// Replaces entries of a profile with the extracted ones. The replaced
+// entries are moved aside first, and moved back if anything fails
+async fn swap_entries(
+    profile_path: &Path,
+    staging_dir: &Path,
+    old_dir: &Path,
+    replaced: &[OsString],
+    restored: &[OsString],
+) -> crate::Result<()> {
+    fs::create_dir_all(old_dir).await?;
+
+    let mut moved_out = Vec::new();
+    let mut moved_in = Vec::new();
+    let result = async {
+        for name in replaced {
+            fs::rename(profile_path.join(name), old_dir.join(name)).await?;
+            moved_out.push(name);
+        }
+        for name in restored {
+            fs::rename(staging_dir.join(name), profile_path.join(name)).await?;
+            moved_in.push(name);
+        }
+
+        Ok::<(), crate::Error>(())
+    }
+    .await;
+
+    if result.is_err() {
+        for name in moved_in {
+            let path = profile_path.join(name);
+            let _ = if path.is_dir() {
+                fs::remove_dir_all(&path).await
+            } else {
+                fs::remove_file(&path).await
+            };
+        }
+        for name in moved_out {
+            if let Err(err) =
+                fs::rename(old_dir.join(name), profile_path.join(name)).await
+            {
+                tracing::error!(
+                    "Unable to move {} back after a failed restore: {err}",
+                    old_dir.join(name).display()
+                );
+            }
+        }
+
+        // Anything that couldn't be moved back is left in the folder
+        let _ = fs::remove_dir(old_dir).await;
+    }
+
+    result
+}
+
+async fn read_snapshot(
+    snapshots_dir: &Path,
+    snapshot_id: &str,
+) -> crate::Result<Snapshot> {
+    check_id(snapshot_id)?;
+    let path = metadata_path(snapshots_dir, snapshot_id);
+    if !path.exists() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "No snapshot with ID {snapshot_id}"
+        ))
+        .into());
+    }
+
+    // The archive is found by the ID in the file, so it must be the one
+    // asked for
+    let snapshot: Snapshot = serde_json::from_slice(&fs::read(path).await?)?;
+    if snapshot.id != snapshot_id {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Snapshot {snapshot_id} has a mismatched ID"
+        ))
+        .into());
+    }
+
+    Ok(snapshot)
+}
+
+// Extracts a snapshot archive into a directory, returning the archived
+// profile.json
+async fn extract(
+    archive: &Path,
+    dest: &Path,
+    loading_bar: &LoadingBarId,
+    loading_amount: f64,
+) -> crate::Result<Profile> {
+    let state = State::get().await?;
+    let zip_reader = ZipFileReader::new(archive).await?;
+    let num_entries = zip_reader.file().entries().len();
+
+    let mut profile = None;
+    for index in 0..num_entries {
+        let entry = zip_reader
+            .file()
+            .entries()
+            .get(index)
+            .unwrap()
+            .entry()
+            .clone();
+        let file_name = entry.filename().to_string();
+
+        if !file_name.ends_with('/') {
+            let mut content = Vec::new();
+            let mut reader = zip_reader.entry(index).await?;
+            reader.read_to_end_checked(&mut content, &entry).await?;
+
+            if file_name == PROFILE_JSON_PATH {
+                profile = Some(read_profile_json(&content)?.0);
+            } else if Path::new(&file_name)
+                .components()
+                .all(|x| matches!(x, Component::Normal(_)))
+            {
+                write(&dest.join(&file_name), &content, &state.io_semaphore)
+                    .await?;
+            }
+        }
+
+        emit_loading(loading_bar, loading_amount / num_entries as f64, None)
+            .await?;
+    }
+
+    profile.ok_or_else(|| {
+        crate::ErrorKind::InputError(format!(
+            "Snapshot {} has no {PROFILE_JSON_PATH}",
+            archive.display()
+        ))
+        .into()
+    })
+}
+
+// Removes the snapshots of a profile that fall outside the retention policy
+// in the settings. The newest snapshot is always kept
+async fn apply_retention(profile_uuid: Uuid) -> crate::Result<()> {
+    let state = State::get().await?;
+    let retention = state.settings.read().await.snapshot_retention;
+    let snapshots_dir = state.directories.profile_snapshots_dir(profile_uuid);
+
+    let snapshots = list_by_uuid(profile_uuid).await?;
+    for (index, snapshot) in snapshots.iter().enumerate().skip(1) {
+        let over_count =
+            matches!(retention.max_snapshots, Some(max) if index >= max);
+        let over_age = match retention.max_age_days {
+            Some(days) => {
+                Utc::now() - snapshot.created > Duration::days(days as i64)
+            }
+            None => false,
+        };
+
+        if over_count || over_age {
+            remove_files(&snapshots_dir, &snapshot.id).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_ids_outside_the_snapshots_folder() {
+        assert!(is_valid_id("2023-01-01T00-00-00"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("."));
+        assert!(!is_valid_id(".."));
+        assert!(!is_valid_id("../../profiles/uuid/profile"));
+        assert!(!is_valid_id("nested/id"));
+        assert!(!is_valid_id("/etc/passwd"));
+    }
+}
@@ -0,0 +1,606 @@
+//! Theseus profile worlds
+use crate::event::emit::{emit_loading, init_loading};
+use crate::event::LoadingBarType;
+use crate::profile::{build_folder, get, Profile};
+use crate::util::fetch::{copy as copy_file, write};
+use crate::util::nbt::{self, Nbt};
+use crate::State;
+use async_zip::tokio::read::fs::ZipFileReader;
+use async_zip::tokio::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs::{self, File};
+use tokio::io::AsyncReadExt;
+
+const SAVES_FOLDER: &str = "saves";
+const LEVEL_DAT: &str = "level.dat";
+const ICON: &str = "icon.png";
+// Held open by Minecraft while a world is loaded
+const SESSION_LOCK: &str = "session.lock";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+    Unknown,
+}
+
+impl GameMode {
+    fn from_id(id: i64) -> Self {
+        match id {
+            0 => Self::Survival,
+            1 => Self::Creative,
+            2 => Self::Adventure,
+            3 => Self::Spectator,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// A world in a profile's saves folder
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct World {
+    /// Name of the world's folder, which identifies it within the profile
+    pub folder: String,
+    pub name: String,
+    pub game_mode: GameMode,
+    pub hardcore: bool,
+    pub last_played: Option<DateTime<Utc>>,
+    /// Name of the Minecraft version the world was last played in
+    pub version: Option<String>,
+    pub seed: Option<i64>,
+    pub icon: Option<PathBuf>,
+}
+
+/// A zip archive of a single world
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WorldBackup {
+    pub id: String,
+    pub folder: String,
+    pub name: String,
+    pub created: DateTime<Utc>,
+    pub size: u64,
+}
+
+fn archive_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.zip"))
+}
+
+fn metadata_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+async fn get_profile(profile_path: &Path) -> crate::Result<Profile> {
+    get(profile_path, None).await?.ok_or_else(|| {
+        crate::ErrorKind::UnmanagedProfileError(
+            profile_path.display().to_string(),
+        )
+        .into()
+    })
+}
+
+// Worlds and other files Minecraft writes can't be changed while it runs
+pub(crate) async fn check_not_running(
+    profile: &Profile,
+    action: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    if state
+        .children
+        .read()
+        .await
+        .running_profile_uuids()
+        .await?
+        .contains(&profile.uuid)
+    {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Cannot {action} while {} is running",
+            profile.metadata.name
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+// Whether a name is a single path component, so joining it to a folder
+// can't reach outside that folder
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+// Checks that a backup ID names a file directly in the backups folder
+fn check_backup_id(id: &str) -> crate::Result<()> {
+    if !is_plain_name(id) {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Invalid world backup ID: {id}"
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+// Gets the folder of a world, which must be directly in saves
+fn world_path(profile: &Profile, folder: &str) -> crate::Result<PathBuf> {
+    if !is_plain_name(folder) {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Invalid world folder: {folder}"
+        ))
+        .into());
+    }
+
+    Ok(profile.path.join(SAVES_FOLDER).join(folder))
+}
+
+/// Reads a world's level.dat
+#[tracing::instrument]
+pub async fn read_world(path: &Path) -> crate::Result<World> {
+    let (_, root) = nbt::read_gzip(&fs::read(path.join(LEVEL_DAT)).await?)?;
+    let data = root.get("Data").ok_or_else(|| {
+        crate::ErrorKind::NBTError(format!(
+            "{} has no Data tag",
+            path.join(LEVEL_DAT).display()
+        ))
+    })?;
+
+    let folder = path
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    // The seed moved into the world generation settings in 1.16
+    let seed = data
+        .get("WorldGenSettings")
+        .and_then(|x| x.get("seed"))
+        .or_else(|| data.get("RandomSeed"))
+        .and_then(Nbt::as_i64);
+
+    let icon = path.join(ICON);
+
+    Ok(World {
+        name: data
+            .get("LevelName")
+            .and_then(Nbt::as_str)
+            .map(String::from)
+            .unwrap_or_else(|| folder.clone()),
+        folder,
+        game_mode: data
+            .get("GameType")
+            .and_then(Nbt::as_i64)
+            .map_or(GameMode::Unknown, GameMode::from_id),
+        hardcore: data.get("hardcore").and_then(Nbt::as_i64).unwrap_or(0) != 0,
+        last_played: data
+            .get("LastPlayed")
+            .and_then(Nbt::as_i64)
+            .and_then(|x| Utc.timestamp_millis_opt(x).single()),
+        version: data
+            .get("Version")
+            .and_then(|x| x.get("Name"))
+            .and_then(Nbt::as_str)
+            .map(String::from),
+        seed,
+        icon: icon.exists().then_some(icon),
+    })
+}
+
+/// Lists the worlds of a profile, most recently played first
+#[tracing::instrument]
+pub async fn list(profile_path: &Path) -> crate::Result<Vec<World>> {
+    let profile = get_profile(profile_path).await?;
+    let saves_dir = profile.path.join(SAVES_FOLDER);
+
+    let mut worlds = Vec::new();
+    if !saves_dir.exists() {
+        return Ok(worlds);
+    }
+
+    let mut entries = fs::read_dir(&saves_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if !path.join(LEVEL_DAT).exists() {
+            continue;
+        }
+
+        match read_world(&path).await {
+            Ok(world) => worlds.push(world),
+            Err(err) => {
+                tracing::warn!(
+                    "Unable to read world {}: {err}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    worlds.sort_by_key(|x| Reverse(x.last_played));
+    Ok(worlds)
+}
+
+/// Gets a world of a profile by its folder name
+#[tracing::instrument]
+pub async fn get_world(
+    profile_path: &Path,
+    folder: &str,
+) -> crate::Result<World> {
+    let profile = get_profile(profile_path).await?;
+    read_world(&world_path(&profile, folder)?).await
+}
+
+/// Deletes a world from a profile
+#[tracing::instrument]
+pub async fn delete(profile_path: &Path, folder: &str) -> crate::Result<()> {
+    let profile = get_profile(profile_path).await?;
+    check_not_running(&profile, "delete a world").await?;
+
+    let path = world_path(&profile, folder)?;
+    if path.exists() {
+        fs::remove_dir_all(path).await?;
+    }
+
+    Ok(())
+}
+
+/// Copies a world into another profile, returning the folder name it was
+/// given there
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn copy(
+    profile_path: &Path,
+    folder: &str,
+    dest_profile_path: &Path,
+) -> crate::Result<String> {
+    let state = State::get().await?;
+    let profile = get_profile(profile_path).await?;
+    let dest_profile = get_profile(dest_profile_path).await?;
+
+    let src = world_path(&profile, folder)?;
+    if !src.join(LEVEL_DAT).exists() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "No world {folder} in {}",
+            profile.metadata.name
+        ))
+        .into());
+    }
+
+    let dest_folder = unique_folder(&dest_profile, folder);
+    let dest = world_path(&dest_profile, &dest_folder)?;
+
+    let mut path_list = Vec::new();
+    build_folder(&src, &mut path_list).await?;
+    for path in path_list {
+        if path.file_name() == Some(OsStr::new(SESSION_LOCK)) {
+            continue;
+        }
+
+        copy_file(
+            &path,
+            &dest.join(path.strip_prefix(&src)?),
+            &state.io_semaphore,
+        )
+        .await?;
+    }
+
+    Ok(dest_folder)
+}
+
+// Adds " (n)" to a world folder until it's free in the profile
+fn unique_folder(profile: &Profile, folder: &str) -> String {
+    let saves_dir = profile.path.join(SAVES_FOLDER);
+    if !saves_dir.join(folder).exists() {
+        return folder.to_string();
+    }
+
+    (1..)
+        .map(|i| format!("{folder} ({i})"))
+        .find(|x| !saves_dir.join(x).exists())
+        .unwrap_or_else(|| folder.to_string())
+}
+
+/// Backs up a world of a profile to a zip archive
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn backup(
+    profile_path: &Path,
+    folder: &str,
+) -> crate::Result<WorldBackup> {
+    let state = State::get().await?;
+    let profile = get_profile(profile_path).await?;
+    let world_dir = world_path(&profile, folder)?;
+    let world = read_world(&world_dir).await?;
+
+    let created = Utc::now();
+    let id = created.timestamp_millis().to_string();
+    let backups_dir = state.directories.profile_world_backups_dir(profile.uuid);
+    fs::create_dir_all(&backups_dir).await?;
+
+    let archive = archive_path(&backups_dir, &id);
+    let result = async {
+        let io_semaphore = state.io_semaphore.0.read().await;
+        let _permit = io_semaphore.acquire().await?;
+
+        let mut path_list = Vec::new();
+        build_folder(&world_dir, &mut path_list).await?;
+
+        let loading_bar = init_loading(
+            LoadingBarType::WorldBackup {
+                profile_path: profile.path.clone(),
+                world_name: world.name.clone(),
+            },
+            path_list.len() as f64,
+            "Backing up world",
+        )
+        .await?;
+
+        let mut file = File::create(&archive).await?;
+        let mut writer = ZipFileWriter::new(&mut file);
+
+        for path in path_list {
+            emit_loading(&loading_bar, 1.0, None).await?;
+
+            if path.file_name() == Some(OsStr::new(SESSION_LOCK)) {
+                continue;
+            }
+
+            let relative_path = path
+                .strip_prefix(&world_dir)?
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            let mut data = Vec::new();
+            File::open(&path).await?.read_to_end(&mut data).await?;
+            let builder =
+                ZipEntryBuilder::new(relative_path, Compression::Deflate);
+            writer.write_entry_whole(builder, &data).await?;
+        }
+        writer.close().await?;
+
+        let backup = WorldBackup {
+            id: id.clone(),
+            folder: world.folder.clone(),
+            name: world.name.clone(),
+            created,
+            size: fs::metadata(&archive).await?.len(),
+        };
+        fs::write(
+            metadata_path(&backups_dir, &id),
+            serde_json::to_vec(&backup)?,
+        )
+        .await?;
+
+        Ok::<WorldBackup, crate::Error>(backup)
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&archive).await;
+    }
+
+    result
+}
+
+/// Lists the world backups of a profile, newest first
+#[tracing::instrument]
+pub async fn list_backups(
+    profile_path: &Path,
+) -> crate::Result<Vec<WorldBackup>> {
+    let state = State::get().await?;
+    let profile = get_profile(profile_path).await?;
+    let backups_dir = state.directories.profile_world_backups_dir(profile.uuid);
+
+    let mut backups = Vec::new();
+    if !backups_dir.exists() {
+        return Ok(backups);
+    }
+
+    let mut entries = fs::read_dir(&backups_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension() != Some(OsStr::new("json")) {
+            continue;
+        }
+
+        let backup = async {
+            let json = fs::read(&path).await?;
+            Ok::<_, crate::Error>(serde_json::from_slice::<WorldBackup>(&json)?)
+        }
+        .await;
+
+        match backup {
+            Ok(backup)
+                if is_plain_name(&backup.id)
+                    && archive_path(&backups_dir, &backup.id).exists() =>
+            {
+                backups.push(backup)
+            }
+            Ok(_) => {}
+            Err(err) => {
+                tracing::warn!(
+                    "Unable to read world backup {}: {err}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    backups.sort_by_key(|x| Reverse(x.created));
+    Ok(backups)
+}
+
+/// Removes a world backup of a profile
+#[tracing::instrument]
+pub async fn remove_backup(
+    profile_path: &Path,
+    backup_id: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = get_profile(profile_path).await?;
+    check_backup_id(backup_id)?;
+    let backups_dir = state.directories.profile_world_backups_dir(profile.uuid);
+
+    for path in [
+        archive_path(&backups_dir, backup_id),
+        metadata_path(&backups_dir, backup_id),
+    ] {
+        if path.exists() {
+            fs::remove_file(path).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Restores a world backup, replacing the world it was taken from
+#[tracing::instrument]
+#[theseus_macros::debug_pin]
+pub async fn restore_backup(
+    profile_path: &Path,
+    backup_id: &str,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let profile = get_profile(profile_path).await?;
+    check_not_running(&profile, "restore a world").await?;
+    check_backup_id(backup_id)?;
+
+    let backups_dir = state.directories.profile_world_backups_dir(profile.uuid);
+    let path = metadata_path(&backups_dir, backup_id);
+    if !path.exists() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "No world backup with ID {backup_id}"
+        ))
+        .into());
+    }
+    let backup: WorldBackup = serde_json::from_slice(&fs::read(path).await?)?;
+    if backup.id != backup_id {
+        return Err(crate::ErrorKind::InputError(format!(
+            "World backup {backup_id} has a mismatched ID"
+        ))
+        .into());
+    }
+
+    // The backup is extracted next to the world, and only replaces it once
+    // it has been extracted in full
+    let world_dir = world_path(&profile, &backup.folder)?;
+    let staging_dir =
+        world_dir.with_file_name(format!(".{}.restore", backup.folder));
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir).await?;
+    }
+
+    let extracted = extract_backup(
+        &archive_path(&backups_dir, backup_id),
+        &staging_dir,
+        &profile,
+        &backup,
+    )
+    .await;
+    if let Err(err) = extracted {
+        let _ = fs::remove_dir_all(&staging_dir).await;
+        return Err(err);
+    }
+
+    if world_dir.exists() {
+        let old_dir =
+            world_dir.with_file_name(format!(".{}.old", backup.folder));
+        if old_dir.exists() {
+            fs::remove_dir_all(&old_dir).await?;
+        }
+
+        fs::rename(&world_dir, &old_dir).await?;
+        if let Err(err) = fs::rename(&staging_dir, &world_dir).await {
+            fs::rename(&old_dir, &world_dir).await?;
+            let _ = fs::remove_dir_all(&staging_dir).await;
+            return Err(err.into());
+        }
+        if let Err(err) = fs::remove_dir_all(&old_dir).await {
+            tracing::warn!(
+                "Unable to remove replaced world {}: {err}",
+                old_dir.display()
+            );
+        }
+    } else {
+        fs::rename(&staging_dir, &world_dir).await?;
+    }
+
+    Ok(())
+}
+
+// Extracts a world backup archive into a folder
+async fn extract_backup(
+    archive: &Path,
+    dest: &Path,
+    profile: &Profile,
+    backup: &WorldBackup,
+) -> crate::Result<()> {
+    let state = State::get().await?;
+    let zip_reader = ZipFileReader::new(archive).await?;
+    let num_entries = zip_reader.file().entries().len();
+
+    let loading_bar = init_loading(
+        LoadingBarType::WorldRestore {
+            profile_path: profile.path.clone(),
+            world_name: backup.name.clone(),
+        },
+        num_entries as f64,
+        "Restoring world",
+    )
+    .await?;
+
+    for index in 0..num_entries {
+        let entry = zip_reader
+            .file()
+            .entries()
+            .get(index)
+            .unwrap()
+            .entry()
+            .clone();
+        let file_name = entry.filename().to_string();
+
+        if !file_name.ends_with('/')
+            && Path::new(&file_name)
+                .components()
+                .all(|x| matches!(x, Component::Normal(_)))
+        {
+            let mut content = Vec::new();
+            let mut reader = zip_reader.entry(index).await?;
+            reader.read_to_end_checked(&mut content, &entry).await?;
+
+            write(&dest.join(&file_name), &content, &state.io_semaphore)
+                .await?;
+        }
+
+        emit_loading(&loading_bar, 1.0, None).await?;
+    }
+
+    // Worlds without files still get a folder, to be renamed into place
+    fs::create_dir_all(dest).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_names_outside_the_folder() {
+        assert!(is_plain_name("New World"));
+        assert!(is_plain_name("New World-2023-01-01T00-00-00"));
+        assert!(!is_plain_name(""));
+        assert!(!is_plain_name(".."));
+        assert!(!is_plain_name("../../profiles/uuid/profile"));
+        assert!(!is_plain_name("saves/New World"));
+        assert!(check_backup_id("../backup").is_err());
+    }
+}
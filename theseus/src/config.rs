@@ -1,3 +1,4 @@
 //! Configuration structs
 
 pub const MODRINTH_API_URL: &str = "https://api.modrinth.com/v2/";
+pub const CURSEFORGE_API_URL: &str = "https://api.curseforge.com/v1/";
@@ -64,6 +64,15 @@ pub enum ErrorKind {
     #[error("Profile {0} is not managed by Theseus!")]
     UnmanagedProfileError(String),
 
+    #[error("Unsupported profile format: {0}")]
+    ProfileSchemaError(String),
+
+    #[error("Profile {0} is busy {1}")]
+    ProfileBusyError(String, crate::state::ProfileOperation),
+
+    #[error("Error reading NBT: {0}")]
+    NBTError(String),
+
     #[error("Could not create profile: {0}")]
     ProfileCreationError(#[from] profile_create::ProfileCreationError),
 
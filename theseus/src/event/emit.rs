@@ -11,7 +11,8 @@ use std::path::PathBuf;
 
 #[cfg(feature = "tauri")]
 use crate::event::{
-    LoadingPayload, ProcessPayload, ProfilePayload, WarningPayload,
+    LoadingPayload, ProcessPayload, ProfilePayload, ProfilesPayload,
+    WarningPayload,
 };
 #[cfg(feature = "tauri")]
 use tauri::Manager;
@@ -251,10 +252,11 @@ pub async fn emit_command(command: CommandPayload) -> crate::Result<()> {
     Ok(())
 }
 
-// emit_process(uuid, pid, event, message)
+// emit_process(uuid, profile_uuid, pid, event, message)
 #[allow(unused_variables)]
 pub async fn emit_process(
     uuid: Uuid,
+    profile_uuid: Uuid,
     pid: u32,
     event: ProcessPayloadType,
     message: &str,
@@ -268,6 +270,7 @@ pub async fn emit_process(
                 "process",
                 ProcessPayload {
                     uuid,
+                    profile_uuid,
                     pid,
                     event,
                     message: message.to_string(),
@@ -305,6 +308,23 @@ pub async fn emit_profile(
     Ok(())
 }
 
+// emit_profiles(uuids, event)
+#[allow(unused_variables)]
+pub async fn emit_profiles(
+    uuids: Vec<Uuid>,
+    event: ProfilePayloadType,
+) -> crate::Result<()> {
+    #[cfg(feature = "tauri")]
+    {
+        let event_state = crate::EventState::get().await?;
+        event_state
+            .app
+            .emit_all("profiles", ProfilesPayload { uuids, event })
+            .map_err(EventError::from)?;
+    }
+    Ok(())
+}
+
 // loading_join! macro
 // loading_join!(key: Option<&LoadingBarId>, total: f64, message: Option<&str>; task1, task2, task3...)
 // This will submit a loading event with the given message for each task as they complete
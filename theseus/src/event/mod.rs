@@ -181,6 +181,35 @@ pub enum LoadingBarType {
         profile_path: PathBuf,
         profile_name: String,
     },
+    CopyProfile {
+        import_location: PathBuf,
+        profile_name: String,
+    },
+    CopyLauncherFiles {
+        import_location: PathBuf,
+    },
+    SnapshotCreate {
+        profile_path: PathBuf,
+        profile_name: String,
+    },
+    SnapshotRestore {
+        profile_path: PathBuf,
+        profile_name: String,
+    },
+    WorldBackup {
+        profile_path: PathBuf,
+        world_name: String,
+    },
+    WorldRestore {
+        profile_path: PathBuf,
+        world_name: String,
+    },
+    DiskUsage,
+    GarbageCollection,
+    ProfileVerify {
+        profile_path: PathBuf,
+        profile_name: String,
+    },
 }
 
 #[derive(Serialize, Clone)]
@@ -217,6 +246,7 @@ pub enum CommandPayload {
 #[derive(Serialize, Clone)]
 pub struct ProcessPayload {
     pub uuid: Uuid, // processes in state are going to be identified by UUIDs, as they might change to different processes
+    pub profile_uuid: Uuid,
     pub pid: u32,
     pub event: ProcessPayloadType,
     pub message: String,
@@ -236,6 +266,12 @@ pub struct ProfilePayload {
     pub name: String,
     pub event: ProfilePayloadType,
 }
+/// Sent once for a change made to several profiles together
+#[derive(Serialize, Clone)]
+pub struct ProfilesPayload {
+    pub uuids: Vec<Uuid>,
+    pub event: ProfilePayloadType,
+}
 #[derive(Serialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum ProfilePayloadType {
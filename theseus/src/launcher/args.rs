@@ -1,6 +1,6 @@
 //! Minecraft CLI argument logic
 // TODO: Rafactor this section
-use super::{auth::Credentials, parse_rule};
+use super::{auth::Credentials, parse_rule, Features, QuickPlay};
 use crate::{
     state::{MemorySettings, WindowSize},
     util::platform::classpath_separator,
@@ -18,6 +18,8 @@ use uuid::Uuid;
 // Replaces the space separator with a newline character, as to not split the arguments
 const TEMPORARY_REPLACE_CHAR: &str = "\n";
 
+const DEFAULT_SERVER_PORT: &str = "25565";
+
 pub fn get_class_paths(
     libraries_path: &Path,
     libraries: &[Library],
@@ -28,7 +30,10 @@ pub fn get_class_paths(
         .iter()
         .filter_map(|library| {
             if let Some(rules) = &library.rules {
-                if !rules.iter().any(|x| parse_rule(x, java_arch)) {
+                if !rules
+                    .iter()
+                    .any(|x| parse_rule(x, java_arch, &Features::default()))
+                {
                     return None;
                 }
             }
@@ -122,6 +127,8 @@ pub fn get_jvm_arguments(
                 )
             },
             java_arch,
+            // JVM argument rules only depend on the OS
+            &Features::default(),
         )?;
     } else {
         parsed_arguments.push(format!(
@@ -201,8 +208,10 @@ pub fn get_minecraft_arguments(
     version_type: &VersionType,
     resolution: WindowSize,
     java_arch: &str,
+    features: &Features,
+    quick_play_path: &Path,
 ) -> crate::Result<Vec<String>> {
-    if let Some(arguments) = arguments {
+    let mut parsed_arguments = if let Some(arguments) = arguments {
         let mut parsed_arguments = Vec::new();
 
         parse_arguments(
@@ -220,12 +229,15 @@ pub fn get_minecraft_arguments(
                     assets_directory,
                     version_type,
                     resolution,
+                    features.quick_play,
+                    quick_play_path,
                 )
             },
             java_arch,
+            features,
         )?;
 
-        Ok(parsed_arguments)
+        parsed_arguments
     } else if let Some(legacy_arguments) = legacy_arguments {
         let mut parsed_arguments = Vec::new();
         for x in legacy_arguments.split(' ') {
@@ -240,11 +252,81 @@ pub fn get_minecraft_arguments(
                 assets_directory,
                 version_type,
                 resolution,
+                None,
+                quick_play_path,
             )?);
         }
-        Ok(parsed_arguments)
+        parsed_arguments
     } else {
-        Ok(Vec::new())
+        Vec::new()
+    };
+
+    if let Some(quick_play) = features.quick_play {
+        if !supports_quick_play(arguments) {
+            parsed_arguments.extend(legacy_quick_play_arguments(quick_play)?);
+        }
+    }
+
+    Ok(parsed_arguments)
+}
+
+// Whether a version's arguments have Quick Play, which 1.20 added
+fn supports_quick_play(arguments: Option<&[Argument]>) -> bool {
+    arguments.unwrap_or_default().iter().any(|x| match x {
+        Argument::Ruled { rules, .. } => rules.iter().any(|rule| {
+            rule.features
+                .as_ref()
+                .is_some_and(|x| x.is_quick_play_multiplayer.is_some())
+        }),
+        Argument::Normal(_) => false,
+    })
+}
+
+// Older versions can only be launched into a server, with --server and
+// --port
+fn legacy_quick_play_arguments(
+    quick_play: &QuickPlay,
+) -> crate::Result<Vec<String>> {
+    match quick_play {
+        QuickPlay::Multiplayer(address) => {
+            let (host, port) = split_server_address(address);
+            Ok(vec![
+                "--server".to_string(),
+                host.to_string(),
+                "--port".to_string(),
+                port.to_string(),
+            ])
+        }
+        QuickPlay::Singleplayer(_) | QuickPlay::Realms(_) => {
+            Err(crate::ErrorKind::LauncherError(
+                "Launching into a world or realm needs Minecraft 1.20 or newer"
+                    .to_string(),
+            )
+            .as_error())
+        }
+    }
+}
+
+// Splits an address into its host and port. IPv6 hosts are written in
+// brackets when a port follows them
+fn split_server_address(address: &str) -> (&str, &str) {
+    let port_separator = if address.starts_with('[') {
+        address.find("]:").map(|x| x + 1)
+    } else if address.matches(':').count() == 1 {
+        address.find(':')
+    } else {
+        None
+    };
+
+    match port_separator {
+        Some(index) => (
+            address[..index].trim_matches(|c| c == '[' || c == ']'),
+            &address[index + 1..],
+        ),
+        None => (
+            address.trim_matches(|c| c == '[' || c == ']'),
+            DEFAULT_SERVER_PORT,
+        ),
     }
 }
 
@@ -260,7 +342,20 @@ fn parse_minecraft_argument(
     assets_directory: &Path,
     version_type: &VersionType,
     resolution: WindowSize,
+    quick_play: Option<&QuickPlay>,
+    quick_play_path: &Path,
 ) -> crate::Result<String> {
+    // Only the argument of the Quick Play mode in use is enabled, so the
+    // others are never substituted
+    let quick_play_target = match quick_play {
+        Some(
+            QuickPlay::Singleplayer(x)
+            | QuickPlay::Multiplayer(x)
+            | QuickPlay::Realms(x),
+        ) => x.as_str(),
+        None => "",
+    };
+
     Ok(argument
         .replace("${accessToken}", access_token)
         .replace("${auth_access_token}", access_token)
@@ -313,7 +408,11 @@ fn parse_minecraft_argument(
         )
         .replace("${version_type}", version_type.as_str())
         .replace("${resolution_width}", &resolution.0.to_string())
-        .replace("${resolution_height}", &resolution.1.to_string()))
+        .replace("${resolution_height}", &resolution.1.to_string())
+        .replace("${quickPlayPath}", &quick_play_path.to_string_lossy())
+        .replace("${quickPlaySingleplayer}", quick_play_target)
+        .replace("${quickPlayMultiplayer}", quick_play_target)
+        .replace("${quickPlayRealms}", quick_play_target))
 }
 
 fn parse_arguments<F>(
@@ -321,6 +420,7 @@ fn parse_arguments<F>(
     parsed_arguments: &mut Vec<String>,
     parse_function: F,
     java_arch: &str,
+    features: &Features,
 ) -> crate::Result<()>
 where
     F: Fn(&str) -> crate::Result<String>,
@@ -335,7 +435,7 @@ where
                 }
             }
             Argument::Ruled { rules, value } => {
-                if rules.iter().any(|x| parse_rule(x, java_arch)) {
+                if rules.iter().any(|x| parse_rule(x, java_arch, features)) {
                     match value {
                         ArgumentValue::Single(arg) => {
                             parsed_arguments.push(parse_function(
@@ -253,7 +253,7 @@ pub async fn download_libraries(
         stream::iter(libraries.iter())
             .map(Ok::<&Library, crate::Error>), None, loading_bar,loading_amount,num_files, None,|library| async move {
                 if let Some(rules) = &library.rules {
-                    if !rules.iter().any(|x| super::parse_rule(x, java_arch)) {
+                    if !rules.iter().any(|x| super::parse_rule(x, java_arch, &super::Features::default())) {
                         tracing::trace!("Skipped library {}", &library.name);
                         return Ok(());
                     }
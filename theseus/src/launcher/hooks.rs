@@ -0,0 +1,305 @@
+//! Parsing and running of user-defined hooks
+use crate::state::{Hooks, Profile};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Values a hook can refer to, as `${NAME}` in its arguments and as
+/// environment variables of the same name
+#[derive(Debug, Clone)]
+pub struct HookContext {
+    pub profile_path: PathBuf,
+    pub profile_name: String,
+    pub game_version: String,
+    pub loader: String,
+    pub java_path: Option<PathBuf>,
+    pub exit_code: Option<i32>,
+}
+
+impl HookContext {
+    pub fn new(profile: &Profile, java_path: Option<&Path>) -> Self {
+        Self {
+            profile_path: profile.path.clone(),
+            profile_name: profile.metadata.name.clone(),
+            game_version: profile.metadata.game_version.clone(),
+            loader: profile.metadata.loader.as_api_str().to_string(),
+            java_path: java_path.map(Path::to_path_buf),
+            exit_code: None,
+        }
+    }
+
+    pub fn with_exit_code(&self, exit_code: Option<i32>) -> Self {
+        Self {
+            exit_code,
+            ..self.clone()
+        }
+    }
+
+    // Variables that aren't known yet (the Java path before it's resolved,
+    // or the exit code before the game exits) are left out
+    fn vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            (
+                "PROFILE_PATH",
+                self.profile_path.to_string_lossy().to_string(),
+            ),
+            ("PROFILE_NAME", self.profile_name.clone()),
+            ("GAME_VERSION", self.game_version.clone()),
+            ("LOADER", self.loader.clone()),
+        ];
+        if let Some(java_path) = &self.java_path {
+            vars.push(("JAVA_PATH", java_path.to_string_lossy().to_string()));
+        }
+        if let Some(exit_code) = self.exit_code {
+            vars.push(("EXIT_CODE", exit_code.to_string()));
+        }
+        vars
+    }
+
+    // Replaces every known `${NAME}` in an argument. Unknown names are kept
+    // as written
+    fn substitute(&self, arg: &str) -> String {
+        self.vars()
+            .iter()
+            .fold(arg.to_string(), |arg, (name, value)| {
+                arg.replace(&format!("${{{name}}}"), value)
+            })
+    }
+}
+
+/// Splits a hook into arguments the way a shell would: whitespace separates
+/// arguments, and single or double quotes group them. Outside quotes, a
+/// backslash escapes a following quote, space or backslash. Inside double
+/// quotes it only escapes a double quote or backslash, and inside single
+/// quotes nothing is escaped. Other backslashes are kept as-is so Windows
+/// paths don't need escaping
+pub fn split_command(hook: &str) -> crate::Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = hook.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => current.get_or_insert_with(String::new).push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            }
+            (Some('"'), '\\') if matches!(chars.peek(), Some('\\' | '"')) => {
+                if let Some(next) = chars.next() {
+                    current.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (None, '\\')
+                if matches!(
+                    chars.peek(),
+                    Some('\\' | '"' | '\'' | ' ' | '\t')
+                ) =>
+            {
+                if let Some(next) = chars.next() {
+                    current.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (None, c) if c.is_whitespace() => {
+                if let Some(arg) = current.take() {
+                    args.push(arg);
+                }
+            }
+            (_, c) => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(crate::ErrorKind::InputError(format!(
+            "Unterminated quote in hook: {hook}"
+        ))
+        .into());
+    }
+    args.extend(current);
+
+    Ok(args)
+}
+
+/// Builds the command for a hook, with its variables substituted and set in
+/// its environment. Returns `None` for a blank hook
+pub fn build_command(
+    hook: &str,
+    context: &HookContext,
+) -> crate::Result<Option<Command>> {
+    let mut args = split_command(hook)?
+        .into_iter()
+        .map(|arg| context.substitute(&arg));
+
+    Ok(args.next().map(|program| {
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .envs(context.vars())
+            .current_dir(&context.profile_path);
+        command
+    }))
+}
+
+/// Runs a hook to completion, failing on a non-zero exit code
+pub async fn run_hook(
+    name: &str,
+    hook: &Option<String>,
+    context: &HookContext,
+) -> crate::Result<()> {
+    let command = match hook {
+        Some(hook) => build_command(hook, context)?,
+        None => None,
+    };
+
+    if let Some(mut command) = command {
+        let result = command.spawn()?.wait().await?;
+
+        if !result.success() {
+            return Err(crate::ErrorKind::LauncherError(format!(
+                "Non-zero exit code for {name} hook: {}",
+                result.code().unwrap_or(-1)
+            ))
+            .as_error());
+        }
+    }
+
+    Ok(())
+}
+
+/// The hooks run once Minecraft has exited, which can only be built once its
+/// exit code is known
+#[derive(Debug)]
+pub struct ExitHooks {
+    pub post_exit: Option<String>,
+    pub on_crash: Option<String>,
+    pub context: HookContext,
+}
+
+impl ExitHooks {
+    pub fn new(hooks: &Hooks, context: HookContext) -> Self {
+        Self {
+            post_exit: hooks.post_exit.clone(),
+            on_crash: hooks.on_crash.clone(),
+            context,
+        }
+    }
+
+    pub fn post_exit(
+        &self,
+        exit_code: Option<i32>,
+    ) -> crate::Result<Option<Command>> {
+        match &self.post_exit {
+            Some(hook) => {
+                build_command(hook, &self.context.with_exit_code(exit_code))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn run_on_crash(
+        &self,
+        exit_code: Option<i32>,
+    ) -> crate::Result<()> {
+        run_hook(
+            "on-crash",
+            &self.on_crash,
+            &self.context.with_exit_code(exit_code),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(hook: &str) -> Vec<String> {
+        split_command(hook).unwrap()
+    }
+
+    #[test]
+    fn splits_on_whitespace() {
+        assert_eq!(
+            split("  java  -jar\tapp.jar "),
+            ["java", "-jar", "app.jar"]
+        );
+        assert!(split("   ").is_empty());
+    }
+
+    #[test]
+    fn quoted_paths_with_spaces() {
+        assert_eq!(
+            split(r#""/opt/my games/run.sh" 'second arg' "#),
+            ["/opt/my games/run.sh", "second arg"]
+        );
+        assert_eq!(split(r#"echo "" ''"#), ["echo", "", ""]);
+        assert_eq!(split(r#"pre"fix "'suffix'"#), ["prefix suffix"]);
+    }
+
+    #[test]
+    fn nested_quote_kinds() {
+        assert_eq!(
+            split(r#"echo "it's here" 'say "hi"'"#),
+            ["echo", "it's here", r#"say "hi""#]
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(split(r#"a\ b \"c\" \\d"#), ["a b", r#""c""#, r"\d"]);
+        // Only a double quote or backslash is escaped in double quotes
+        assert_eq!(split(r#""a\"b\\c\ d\tx\'y""#), [r#"a"b\c\ d\tx\'y"#]);
+        // Nothing is escaped in single quotes
+        assert_eq!(split(r"'a\b\\c'"), [r"a\b\\c"]);
+    }
+
+    #[test]
+    fn unterminated_quote() {
+        assert!(split_command(r#"echo "unterminated"#).is_err());
+        assert!(split_command("echo 'unterminated").is_err());
+        assert!(split_command(r#"echo "a\""#).is_err());
+    }
+
+    #[test]
+    fn unquoted_windows_paths() {
+        assert_eq!(
+            split(r"C:\Games\tools\backup.exe D:\Saves"),
+            [r"C:\Games\tools\backup.exe", r"D:\Saves"]
+        );
+        // Spaces still separate arguments unless quoted
+        assert_eq!(
+            split(r"C:\Program Files\Java\bin\java.exe -version"),
+            [r"C:\Program", r"Files\Java\bin\java.exe", "-version"]
+        );
+        assert_eq!(
+            split(r#""C:\Program Files\Java\bin\java.exe" -version"#),
+            [r"C:\Program Files\Java\bin\java.exe", "-version"]
+        );
+    }
+
+    #[test]
+    fn substitutes_each_argument() {
+        let context = HookContext {
+            profile_path: PathBuf::from("/profiles/my profile"),
+            profile_name: "My Profile".to_string(),
+            game_version: "1.20.1".to_string(),
+            loader: "fabric".to_string(),
+            java_path: None,
+            exit_code: Some(1),
+        };
+        let command = build_command(
+            r#"notify "exited with ${EXIT_CODE} in ${PROFILE_NAME}" ${JAVA_PATH}"#,
+            &context,
+        )
+        .unwrap()
+        .unwrap();
+        let command = command.as_std();
+
+        assert_eq!(command.get_program(), "notify");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["exited with 1 in My Profile", "${JAVA_PATH}"]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 //! Logic for launching Minecraft
-use crate::event::emit::{emit_loading, init_or_edit_loading};
+use crate::event::emit::{emit_loading, init_loading, init_or_edit_loading};
 use crate::event::{LoadingBarId, LoadingBarType};
 use crate::jre::{JAVA_17_KEY, JAVA_18PLUS_KEY, JAVA_8_KEY};
 use crate::prelude::JavaVersion;
@@ -14,9 +14,12 @@ use chrono::Utc;
 use daedalus as d;
 use daedalus::minecraft::VersionInfo;
 use dunce::canonicalize;
+use hooks::{build_command, run_hook, ExitHooks, HookContext};
+use serde::{Deserialize, Serialize};
 use st::Profile;
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 use std::{process::Stdio, sync::Arc};
 use tokio::process::Command;
 use uuid::Uuid;
@@ -25,27 +28,93 @@ mod args;
 
 pub mod auth;
 pub mod download;
+pub mod hooks;
+pub mod verify;
+
+/// Options for a single launch of a profile
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// Where the game goes once it has started, instead of the title screen
+    pub quick_play: Option<QuickPlay>,
+}
+
+/// A world or server to launch straight into
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickPlay {
+    /// A world, by the name of its folder in saves
+    Singleplayer(String),
+    /// A server address, with an optional port
+    Multiplayer(String),
+    /// A realm, by its ID
+    Realms(String),
+}
+
+/// The launcher features that Minecraft's argument rules can depend on
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Features<'a> {
+    pub custom_resolution: bool,
+    pub quick_play: Option<&'a QuickPlay>,
+}
+
+impl Features<'_> {
+    // A rule matches when every feature it names has the value it expects
+    fn matches(&self, rule: &d::minecraft::FeatureRule) -> bool {
+        let quick_play = self.quick_play;
+        [
+            (rule.is_demo_user, false),
+            (rule.has_custom_resolution, self.custom_resolution),
+            (rule.has_quick_plays_support, quick_play.is_some()),
+            (
+                rule.is_quick_play_singleplayer,
+                matches!(quick_play, Some(QuickPlay::Singleplayer(_))),
+            ),
+            (
+                rule.is_quick_play_multiplayer,
+                matches!(quick_play, Some(QuickPlay::Multiplayer(_))),
+            ),
+            (
+                rule.is_quick_play_realms,
+                matches!(quick_play, Some(QuickPlay::Realms(_))),
+            ),
+        ]
+        .iter()
+        .all(|(expected, actual)| match expected {
+            Some(expected) => expected == actual,
+            None => true,
+        })
+    }
+}
 
 #[tracing::instrument]
-pub fn parse_rule(rule: &d::minecraft::Rule, java_version: &str) -> bool {
+pub fn parse_rule(
+    rule: &d::minecraft::Rule,
+    java_version: &str,
+    features: &Features,
+) -> bool {
     use d::minecraft::{Rule, RuleAction};
 
     let res = match rule {
         Rule {
-            os: Some(ref os), ..
-        } => crate::util::platform::os_rule(os, java_version),
+            os: None,
+            features: None,
+            ..
+        } => false,
         Rule {
-            features: Some(ref features),
+            os,
+            features: rule_features,
             ..
         } => {
-            !features.is_demo_user.unwrap_or(true)
-                || features.has_custom_resolution.unwrap_or(false)
-                || !features.has_quick_plays_support.unwrap_or(true)
-                || !features.is_quick_play_multiplayer.unwrap_or(true)
-                || !features.is_quick_play_realms.unwrap_or(true)
-                || !features.is_quick_play_singleplayer.unwrap_or(true)
+            let os_matches = match os {
+                Some(os) => crate::util::platform::os_rule(os, java_version),
+                None => true,
+            };
+            let features_matches = match rule_features {
+                Some(rule_features) => features.matches(rule_features),
+                None => true,
+            };
+            os_matches && features_matches
         }
-        _ => false,
     };
 
     match rule.action {
@@ -98,6 +167,67 @@ pub async fn get_java_version_from_profile(
     }
 }
 
+/// Checks an installed profile's game files against their hashes, and
+/// downloads the ones that don't match again if `repair` is set
+#[tracing::instrument(skip(profile))]
+#[theseus_macros::debug_pin]
+pub async fn verify_minecraft(
+    profile: &Profile,
+    repair: bool,
+) -> crate::Result<verify::VerifyReport> {
+    let state = State::get().await?;
+    let metadata = state.metadata.read().await;
+
+    let version = metadata
+        .minecraft
+        .versions
+        .iter()
+        .find(|it| it.id == profile.metadata.game_version)
+        .ok_or(crate::ErrorKind::LauncherError(format!(
+            "Invalid game version: {}",
+            profile.metadata.game_version
+        )))?;
+
+    let loading_bar = init_loading(
+        LoadingBarType::ProfileVerify {
+            profile_path: profile.path.clone(),
+            profile_name: profile.metadata.name.clone(),
+        },
+        100.0,
+        "Verifying game files",
+    )
+    .await?;
+
+    let version_info = download::download_version_info(
+        &state,
+        version,
+        profile.metadata.loader_version.as_ref(),
+        None,
+        None,
+    )
+    .await?;
+    let assets_index =
+        download::download_assets_index(&state, &version_info, None).await?;
+
+    let java_version = get_java_version_from_profile(profile, &version_info)
+        .await?
+        .ok_or_else(|| {
+            crate::ErrorKind::OtherError(
+                "No available java installation".to_string(),
+            )
+        })?;
+
+    verify::verify_files(
+        &state,
+        &version_info,
+        &assets_index,
+        &java_version.architecture,
+        repair,
+        &loading_bar,
+    )
+    .await
+}
+
 #[tracing::instrument(skip(profile))]
 #[theseus_macros::debug_pin]
 pub async fn install_minecraft(
@@ -116,6 +246,18 @@ pub async fn install_minecraft(
     )
     .await?;
 
+    let state = State::get().await?;
+    let hooks = match &profile.hooks {
+        Some(hooks) => hooks.clone(),
+        None => state.settings.read().await.hooks.clone(),
+    };
+    run_hook(
+        "pre-install",
+        &hooks.pre_install,
+        &HookContext::new(profile, None),
+    )
+    .await?;
+
     crate::api::profile::edit(&profile.path, |prof| {
         prof.install_stage = ProfileInstallStage::Installing;
 
@@ -124,7 +266,6 @@ pub async fn install_minecraft(
     .await?;
     State::sync().await?;
 
-    let state = State::get().await?;
     let instance_path = &canonicalize(&profile.path)?;
     let metadata = state.metadata.read().await;
 
@@ -279,21 +420,30 @@ pub async fn install_minecraft(
     State::sync().await?;
     emit_loading(&loading_bar, 1.0, Some("Finished installing")).await?;
 
+    // The install itself succeeded, so a failing hook is only logged
+    let context =
+        HookContext::new(profile, Some(Path::new(&java_version.path)));
+    if let Err(err) =
+        run_hook("post-install", &hooks.post_install, &context).await
+    {
+        tracing::warn!("Error running post-install hook: {err}");
+    }
+
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 #[tracing::instrument]
 #[theseus_macros::debug_pin]
-#[allow(clippy::too_many_arguments)]
 pub async fn launch_minecraft(
     java_args: &[String],
     env_args: &[(String, String)],
-    wrapper: &Option<String>,
+    hooks: &st::Hooks,
     memory: &st::MemorySettings,
     resolution: &st::WindowSize,
     credentials: &auth::Credentials,
-    post_exit_hook: Option<Command>,
     profile: &Profile,
+    options: &LaunchOptions,
 ) -> crate::Result<Arc<tokio::sync::RwLock<MinecraftChild>>> {
     if profile.install_stage == ProfileInstallStage::PackInstalling
         || profile.install_stage == ProfileInstallStage::Installing
@@ -352,10 +502,45 @@ pub async fn launch_minecraft(
         .version_dir(&version_jar)
         .join(format!("{version_jar}.jar"));
 
+    let hook_context =
+        HookContext::new(profile, Some(Path::new(&java_version.path)));
+    run_hook("pre-launch", &hooks.pre_launch, &hook_context).await?;
+
+    let wrapper = match &hooks.wrapper {
+        Some(hook) => build_command(hook, &hook_context)?,
+        None => None,
+    };
+
+    let features = Features {
+        // The window size is always passed, from the profile or settings
+        custom_resolution: true,
+        quick_play: options.quick_play.as_ref(),
+    };
+
+    // Minecraft logs what it joined through Quick Play here
+    let quick_play_path = instance_path.join("quickPlay").join("log.json");
+    match &options.quick_play {
+        Some(QuickPlay::Singleplayer(world))
+            if !instance_path.join("saves").join(world).is_dir() =>
+        {
+            return Err(crate::ErrorKind::LauncherError(format!(
+                "World {world} does not exist in {}",
+                profile.metadata.name
+            ))
+            .as_error());
+        }
+        Some(_) => {
+            if let Some(parent) = quick_play_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+        }
+        None => {}
+    }
+
     let args = version_info.arguments.clone().unwrap_or_default();
     let mut command = match wrapper {
-        Some(hook) => {
-            wrap_ref_builder!(it = Command::new(hook) => {it.arg(&java_version.path)})
+        Some(wrapper) => {
+            wrap_ref_builder!(it = wrapper => {it.arg(&java_version.path)})
         }
         None => Command::new(&java_version.path),
     };
@@ -365,7 +550,7 @@ pub async fn launch_minecraft(
     // Check if profile has a running profile, and reject running the command if it does
     // Done late so a quick double call doesn't launch two instances
     let existing_processes =
-        process::get_uuids_by_profile_path(instance_path).await?;
+        process::get_uuids_by_profile_uuid(&profile.uuid).await?;
     if let Some(uuid) = existing_processes.first() {
         return Err(crate::ErrorKind::LauncherError(format!(
             "Profile {} is already running at UUID: {uuid}",
@@ -409,6 +594,8 @@ pub async fn launch_minecraft(
                 &version.type_,
                 *resolution,
                 &java_version.architecture,
+                &features,
+                &quick_play_path,
             )?
             .into_iter()
             .collect::<Vec<_>>(),
@@ -490,10 +677,12 @@ pub async fn launch_minecraft(
     state_children
         .insert_process(
             Uuid::new_v4(),
+            profile.uuid,
             instance_path.to_path_buf(),
+            credentials.id,
             stdout_log_path,
             command,
-            post_exit_hook,
+            ExitHooks::new(hooks, hook_context),
             censor_strings,
         )
         .await
@@ -0,0 +1,346 @@
+//! Verification of installed Minecraft files against their hashes
+use crate::{
+    event::{
+        emit::{emit_loading, loading_try_for_each_concurrent},
+        LoadingBarId,
+    },
+    state::State,
+    util::{fetch::*, platform::OsExt},
+};
+use daedalus::{
+    self as d,
+    minecraft::{AssetsIndex, Os, VersionInfo as GameVersionInfo},
+};
+use futures::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokio::fs;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifiedFileKind {
+    Client,
+    Library,
+    Native,
+    AssetObject,
+}
+
+/// A file that is missing or doesn't match its hash
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileMismatch {
+    pub kind: VerifiedFileKind,
+    pub path: PathBuf,
+    pub missing: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VerifyReport {
+    /// Number of files checked
+    pub checked: usize,
+    pub mismatches: Vec<FileMismatch>,
+    /// Whether the mismatched files were downloaded again
+    pub repaired: bool,
+}
+
+struct FileCheck {
+    kind: VerifiedFileKind,
+    path: PathBuf,
+    url: String,
+    /// Files without a known hash are only checked to exist
+    sha1: Option<String>,
+}
+
+// Gets the sha1 of a file, or None if it doesn't exist
+async fn file_sha1(
+    path: &Path,
+    semaphore: &IoSemaphore,
+) -> crate::Result<Option<String>> {
+    let bytes = {
+        let io_semaphore = semaphore.0.read().await;
+        let _permit = io_semaphore.acquire().await?;
+        match fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(None)
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    let hash = tokio::task::spawn_blocking(move || {
+        sha1::Sha1::from(bytes).hexdigest()
+    })
+    .await?;
+    Ok(Some(hash))
+}
+
+fn file_checks(
+    st: &State,
+    version_info: &GameVersionInfo,
+    assets_index: &AssetsIndex,
+    java_arch: &str,
+) -> crate::Result<Vec<FileCheck>> {
+    let mut checks = Vec::new();
+
+    if let Some(client) = version_info
+        .downloads
+        .get(&d::minecraft::DownloadType::Client)
+    {
+        let version = &version_info.id;
+        checks.push(FileCheck {
+            kind: VerifiedFileKind::Client,
+            path: st
+                .directories
+                .version_dir(version)
+                .join(format!("{version}.jar")),
+            url: client.url.clone(),
+            sha1: Some(client.sha1.clone()),
+        });
+    }
+
+    for library in &version_info.libraries {
+        if let Some(rules) = &library.rules {
+            if !rules.iter().any(|x| {
+                super::parse_rule(x, java_arch, &super::Features::default())
+            }) {
+                continue;
+            }
+        }
+
+        let artifact_path = d::get_path_from_artifact(&library.name)?;
+        let path = st.directories.libraries_dir().join(&artifact_path);
+        match library.downloads {
+            Some(d::minecraft::LibraryDownloads {
+                artifact: Some(ref artifact),
+                ..
+            }) => {
+                // Artifacts without a URL are made by forge processors
+                // while installing, so only an install can fix them
+                if !artifact.url.is_empty() {
+                    checks.push(FileCheck {
+                        kind: VerifiedFileKind::Library,
+                        path,
+                        url: artifact.url.clone(),
+                        sha1: Some(artifact.sha1.clone()),
+                    });
+                }
+            }
+            _ => checks.push(FileCheck {
+                kind: VerifiedFileKind::Library,
+                path,
+                url: [
+                    library
+                        .url
+                        .as_deref()
+                        .unwrap_or("https://libraries.minecraft.net/"),
+                    &artifact_path,
+                ]
+                .concat(),
+                sha1: None,
+            }),
+        }
+    }
+
+    for asset in assets_index.objects.values() {
+        let hash = &asset.hash;
+        checks.push(FileCheck {
+            kind: VerifiedFileKind::AssetObject,
+            path: st.directories.object_dir(hash),
+            url: format!(
+                "https://resources.download.minecraft.net/{}/{hash}",
+                &hash[..2]
+            ),
+            sha1: Some(hash.clone()),
+        });
+    }
+
+    Ok(checks)
+}
+
+// Natives are extracted from their classifier jars, which aren't kept, so
+// each jar is downloaded (checking its hash) and its files compared with
+// the extracted ones. Returns the number of files checked and the ones that
+// differ
+async fn verify_natives(
+    st: &State,
+    version_info: &GameVersionInfo,
+    java_arch: &str,
+    repair: bool,
+) -> crate::Result<(usize, Vec<FileMismatch>)> {
+    let natives_dir = st.directories.version_natives_dir(&version_info.id);
+    let mut checked = 0;
+    let mut mismatches = Vec::new();
+
+    for library in &version_info.libraries {
+        if let Some(rules) = &library.rules {
+            if !rules.iter().any(|x| {
+                super::parse_rule(x, java_arch, &super::Features::default())
+            }) {
+                continue;
+            }
+        }
+
+        let native = library
+            .natives
+            .as_ref()
+            .and_then(|x| x.get(&Os::native_arch(java_arch)))
+            .zip(
+                library
+                    .downloads
+                    .as_ref()
+                    .and_then(|x| x.classifiers.as_ref()),
+            )
+            .and_then(|(os_key, classifiers)| {
+                classifiers.get(
+                    &os_key
+                        .replace("${arch}", crate::util::platform::ARCH_WIDTH),
+                )
+            });
+        let native = match native {
+            Some(native) => native,
+            None => continue,
+        };
+
+        let data =
+            fetch(&native.url, Some(&native.sha1), &st.fetch_semaphore).await?;
+        let dir = natives_dir.clone();
+        let url = native.url.clone();
+        let (library_checked, library_mismatches) =
+            tokio::task::spawn_blocking(move || {
+                let mut archive = zip::ZipArchive::new(std::io::Cursor::new(
+                    &data,
+                ))
+                .map_err(|err| {
+                    crate::ErrorKind::LauncherError(format!(
+                        "Could not read native {}: {err}",
+                        url
+                    ))
+                })?;
+
+                let mut checked = 0;
+                let mut mismatches = Vec::new();
+                for index in 0..archive.len() {
+                    let mut file = archive.by_index(index).map_err(|err| {
+                        crate::ErrorKind::LauncherError(format!(
+                            "Could not read native {}: {err}",
+                            url
+                        ))
+                    })?;
+                    let name = match file.enclosed_name() {
+                        Some(name) if file.is_file() => name.to_path_buf(),
+                        _ => continue,
+                    };
+
+                    checked += 1;
+                    let mut expected = Vec::new();
+                    file.read_to_end(&mut expected)?;
+                    let path = dir.join(name);
+                    match std::fs::read(&path) {
+                        Ok(actual) if actual == expected => {}
+                        actual => mismatches.push(FileMismatch {
+                            kind: VerifiedFileKind::Native,
+                            path,
+                            missing: actual.is_err(),
+                        }),
+                    }
+                }
+
+                if repair && !mismatches.is_empty() {
+                    archive.extract(&dir).map_err(|err| {
+                        crate::ErrorKind::LauncherError(format!(
+                            "Could not extract native {}: {err}",
+                            url
+                        ))
+                    })?;
+                }
+
+                Ok::<_, crate::Error>((checked, mismatches))
+            })
+            .await??;
+
+        checked += library_checked;
+        mismatches.extend(library_mismatches);
+    }
+
+    Ok((checked, mismatches))
+}
+
+/// Checks the client jar, libraries, natives and asset objects of a version
+/// against their hashes, downloading the ones that don't match again if
+/// `repair` is set
+#[tracing::instrument(skip(st, version_info, assets_index))]
+#[theseus_macros::debug_pin]
+pub async fn verify_files(
+    st: &State,
+    version_info: &GameVersionInfo,
+    assets_index: &AssetsIndex,
+    java_arch: &str,
+    repair: bool,
+    loading_bar: &LoadingBarId,
+) -> crate::Result<VerifyReport> {
+    let checks = file_checks(st, version_info, assets_index, java_arch)?;
+    let num_checks = checks.len();
+    let mismatches = Mutex::new(Vec::new());
+
+    loading_try_for_each_concurrent(
+        stream::iter(checks).map(Ok::<FileCheck, crate::Error>),
+        None,
+        Some(loading_bar),
+        90.0,
+        num_checks,
+        None,
+        |check| {
+            let mismatches = &mismatches;
+            async move {
+                let hash = file_sha1(&check.path, &st.io_semaphore).await?;
+                let matches = match (&hash, &check.sha1) {
+                    (None, _) => false,
+                    (Some(hash), Some(expected)) => hash == expected,
+                    (Some(_), None) => true,
+                };
+                if matches {
+                    return Ok(());
+                }
+
+                tracing::warn!(
+                    "{} does not match its hash",
+                    check.path.display()
+                );
+                if repair {
+                    let bytes = fetch(
+                        &check.url,
+                        check.sha1.as_deref(),
+                        &st.fetch_semaphore,
+                    )
+                    .await?;
+                    write(&check.path, &bytes, &st.io_semaphore).await?;
+                }
+
+                if let Ok(mut mismatches) = mismatches.lock() {
+                    mismatches.push(FileMismatch {
+                        kind: check.kind,
+                        path: check.path,
+                        missing: hash.is_none(),
+                    });
+                }
+                Ok(())
+            }
+        },
+    )
+    .await?;
+
+    let mut mismatches =
+        mismatches.into_inner().unwrap_or_else(|x| x.into_inner());
+    let (natives_checked, natives_mismatches) =
+        verify_natives(st, version_info, java_arch, repair).await?;
+    mismatches.extend(natives_mismatches);
+    emit_loading(loading_bar, 10.0, None).await?;
+
+    Ok(VerifyReport {
+        checked: num_checks + natives_checked,
+        mismatches,
+        repaired: repair,
+    })
+}
@@ -1,4 +1,5 @@
-use super::Profile;
+use super::{record_session, Profile, Session};
+use chrono::{DateTime, Utc};
 use std::path::{Path, PathBuf};
 use std::process::ExitStatus;
 use std::{collections::HashMap, sync::Arc};
@@ -12,7 +13,9 @@ use tracing::error;
 
 use crate::event::emit::emit_process;
 use crate::event::ProcessPayloadType;
+use crate::launcher::hooks::ExitHooks;
 use crate::EventState;
+use crate::State;
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
@@ -24,7 +27,8 @@ pub struct Children(HashMap<Uuid, Arc<RwLock<MinecraftChild>>>);
 #[derive(Debug)]
 pub struct MinecraftChild {
     pub uuid: Uuid,
-    pub profile_path: PathBuf, //todo: make UUID when profiles are recognized by UUID
+    pub profile_uuid: Uuid,
+    pub profile_path: PathBuf,
     pub manager: Option<JoinHandle<crate::Result<ExitStatus>>>, // None when future has completed and been handled
     pub current_child: Arc<RwLock<Child>>,
     pub output: SharedOutput,
@@ -39,19 +43,23 @@ impl Children {
     // The threads for stdout and stderr are spawned here
     // Unlike a Hashmap's 'insert', this directly returns the reference to the MinecraftChild rather than any previously stored MinecraftChild that may exist
 
+    #[allow(clippy::too_many_arguments)]
     #[tracing::instrument(skip(self))]
     #[theseus_macros::debug_pin]
     pub async fn insert_process(
         &mut self,
         uuid: Uuid,
+        profile_uuid: Uuid,
         profile_path: PathBuf,
+        user: Uuid,
         log_path: PathBuf,
         mut mc_command: Command,
-        post_command: Option<Command>, // Command to run after minecraft.
+        exit_hooks: ExitHooks, // Hooks to run after minecraft.
         censor_strings: HashMap<String, String>,
     ) -> crate::Result<Arc<RwLock<MinecraftChild>>> {
         // Takes the first element of the commands vector and spawns it
         let mut child = mc_command.spawn()?;
+        let start = Utc::now();
 
         // Create std watcher threads for stdout and stderr
         let shared_output =
@@ -82,13 +90,17 @@ impl Children {
         let current_child = Arc::new(RwLock::new(child));
         let manager = Some(tokio::spawn(Self::sequential_process_manager(
             uuid,
-            post_command,
+            profile_uuid,
+            user,
+            start,
+            exit_hooks,
             pid,
             current_child.clone(),
         )));
 
         emit_process(
             uuid,
+            profile_uuid,
             pid,
             ProcessPayloadType::Launched,
             "Launched Minecraft",
@@ -98,6 +110,7 @@ impl Children {
         // Create MinecraftChild
         let mchild = MinecraftChild {
             uuid,
+            profile_uuid,
             profile_path,
             current_child,
             output: shared_output,
@@ -116,7 +129,10 @@ impl Children {
     #[theseus_macros::debug_pin]
     async fn sequential_process_manager(
         uuid: Uuid,
-        post_command: Option<Command>,
+        profile_uuid: Uuid,
+        user: Uuid,
+        start: DateTime<Utc>,
+        exit_hooks: ExitHooks,
         mut current_pid: u32,
         current_child: Arc<RwLock<Child>>,
     ) -> crate::Result<ExitStatus> {
@@ -131,6 +147,20 @@ impl Children {
             }
         }
 
+        // The session only covers Minecraft itself, not the post-commands
+        let session = Session {
+            start,
+            end: Utc::now(),
+            exit_code: mc_exit_status.code(),
+            user,
+        };
+        let state = State::get().await?;
+        if let Err(err) =
+            record_session(&state.directories, profile_uuid, session).await
+        {
+            error!("Failed to record play session: {}", err);
+        }
+
         // If in tauri, window should show itself again after process exists if it was hidden
         #[cfg(feature = "tauri")]
         {
@@ -141,8 +171,19 @@ impl Children {
         }
 
         if !mc_exit_status.success() {
+            // A game killed by a signal (such as when it's stopped from the
+            // launcher) has no exit code, and didn't crash
+            if mc_exit_status.code().is_some() {
+                if let Err(err) =
+                    exit_hooks.run_on_crash(mc_exit_status.code()).await
+                {
+                    error!("Error running on-crash hook: {}", err);
+                }
+            }
+
             emit_process(
                 uuid,
+                profile_uuid,
                 current_pid,
                 ProcessPayloadType::Finished,
                 "Exited process",
@@ -153,7 +194,9 @@ impl Children {
         }
 
         // If a post-command exist, switch to it and wait on it
-        if let Some(mut m_command) = post_command {
+        if let Some(mut m_command) =
+            exit_hooks.post_exit(mc_exit_status.code())?
+        {
             {
                 let mut current_child = current_child.write().await;
                 let new_child = m_command.spawn()?;
@@ -167,6 +210,7 @@ impl Children {
             }
             emit_process(
                 uuid,
+                profile_uuid,
                 current_pid,
                 ProcessPayloadType::Updated,
                 "Completed Minecraft, switching to post-commands",
@@ -183,6 +227,7 @@ impl Children {
 
         emit_process(
             uuid,
+            profile_uuid,
             current_pid,
             ProcessPayloadType::Finished,
             "Exited process",
@@ -232,10 +277,10 @@ impl Children {
         Ok(keys)
     }
 
-    // Gets all PID keys of running children with a given profile path
+    // Gets all PID keys of running children with a given profile UUID
     pub async fn running_keys_with_profile(
         &self,
-        profile_path: &Path,
+        profile_uuid: &Uuid,
     ) -> crate::Result<Vec<Uuid>> {
         let running_keys = self.running_keys().await?;
         let mut keys = Vec::new();
@@ -243,7 +288,7 @@ impl Children {
             if let Some(child) = self.get(&key) {
                 let child = child.clone();
                 let child = child.read().await;
-                if child.profile_path == profile_path {
+                if &child.profile_uuid == profile_uuid {
                     keys.push(key);
                 }
             }
@@ -251,6 +296,21 @@ impl Children {
         Ok(keys)
     }
 
+    // Gets the UUIDs of all profiles of running children
+    pub async fn running_profile_uuids(&self) -> crate::Result<Vec<Uuid>> {
+        let mut profiles = Vec::new();
+        for key in self.keys() {
+            if let Some(child) = self.get(&key) {
+                let child = child.clone();
+                let child = child.write().await;
+                if child.current_child.write().await.try_wait()?.is_none() {
+                    profiles.push(child.profile_uuid);
+                }
+            }
+        }
+        Ok(profiles)
+    }
+
     // Gets all profiles of running children
     pub async fn running_profile_paths(&self) -> crate::Result<Vec<PathBuf>> {
         let mut profiles = Vec::new();
@@ -275,8 +335,8 @@ impl Children {
                 let child = child.clone();
                 let child = child.write().await;
                 if child.current_child.write().await.try_wait()?.is_none() {
-                    if let Some(prof) = crate::api::profile::get(
-                        &child.profile_path.clone(),
+                    if let Some(prof) = crate::api::profile::get_by_uuid(
+                        &child.profile_uuid,
                         None,
                     )
                     .await?
@@ -122,6 +122,12 @@ impl DirectoryInfo {
         self.config_dir.join("profiles")
     }
 
+    /// Get the directory that profiles which fail to load are moved to
+    #[inline]
+    pub fn broken_profiles_dir(&self) -> PathBuf {
+        self.config_dir.join("broken_profiles")
+    }
+
     /// Gets the logs dir for a given profile
     #[inline]
     pub fn profile_logs_dir(&self, profile: uuid::Uuid) -> PathBuf {
@@ -130,11 +136,65 @@ impl DirectoryInfo {
             .join("modrinth_logs")
     }
 
+    /// Get the snapshots directory for profile snapshots
+    #[inline]
+    pub fn snapshots_dir(&self) -> PathBuf {
+        self.config_dir.join("snapshots")
+    }
+
+    /// Gets the snapshots dir for a given profile
+    #[inline]
+    pub fn profile_snapshots_dir(&self, profile: uuid::Uuid) -> PathBuf {
+        self.snapshots_dir().join(profile.to_string())
+    }
+
+    /// Get the directory for world backups
+    #[inline]
+    pub fn world_backups_dir(&self) -> PathBuf {
+        self.config_dir.join("world_backups")
+    }
+
+    /// Gets the world backups dir for a given profile
+    #[inline]
+    pub fn profile_world_backups_dir(&self, profile: uuid::Uuid) -> PathBuf {
+        self.world_backups_dir().join(profile.to_string())
+    }
+
+    /// Get the directory of recorded play sessions
+    #[inline]
+    pub fn sessions_dir(&self) -> PathBuf {
+        self.config_dir.join("sessions")
+    }
+
+    /// Gets the file of recorded play sessions for a given profile
+    #[inline]
+    pub fn profile_sessions_file(&self, profile: uuid::Uuid) -> PathBuf {
+        self.sessions_dir().join(format!("{profile}.json"))
+    }
+
+    /// Get the directory of folders shared between profiles
+    #[inline]
+    pub fn shared_dir(&self) -> PathBuf {
+        self.config_dir.join("shared")
+    }
+
+    /// Gets the central copy of a shared folder, which profiles link to
+    #[inline]
+    pub fn shared_folder_dir(&self, folder: &str) -> PathBuf {
+        self.shared_dir().join(folder)
+    }
+
     #[inline]
     pub fn launcher_logs_dir(&self) -> PathBuf {
         self.config_dir.join("launcher_logs")
     }
 
+    /// Get the directory unused files are moved to when they're quarantined
+    #[inline]
+    pub fn quarantine_dir(&self) -> PathBuf {
+        self.config_dir.join("quarantine")
+    }
+
     /// Get the file containing the global database
     #[inline]
     pub fn database_file(&self) -> PathBuf {
@@ -158,6 +218,18 @@ impl DirectoryInfo {
         self.config_dir.join("caches").join("metadata")
     }
 
+    /// Get the directory of cached profile and project icons
+    #[inline]
+    pub fn caches_icons_dir(&self) -> PathBuf {
+        self.caches_dir().join("icons")
+    }
+
+    /// Get the file caching the hashes of project files
+    #[inline]
+    pub fn file_hashes_file(&self) -> PathBuf {
+        self.caches_dir().join("file_hashes.json")
+    }
+
     /// Get path from environment variable
     #[inline]
     fn env_path(name: &str) -> Option<PathBuf> {
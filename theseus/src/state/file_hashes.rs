@@ -0,0 +1,177 @@
+//! Persistent cache of project file hashes
+//! Projects are looked up on Modrinth by hash, and hashing every jar of
+//! every profile on each start is slow. Hashes are kept by path, and reused
+//! as long as the file's size and modification time haven't changed
+use crate::state::DirectoryInfo;
+use crate::util::fetch::IoSemaphore;
+use crate::util::io::{read_with_backup, write_atomic};
+use crate::State;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+
+// Files are hashed in chunks of this size, so they're never read into
+// memory whole
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct FileHashes {
+    pub sha512: String,
+    pub sha1: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CachedHashes {
+    size: u64,
+    /// Modification time, in milliseconds since the Unix epoch
+    modified: u64,
+    #[serde(flatten)]
+    hashes: FileHashes,
+}
+
+#[derive(Default)]
+pub(crate) struct FileHashCache {
+    entries: HashMap<PathBuf, CachedHashes>,
+}
+
+impl FileHashCache {
+    #[tracing::instrument]
+    pub async fn init(dirs: &DirectoryInfo) -> Self {
+        let path = dirs.file_hashes_file();
+        if !path.exists() {
+            return Self::default();
+        }
+
+        // A cache that can't be read is rebuilt as files are hashed again
+        match read_with_backup(&path, |bytes| {
+            Ok(serde_json::from_slice(bytes)?)
+        })
+        .await
+        {
+            Ok(entries) => Self { entries },
+            Err(err) => {
+                tracing::warn!("Unable to read file hash cache: {err}");
+                Self::default()
+            }
+        }
+    }
+
+    async fn sync(&self, dirs: &DirectoryInfo) -> crate::Result<()> {
+        write_atomic(
+            &dirs.file_hashes_file(),
+            &serde_json::to_vec(&self.entries)?,
+        )
+        .await
+    }
+
+    /// Forgets the hashes of files that no longer exist
+    pub async fn prune(&mut self, dirs: &DirectoryInfo) -> crate::Result<()> {
+        let len = self.entries.len();
+        self.entries.retain(|path, _| path.exists());
+        if self.entries.len() != len {
+            self.sync(dirs).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// Size and modification time of a file, which key its cached hashes
+async fn file_key(path: &Path) -> crate::Result<(u64, u64)> {
+    let metadata = fs::metadata(path).await?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis() as u64)
+        .unwrap_or_default();
+    Ok((metadata.len(), modified))
+}
+
+// Hashes a file while reading it in chunks
+fn hash_file(path: &Path) -> crate::Result<FileHashes> {
+    let mut file = std::fs::File::open(path)?;
+    let mut sha512 = sha2::Sha512::new();
+    let mut sha1 = sha1::Sha1::new();
+
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        sha512.update(&buffer[..read]);
+        sha1.update(&buffer[..read]);
+    }
+
+    Ok(FileHashes {
+        sha512: format!("{:x}", sha512.finalize()),
+        sha1: sha1.digest().to_string(),
+    })
+}
+
+/// Gets the hashes of files. Files that changed since they were last hashed
+/// are hashed concurrently, as many at once as the IO semaphore allows
+#[tracing::instrument(skip(io_semaphore))]
+pub async fn get_file_hashes(
+    paths: &[PathBuf],
+    io_semaphore: &IoSemaphore,
+) -> crate::Result<HashMap<PathBuf, FileHashes>> {
+    let state = State::get().await?;
+
+    let mut hashes = HashMap::new();
+    let mut changed = Vec::new();
+    {
+        let cache = state.file_hashes.read().await;
+        for path in paths {
+            let (size, modified) = file_key(path).await?;
+            match cache.entries.get(path) {
+                Some(cached)
+                    if cached.size == size && cached.modified == modified =>
+                {
+                    hashes.insert(path.clone(), cached.hashes.clone());
+                }
+                _ => changed.push((path.clone(), size, modified)),
+            }
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(hashes);
+    }
+
+    let hashed = futures::future::try_join_all(changed.into_iter().map(
+        |(path, size, modified)| async move {
+            let io_semaphore = io_semaphore.0.read().await;
+            let _permit = io_semaphore.acquire().await?;
+
+            let file_path = path.clone();
+            let hashes =
+                tokio::task::spawn_blocking(move || hash_file(&file_path))
+                    .await??;
+
+            Ok::<_, crate::Error>((
+                path,
+                CachedHashes {
+                    size,
+                    modified,
+                    hashes,
+                },
+            ))
+        },
+    ))
+    .await?;
+
+    // The cache is written while locked, so concurrent writes don't race
+    let mut cache = state.file_hashes.write().await;
+    for (path, cached) in hashed {
+        hashes.insert(path.clone(), cached.hashes.clone());
+        cache.entries.insert(path, cached);
+    }
+    cache.sync(&state.directories).await?;
+
+    Ok(hashes)
+}
@@ -0,0 +1,149 @@
+//! Minecraft's options.txt
+//! Each line is a `key:value` pair. Lines are kept in file order, and keys
+//! Theseus doesn't know about are written back untouched, so options added
+//! by newer versions or by mods survive an edit
+use crate::util::io::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs;
+
+pub const OPTIONS_TXT: &str = "options.txt";
+
+/// Groups of options that can be copied between profiles
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionCategory {
+    Keybinds,
+    Video,
+    Audio,
+    /// The enabled resource packs, in the order they're applied
+    ResourcePacks,
+    Language,
+}
+
+const VIDEO_OPTIONS: &[&str] = &[
+    "ao",
+    "attackIndicator",
+    "biomeBlendRadius",
+    "bobView",
+    "chunkSectionFadeInTime",
+    "darknessEffectScale",
+    "enableVsync",
+    "entityDistanceScaling",
+    "entityShadows",
+    "fov",
+    "fovEffectScale",
+    "fullscreen",
+    "fullscreenResolution",
+    "gamma",
+    "glintSpeed",
+    "glintStrength",
+    "graphicsMode",
+    "guiScale",
+    "maxFps",
+    "mipmapLevels",
+    "particles",
+    "prioritizeChunkUpdates",
+    "renderClouds",
+    "renderDistance",
+    "screenEffectScale",
+    "simulationDistance",
+];
+
+const AUDIO_OPTIONS: &[&str] =
+    &["directionalAudio", "showSubtitles", "soundDevice"];
+
+const RESOURCE_PACK_OPTIONS: &[&str] =
+    &["resourcePacks", "incompatibleResourcePacks"];
+
+impl OptionCategory {
+    pub fn contains(&self, key: &str) -> bool {
+        match self {
+            OptionCategory::Keybinds => key.starts_with("key_"),
+            OptionCategory::Video => VIDEO_OPTIONS.contains(&key),
+            OptionCategory::Audio => {
+                key.starts_with("soundCategory_")
+                    || AUDIO_OPTIONS.contains(&key)
+            }
+            OptionCategory::ResourcePacks => {
+                RESOURCE_PACK_OPTIONS.contains(&key)
+            }
+            OptionCategory::Language => key == "lang",
+        }
+    }
+}
+
+/// The contents of an options.txt file
+#[derive(Debug, Clone, Default)]
+pub struct GameOptions {
+    entries: Vec<(String, String)>,
+}
+
+impl GameOptions {
+    /// Parses options.txt. Lines without a `:` are ignored, as Minecraft
+    /// ignores them
+    pub fn parse(contents: &str) -> Self {
+        Self {
+            entries: contents
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Reads a profile's options.txt, which is empty until the game has
+    /// been launched once
+    pub async fn read(profile_path: &Path) -> crate::Result<Self> {
+        let path = profile_path.join(OPTIONS_TXT);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(Self::parse(&fs::read_to_string(path).await?))
+    }
+
+    pub async fn write(&self, profile_path: &Path) -> crate::Result<()> {
+        write_atomic(
+            &profile_path.join(OPTIONS_TXT),
+            self.to_string().as_bytes(),
+        )
+        .await
+    }
+
+    /// Sets an option, keeping its line if it already exists
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.entries.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value.to_string(),
+            None => self.entries.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    /// Sets every option of another file that belongs to one of the given
+    /// categories
+    pub fn merge_categories(
+        &mut self,
+        other: &GameOptions,
+        categories: &[OptionCategory],
+    ) {
+        for (key, value) in &other.entries {
+            if categories.iter().any(|x| x.contains(key)) {
+                self.set(key, value);
+            }
+        }
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+impl std::fmt::Display for GameOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (key, value) in &self.entries {
+            writeln!(f, "{key}:{value}")?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 //! Theseus state management system
 use crate::event::emit::{emit_loading, init_loading_unsafe};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::event::LoadingBarType;
@@ -7,7 +8,7 @@ use crate::loading_join;
 
 use crate::state::users::Users;
 use crate::util::fetch::{FetchSemaphore, IoSemaphore};
-use notify::RecommendedWatcher;
+use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
 use std::sync::Arc;
 use std::time::Duration;
@@ -31,11 +32,29 @@ pub use self::settings::*;
 mod projects;
 pub use self::projects::*;
 
+mod mod_metadata;
+pub use self::mod_metadata::*;
+
+mod file_hashes;
+pub use self::file_hashes::*;
+
 mod users;
 
 mod children;
 pub use self::children::*;
 
+mod sessions;
+pub use self::sessions::*;
+
+mod profile_locks;
+pub use self::profile_locks::*;
+
+mod shared_folders;
+pub use self::shared_folders::*;
+
+mod game_options;
+pub use self::game_options::*;
+
 mod auth_task;
 pub use self::auth_task::*;
 
@@ -79,6 +98,10 @@ pub struct State {
     pub(crate) tags: RwLock<Tags>,
     /// Launcher processes that should be safely exited on shutdown
     pub(crate) safety_processes: RwLock<SafeProcesses>,
+    /// Locks that keep operations on the same profile from overlapping
+    pub(crate) profile_locks: ProfileLocks,
+    /// Hashes of project files, kept between launches
+    pub(crate) file_hashes: RwLock<FileHashCache>,
 
     /// File watcher debouncer
     pub(crate) file_watcher: RwLock<Debouncer<RecommendedWatcher>>,
@@ -99,9 +122,9 @@ impl State {
                     )
                     .await?;
 
-                    let mut file_watcher = init_watcher().await?;
-
                     let directories = DirectoryInfo::init()?;
+                    let mut file_watcher =
+                        init_watcher(directories.shared_dir()).await?;
                     emit_loading(&loading_bar, 10.0, None).await?;
 
                     // Settings
@@ -134,6 +157,7 @@ impl State {
                         users_fut,
                     }?;
 
+                    let file_hashes = FileHashCache::init(&directories).await;
                     let children = Children::new();
                     let auth_flow = AuthTask::new();
                     let safety_processes = SafeProcesses::new();
@@ -157,6 +181,8 @@ impl State {
                         auth_flow: RwLock::new(auth_flow),
                         tags: RwLock::new(tags),
                         safety_processes: RwLock::new(safety_processes),
+                        profile_locks: ProfileLocks::new(),
+                        file_hashes: RwLock::new(file_hashes),
                         file_watcher: RwLock::new(file_watcher),
                     }))
                 }
@@ -240,10 +266,12 @@ impl State {
     }
 }
 
-async fn init_watcher() -> crate::Result<Debouncer<RecommendedWatcher>> {
+async fn init_watcher(
+    shared_dir: PathBuf,
+) -> crate::Result<Debouncer<RecommendedWatcher>> {
     let (mut tx, mut rx) = channel(1);
 
-    let file_watcher = new_debouncer(
+    let mut file_watcher = new_debouncer(
         Duration::from_secs(2),
         None,
         move |res: DebounceEventResult| {
@@ -253,12 +281,37 @@ async fn init_watcher() -> crate::Result<Debouncer<RecommendedWatcher>> {
         },
     )?;
 
+    // Shared folders are watched here rather than through each profile
+    std::fs::create_dir_all(&shared_dir)?;
+    file_watcher
+        .watcher()
+        .watch(&shared_dir, RecursiveMode::Recursive)?;
+
     tokio::task::spawn(async move {
         while let Some(res) = rx.next().await {
             match res {
                 Ok(events) => {
-                    let mut visited_paths = Vec::new();
+                    // Changed paths by profile, relative to its folder
+                    let mut changed_paths: HashMap<PathBuf, Vec<PathBuf>> =
+                        HashMap::new();
+                    let mut visited_folders = Vec::new();
                     events.iter().for_each(|e| {
+                        if let Some(folder) = e
+                            .path
+                            .strip_prefix(&shared_dir)
+                            .ok()
+                            .and_then(|x| x.iter().next())
+                        {
+                            let folder = folder.to_string_lossy().to_string();
+                            if !visited_folders.contains(&folder) {
+                                Profile::sync_shared_folder_task(
+                                    folder.clone(),
+                                );
+                                visited_folders.push(folder);
+                            }
+                            return;
+                        }
+
                         let mut new_path = PathBuf::new();
                         let mut found = false;
 
@@ -281,11 +334,26 @@ async fn init_watcher() -> crate::Result<Debouncer<RecommendedWatcher>> {
                                 .unwrap_or(false)
                         {
                             Profile::crash_task(new_path);
-                        } else if !visited_paths.contains(&new_path) {
-                            Profile::sync_projects_task(new_path.clone());
-                            visited_paths.push(new_path);
+                        } else if let Ok(relative) =
+                            e.path.strip_prefix(&new_path)
+                        {
+                            let changed = changed_paths
+                                .entry(new_path.clone())
+                                .or_default();
+                            if !changed.iter().any(|x| relative.starts_with(x))
+                            {
+                                changed.push(relative.to_path_buf());
+                            }
                         }
                     });
+
+                    // Only the changed projects are read again
+                    for (profile_path, changed) in changed_paths {
+                        Profile::sync_changed_projects_task(
+                            profile_path,
+                            changed,
+                        );
+                    }
                 }
                 Err(errors) => errors.iter().for_each(|err| {
                     tracing::warn!("Unable to watch file: {err}")
@@ -0,0 +1,852 @@
+//! Metadata mod loaders read from mod jars
+//! Forge and NeoForge describe their mods in META-INF/mods.toml (older Forge
+//! in mcmod.info), Fabric in fabric.mod.json and Quilt in quilt.mod.json.
+//! Each of these is read into a ModMetadata, merged for jars made for several
+//! loaders, so mods that aren't on Modrinth can still be checked for missing
+//! dependencies and unsupported loaders
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+use zip::ZipArchive;
+
+// Mod ids of the game and loaders, which jars declare dependencies on to
+// state the versions they support
+const GAME_IDS: &[&str] = &["minecraft"];
+const LOADER_IDS: &[&str] =
+    &["forge", "neoforge", "fabricloader", "quilt_loader"];
+const IGNORED_IDS: &[&str] = &["java"];
+
+// Nested jars can nest jars too, but no further than this
+const MAX_NESTED_DEPTH: usize = 3;
+
+/// A mod a jar declares it depends on
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ModDependency {
+    pub mod_id: String,
+    /// Versions of the dependency that work, in the range syntax of the
+    /// jar's loader
+    pub version_range: Option<String>,
+    /// Whether the mod fails to load without it
+    pub mandatory: bool,
+    /// The loader whose metadata declares the dependency. Jars made for
+    /// several loaders declare different dependencies for each
+    #[serde(default)]
+    pub loader: Option<String>,
+}
+
+/// A jar shipped inside another jar, which its loader loads as well
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NestedJar {
+    /// Path of the jar inside the outer jar
+    pub path: String,
+    /// Ids of the mods in the jar and in the jars nested in it
+    pub mod_ids: Vec<String>,
+}
+
+/// Everything read from a mod jar's metadata files
+#[derive(Clone, Debug, Default)]
+pub struct ModMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    pub version: Option<String>,
+    /// Path of the icon inside the jar
+    pub icon: Option<String>,
+    /// Ids of the mods in the jar, including ids they provide as aliases
+    pub mod_ids: Vec<String>,
+    /// Loaders the jar is made for, as Modrinth names them
+    pub loaders: Vec<String>,
+    pub dependencies: Vec<ModDependency>,
+    /// Minecraft versions the jar supports, in its loader's range syntax
+    pub game_versions: Option<String>,
+    /// Loader versions the jar supports, in its loader's range syntax
+    pub loader_versions: Option<String>,
+    pub nested_jars: Vec<NestedJar>,
+}
+
+impl ModMetadata {
+    /// Every mod id the jar ships, including those of its nested jars
+    pub fn all_mod_ids(&self) -> impl Iterator<Item = &String> {
+        self.mod_ids
+            .iter()
+            .chain(self.nested_jars.iter().flat_map(|x| &x.mod_ids))
+    }
+
+    // Sorts a dependency on the game or loader into the supported ranges,
+    // and any other into the dependencies
+    fn add_dependency(
+        &mut self,
+        mod_id: String,
+        version_range: Option<String>,
+        mandatory: bool,
+    ) {
+        if GAME_IDS.contains(&&*mod_id) {
+            self.game_versions = version_range;
+        } else if LOADER_IDS.contains(&&*mod_id) {
+            self.loader_versions = version_range;
+        } else if !IGNORED_IDS.contains(&&*mod_id)
+            && !self.mod_ids.contains(&mod_id)
+        {
+            self.dependencies.push(ModDependency {
+                mod_id,
+                version_range,
+                mandatory,
+                loader: self.loaders.first().cloned(),
+            });
+        }
+    }
+
+    // Adds what another loader's metadata file in the same jar declares.
+    // Descriptive fields are taken from the first file that has them
+    fn merge(&mut self, other: ModMetadata) {
+        self.title = self.title.take().or(other.title);
+        self.description = self.description.take().or(other.description);
+        if self.authors.is_empty() {
+            self.authors = other.authors;
+        }
+        self.version = self.version.take().or(other.version);
+        self.icon = self.icon.take().or(other.icon);
+        self.game_versions = self.game_versions.take().or(other.game_versions);
+        self.loader_versions =
+            self.loader_versions.take().or(other.loader_versions);
+
+        for mod_id in other.mod_ids {
+            if !self.mod_ids.contains(&mod_id) {
+                self.mod_ids.push(mod_id);
+            }
+        }
+        for loader in other.loaders {
+            if !self.loaders.contains(&loader) {
+                self.loaders.push(loader);
+            }
+        }
+        self.dependencies.extend(other.dependencies);
+        self.nested_jars.extend(other.nested_jars);
+
+        // Each loader's half of the jar may depend on the other's mod ids
+        let mod_ids = &self.mod_ids;
+        self.dependencies.retain(|x| !mod_ids.contains(&x.mod_id));
+    }
+}
+
+/// Reads the metadata of a mod jar, or None if it isn't a mod or can't be
+/// read
+pub async fn read_mod_metadata(
+    path: &Path,
+) -> crate::Result<Option<ModMetadata>> {
+    let path = path.to_path_buf();
+    Ok(tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(&path).ok()?;
+        let mut archive = ZipArchive::new(file).ok()?;
+        read_archive(&mut archive, 0)
+    })
+    .await?)
+}
+
+fn read_archive<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    depth: usize,
+) -> Option<ModMetadata> {
+    // Jars made for several loaders, as Architectury and Forgix build them,
+    // have a metadata file for each, so every one present is read
+    let parsed = vec![
+        read_file(archive, "META-INF/mods.toml")
+            .and_then(|x| parse_mods_toml(&x, "forge")),
+        read_file(archive, "META-INF/neoforge.mods.toml")
+            .and_then(|x| parse_mods_toml(&x, "neoforge")),
+        read_file(archive, "mcmod.info").and_then(|x| parse_mcmod_info(&x)),
+        read_file(archive, "fabric.mod.json")
+            .and_then(|x| parse_fabric_mod_json(&x)),
+        read_file(archive, "quilt.mod.json")
+            .and_then(|x| parse_quilt_mod_json(&x)),
+    ];
+    let mut metadata =
+        parsed.into_iter().flatten().reduce(|mut x, other| {
+            x.merge(other);
+            x
+        })?;
+
+    // Forge fills the version in from the jar's manifest at runtime
+    if metadata
+        .version
+        .as_deref()
+        .is_some_and(|x| x.contains("${file.jarVersion}"))
+    {
+        metadata.version = read_file(archive, "META-INF/MANIFEST.MF")
+            .and_then(|x| manifest_value(&x, "Implementation-Version"));
+    }
+
+    let mut nested_paths = std::mem::take(&mut metadata.nested_jars)
+        .into_iter()
+        .map(|x| x.path)
+        .collect::<Vec<_>>();
+    if let Some(jarjar) = read_file(archive, "META-INF/jarjar/metadata.json") {
+        nested_paths.extend(parse_jarjar_metadata(&jarjar));
+    }
+    // Fabric and Quilt list their nested jars, but some build tools only
+    // place them in META-INF/jars
+    nested_paths.extend(
+        archive
+            .file_names()
+            .filter(|x| x.starts_with("META-INF/jars/") && x.ends_with(".jar"))
+            .map(String::from)
+            .collect::<Vec<_>>(),
+    );
+    nested_paths.sort();
+    nested_paths.dedup();
+
+    metadata.nested_jars = nested_paths
+        .into_iter()
+        .map(|path| {
+            let mod_ids = if depth < MAX_NESTED_DEPTH {
+                read_bytes(archive, &path)
+                    .and_then(|bytes| {
+                        let mut nested =
+                            ZipArchive::new(Cursor::new(bytes)).ok()?;
+                        read_archive(&mut nested, depth + 1)
+                    })
+                    .map(|x| x.all_mod_ids().cloned().collect())
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            NestedJar { path, mod_ids }
+        })
+        .collect();
+
+    Some(metadata)
+}
+
+fn read_bytes<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Option<Vec<u8>> {
+    let mut file = archive.by_name(name).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+fn read_file<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Option<String> {
+    String::from_utf8(read_bytes(archive, name)?).ok()
+}
+
+fn manifest_value(manifest: &str, key: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    })
+}
+
+fn parse_mods_toml(contents: &str, loader: &str) -> Option<ModMetadata> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ModsToml {
+        #[serde(default)]
+        mods: Vec<ForgeMod>,
+        #[serde(default)]
+        dependencies: HashMap<String, Vec<ForgeDependency>>,
+        logo_file: Option<String>,
+        authors: Option<String>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ForgeMod {
+        mod_id: String,
+        version: Option<String>,
+        display_name: Option<String>,
+        description: Option<String>,
+        logo_file: Option<String>,
+        authors: Option<String>,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ForgeDependency {
+        mod_id: String,
+        // Forge says whether a dependency is needed with `mandatory`,
+        // NeoForge with `type`
+        mandatory: Option<bool>,
+        #[serde(rename = "type")]
+        kind: Option<String>,
+        version_range: Option<String>,
+    }
+
+    let toml = toml::from_str::<ModsToml>(contents).ok()?;
+    let first = toml.mods.first()?;
+
+    let mut metadata = ModMetadata {
+        title: Some(
+            first
+                .display_name
+                .clone()
+                .unwrap_or_else(|| first.mod_id.clone()),
+        ),
+        description: first.description.as_ref().map(|x| x.trim().to_string()),
+        authors: first
+            .authors
+            .clone()
+            .or(toml.authors)
+            .map(|x| vec![x])
+            .unwrap_or_default(),
+        version: first.version.clone(),
+        icon: first.logo_file.clone().or(toml.logo_file),
+        mod_ids: toml.mods.iter().map(|x| x.mod_id.clone()).collect(),
+        loaders: vec![loader.to_string()],
+        ..Default::default()
+    };
+
+    for dependency in toml.dependencies.into_values().flatten() {
+        let mandatory = match (dependency.mandatory, dependency.kind.as_deref())
+        {
+            (Some(mandatory), _) => mandatory,
+            (None, Some("required")) | (None, None) => true,
+            (None, Some("optional")) => false,
+            // Incompatible and discouraged mods aren't dependencies
+            (None, Some(_)) => continue,
+        };
+        metadata.add_dependency(
+            dependency.mod_id,
+            dependency.version_range,
+            mandatory,
+        );
+    }
+
+    Some(metadata)
+}
+
+fn parse_mcmod_info(contents: &str) -> Option<ModMetadata> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum McModInfo {
+        List(Vec<McMod>),
+        V2 {
+            #[serde(rename = "modList")]
+            mod_list: Vec<McMod>,
+        },
+        Single(McMod),
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct McMod {
+        modid: String,
+        #[serde(default)]
+        name: String,
+        description: Option<String>,
+        version: Option<String>,
+        mcversion: Option<String>,
+        author_list: Option<Vec<String>>,
+        logo_file: Option<String>,
+        #[serde(default)]
+        required_mods: Vec<String>,
+    }
+
+    let mods = match serde_json::from_str::<McModInfo>(contents).ok()? {
+        McModInfo::List(mods) | McModInfo::V2 { mod_list: mods } => mods,
+        McModInfo::Single(x) => vec![x],
+    };
+    let first = mods.first()?;
+
+    let mut metadata = ModMetadata {
+        title: Some(if first.name.is_empty() {
+            first.modid.clone()
+        } else {
+            first.name.clone()
+        }),
+        description: first.description.clone(),
+        authors: first.author_list.clone().unwrap_or_default(),
+        version: first.version.clone(),
+        icon: first.logo_file.clone().filter(|x| !x.is_empty()),
+        mod_ids: mods.iter().map(|x| x.modid.clone()).collect(),
+        loaders: vec![String::from("forge")],
+        game_versions: first.mcversion.clone(),
+        ..Default::default()
+    };
+
+    // Required mods are written as `modid` or `modid@range`
+    for required in mods.iter().flat_map(|x| &x.required_mods) {
+        let (mod_id, range) = match required.split_once('@') {
+            Some((mod_id, range)) => (mod_id, Some(range.to_string())),
+            None => (&**required, None),
+        };
+        metadata.add_dependency(mod_id.to_lowercase(), range, true);
+    }
+
+    Some(metadata)
+}
+
+// Fabric and Quilt icons are a path, or paths keyed by their size
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Icon {
+    Path(String),
+    Sizes(HashMap<String, String>),
+}
+
+impl Icon {
+    fn into_path(self) -> Option<String> {
+        match self {
+            Icon::Path(path) => Some(path),
+            // The largest icon looks best
+            Icon::Sizes(sizes) => sizes
+                .into_iter()
+                .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+                .map(|(_, path)| path),
+        }
+    }
+}
+
+// Version ranges are a string, or a list of strings any of which can match
+fn version_range(value: serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(range) => Some(range),
+        serde_json::Value::Array(ranges) => Some(
+            ranges
+                .iter()
+                .filter_map(|x| x.as_str())
+                .collect::<Vec<_>>()
+                .join(" || "),
+        ),
+        _ => None,
+    }
+}
+
+fn parse_fabric_mod_json(contents: &str) -> Option<ModMetadata> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum FabricAuthor {
+        String(String),
+        Object { name: String },
+    }
+    #[derive(Deserialize)]
+    struct FabricJar {
+        file: String,
+    }
+    #[derive(Deserialize)]
+    struct FabricMod {
+        id: String,
+        version: String,
+        name: Option<String>,
+        description: Option<String>,
+        #[serde(default)]
+        authors: Vec<FabricAuthor>,
+        icon: Option<Icon>,
+        #[serde(default)]
+        depends: HashMap<String, serde_json::Value>,
+        #[serde(default)]
+        provides: Vec<String>,
+        #[serde(default)]
+        jars: Vec<FabricJar>,
+    }
+
+    let pack = serde_json::from_str::<FabricMod>(contents).ok()?;
+
+    let mut metadata = ModMetadata {
+        title: Some(pack.name.clone().unwrap_or_else(|| pack.id.clone())),
+        description: pack.description,
+        authors: pack
+            .authors
+            .into_iter()
+            .map(|x| match x {
+                FabricAuthor::String(name) => name,
+                FabricAuthor::Object { name } => name,
+            })
+            .collect(),
+        version: Some(pack.version),
+        icon: pack.icon.and_then(Icon::into_path),
+        mod_ids: std::iter::once(pack.id).chain(pack.provides).collect(),
+        loaders: vec![String::from("fabric")],
+        nested_jars: pack
+            .jars
+            .into_iter()
+            .map(|x| NestedJar {
+                path: x.file,
+                mod_ids: Vec::new(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    for (mod_id, range) in pack.depends {
+        metadata.add_dependency(mod_id, version_range(range), true);
+    }
+
+    Some(metadata)
+}
+
+fn parse_quilt_mod_json(contents: &str) -> Option<ModMetadata> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum QuiltDependency {
+        Id(String),
+        Object {
+            id: String,
+            versions: Option<serde_json::Value>,
+            #[serde(default)]
+            optional: bool,
+        },
+    }
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum QuiltProvides {
+        Id(String),
+        Object { id: String },
+    }
+    #[derive(Deserialize)]
+    struct QuiltMetadata {
+        name: Option<String>,
+        description: Option<String>,
+        contributors: Option<HashMap<String, String>>,
+        icon: Option<Icon>,
+    }
+    #[derive(Deserialize)]
+    struct QuiltLoader {
+        id: String,
+        version: String,
+        metadata: Option<QuiltMetadata>,
+        #[serde(default)]
+        depends: Vec<QuiltDependency>,
+        #[serde(default)]
+        provides: Vec<QuiltProvides>,
+        #[serde(default)]
+        jars: Vec<String>,
+    }
+    #[derive(Deserialize)]
+    struct QuiltMod {
+        quilt_loader: QuiltLoader,
+    }
+
+    let pack = serde_json::from_str::<QuiltMod>(contents)
+        .ok()?
+        .quilt_loader;
+    let id = pack.id;
+    let (title, description, authors, icon) = match pack.metadata {
+        Some(x) => (
+            x.name,
+            x.description,
+            x.contributors
+                .map(|x| x.into_keys().collect())
+                .unwrap_or_default(),
+            x.icon.and_then(Icon::into_path),
+        ),
+        None => (None, None, Vec::new(), None),
+    };
+
+    let mut metadata = ModMetadata {
+        title: Some(title.unwrap_or_else(|| id.clone())),
+        description,
+        authors,
+        version: Some(pack.version),
+        icon,
+        mod_ids: std::iter::once(id)
+            .chain(pack.provides.into_iter().map(|x| match x {
+                QuiltProvides::Id(id) => id,
+                QuiltProvides::Object { id } => id,
+            }))
+            .collect(),
+        loaders: vec![String::from("quilt")],
+        nested_jars: pack
+            .jars
+            .into_iter()
+            .map(|path| NestedJar {
+                path,
+                mod_ids: Vec::new(),
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    for dependency in pack.depends {
+        let (mod_id, range, optional) = match dependency {
+            QuiltDependency::Id(id) => (id, None, false),
+            QuiltDependency::Object {
+                id,
+                versions,
+                optional,
+            } => (id, versions.and_then(version_range), optional),
+        };
+        // Dependencies can be namespaced, as in `maven.group:id`
+        let mod_id = match mod_id.rsplit_once(':') {
+            Some((_, id)) => id.to_string(),
+            None => mod_id,
+        };
+        metadata.add_dependency(mod_id, range, !optional);
+    }
+
+    Some(metadata)
+}
+
+// Forge's jar-in-jar metadata lists each nested jar's path
+fn parse_jarjar_metadata(contents: &str) -> Vec<String> {
+    #[derive(Deserialize)]
+    struct JarJarMetadata {
+        jars: Vec<JarJarEntry>,
+    }
+    #[derive(Deserialize)]
+    struct JarJarEntry {
+        path: String,
+    }
+
+    serde_json::from_str::<JarJarMetadata>(contents)
+        .map(|x| x.jars.into_iter().map(|x| x.path).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    const MODS_TOML: &str = r#"
+        modLoader = "javafml"
+        loaderVersion = "[47,)"
+        license = "MIT"
+        authors = "Someone"
+
+        [[mods]]
+        modId = "examplemod"
+        version = "${file.jarVersion}"
+        displayName = "Example Mod"
+        description = '''
+        An example mod
+        '''
+
+        [[dependencies.examplemod]]
+        modId = "forge"
+        mandatory = true
+        versionRange = "[47,)"
+
+        [[dependencies.examplemod]]
+        modId = "minecraft"
+        mandatory = true
+        versionRange = "[1.20.1,1.21)"
+
+        [[dependencies.examplemod]]
+        modId = "jei"
+        mandatory = false
+        versionRange = "[15,)"
+
+        [[dependencies.examplemod]]
+        modId = "architectury"
+        mandatory = true
+    "#;
+
+    const FABRIC_MOD_JSON: &str = r#"{
+        "schemaVersion": 1,
+        "id": "examplemod",
+        "version": "1.0.0",
+        "name": "Example Mod (Fabric)",
+        "authors": ["Someone", { "name": "Someone Else" }],
+        "icon": { "16": "icon16.png", "128": "icon128.png" },
+        "provides": ["example"],
+        "depends": {
+            "fabricloader": ">=0.14",
+            "minecraft": ["1.20", "1.20.1"],
+            "fabric-api": "*",
+            "java": ">=17"
+        },
+        "jars": [{ "file": "META-INF/jars/library.jar" }]
+    }"#;
+
+    fn jar(files: &[(&str, &[u8])]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    fn dependency<'a>(
+        metadata: &'a ModMetadata,
+        mod_id: &str,
+    ) -> Option<&'a ModDependency> {
+        metadata.dependencies.iter().find(|x| x.mod_id == mod_id)
+    }
+
+    #[test]
+    fn parses_mods_toml() {
+        let metadata = parse_mods_toml(MODS_TOML, "forge").unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Example Mod"));
+        assert_eq!(metadata.description.as_deref(), Some("An example mod"));
+        assert_eq!(metadata.authors, ["Someone"]);
+        assert_eq!(metadata.mod_ids, ["examplemod"]);
+        assert_eq!(metadata.loaders, ["forge"]);
+        assert_eq!(metadata.game_versions.as_deref(), Some("[1.20.1,1.21)"));
+        assert_eq!(metadata.loader_versions.as_deref(), Some("[47,)"));
+
+        assert_eq!(metadata.dependencies.len(), 2);
+        assert!(!dependency(&metadata, "jei").unwrap().mandatory);
+        let architectury = dependency(&metadata, "architectury").unwrap();
+        assert!(architectury.mandatory);
+        assert_eq!(architectury.loader.as_deref(), Some("forge"));
+    }
+
+    #[test]
+    fn parses_neoforge_dependency_types() {
+        let metadata = parse_mods_toml(
+            r#"
+            [[mods]]
+            modId = "examplemod"
+
+            [[dependencies.examplemod]]
+            modId = "required_mod"
+            type = "required"
+
+            [[dependencies.examplemod]]
+            modId = "optional_mod"
+            type = "optional"
+
+            [[dependencies.examplemod]]
+            modId = "incompatible_mod"
+            type = "incompatible"
+            "#,
+            "neoforge",
+        )
+        .unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("examplemod"));
+        assert!(dependency(&metadata, "required_mod").unwrap().mandatory);
+        assert!(!dependency(&metadata, "optional_mod").unwrap().mandatory);
+        assert!(dependency(&metadata, "incompatible_mod").is_none());
+    }
+
+    #[test]
+    fn parses_fabric_mod_json() {
+        let metadata = parse_fabric_mod_json(FABRIC_MOD_JSON).unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Example Mod (Fabric)"));
+        assert_eq!(metadata.authors, ["Someone", "Someone Else"]);
+        assert_eq!(metadata.icon.as_deref(), Some("icon128.png"));
+        assert_eq!(metadata.mod_ids, ["examplemod", "example"]);
+        assert_eq!(metadata.loaders, ["fabric"]);
+        assert_eq!(metadata.game_versions.as_deref(), Some("1.20 || 1.20.1"));
+        assert_eq!(metadata.loader_versions.as_deref(), Some(">=0.14"));
+        assert_eq!(metadata.nested_jars.len(), 1);
+
+        assert_eq!(metadata.dependencies.len(), 1);
+        let fabric_api = dependency(&metadata, "fabric-api").unwrap();
+        assert_eq!(fabric_api.version_range.as_deref(), Some("*"));
+        assert_eq!(fabric_api.loader.as_deref(), Some("fabric"));
+    }
+
+    #[test]
+    fn parses_quilt_mod_json() {
+        let metadata = parse_quilt_mod_json(
+            r#"{
+                "schema_version": 1,
+                "quilt_loader": {
+                    "group": "com.example",
+                    "id": "examplemod",
+                    "version": "1.0.0",
+                    "metadata": {
+                        "name": "Example Mod (Quilt)",
+                        "contributors": { "Someone": "Owner" },
+                        "icon": "icon.png"
+                    },
+                    "provides": [{ "id": "example" }],
+                    "depends": [
+                        { "id": "quilt_loader", "versions": ">=0.19" },
+                        { "id": "minecraft", "versions": ["1.20.1"] },
+                        "org.quiltmc.qsl:quilted_fabric_api",
+                        { "id": "modmenu", "optional": true }
+                    ],
+                    "jars": ["META-INF/jars/library.jar"]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Example Mod (Quilt)"));
+        assert_eq!(metadata.authors, ["Someone"]);
+        assert_eq!(metadata.icon.as_deref(), Some("icon.png"));
+        assert_eq!(metadata.mod_ids, ["examplemod", "example"]);
+        assert_eq!(metadata.loaders, ["quilt"]);
+        assert_eq!(metadata.game_versions.as_deref(), Some("1.20.1"));
+        assert_eq!(metadata.loader_versions.as_deref(), Some(">=0.19"));
+        assert_eq!(metadata.nested_jars.len(), 1);
+
+        assert!(
+            dependency(&metadata, "quilted_fabric_api")
+                .unwrap()
+                .mandatory
+        );
+        assert!(!dependency(&metadata, "modmenu").unwrap().mandatory);
+    }
+
+    #[test]
+    fn parses_mcmod_info() {
+        let metadata = parse_mcmod_info(
+            r#"[{
+                "modid": "oldmod",
+                "name": "Old Mod",
+                "version": "1.0",
+                "mcversion": "1.12.2",
+                "authorList": ["Someone"],
+                "requiredMods": ["Baubles@[1.5,)"]
+            }]"#,
+        )
+        .unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Old Mod"));
+        assert_eq!(metadata.mod_ids, ["oldmod"]);
+        assert_eq!(metadata.loaders, ["forge"]);
+        assert_eq!(metadata.game_versions.as_deref(), Some("1.12.2"));
+        let baubles = dependency(&metadata, "baubles").unwrap();
+        assert_eq!(baubles.version_range.as_deref(), Some("[1.5,)"));
+    }
+
+    #[test]
+    fn merges_multi_loader_jars() {
+        let library = jar(&[(
+            "fabric.mod.json",
+            br#"{ "id": "library", "version": "1.0.0" }"#,
+        )])
+        .into_inner()
+        .into_inner();
+        let mut archive = jar(&[
+            ("META-INF/mods.toml", MODS_TOML.as_bytes()),
+            ("fabric.mod.json", FABRIC_MOD_JSON.as_bytes()),
+            ("META-INF/jars/library.jar", &library),
+            ("META-INF/MANIFEST.MF", b"Implementation-Version: 2.0.0\n"),
+        ]);
+
+        let metadata = read_archive(&mut archive, 0).unwrap();
+
+        assert_eq!(metadata.title.as_deref(), Some("Example Mod"));
+        assert_eq!(metadata.version.as_deref(), Some("2.0.0"));
+        assert_eq!(metadata.loaders, ["forge", "fabric"]);
+        assert_eq!(metadata.mod_ids, ["examplemod", "example"]);
+        assert_eq!(
+            dependency(&metadata, "architectury")
+                .unwrap()
+                .loader
+                .as_deref(),
+            Some("forge")
+        );
+        assert_eq!(
+            dependency(&metadata, "fabric-api")
+                .unwrap()
+                .loader
+                .as_deref(),
+            Some("fabric")
+        );
+
+        assert_eq!(metadata.nested_jars.len(), 1);
+        assert_eq!(metadata.nested_jars[0].mod_ids, ["library"]);
+        assert!(metadata.all_mod_ids().any(|x| x == "library"));
+    }
+
+    #[test]
+    fn ignores_jars_without_metadata() {
+        let mut archive = jar(&[("pack.mcmeta", b"{}")]);
+        assert!(read_archive(&mut archive, 0).is_none());
+    }
+}
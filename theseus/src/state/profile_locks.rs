@@ -0,0 +1,133 @@
+//! Per-profile operation locks
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use uuid::Uuid;
+
+/// An operation that changes a profile, and so can't run at the same time as
+/// another one on the same profile
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileOperation {
+    Installing,
+    InstallingPack,
+    Updating,
+    Launching,
+    Migrating,
+    RestoringSnapshot,
+    SyncingProjects,
+    CleaningUp,
+    Verifying,
+}
+
+impl std::fmt::Display for ProfileOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match *self {
+            Self::Installing => "installing",
+            Self::InstallingPack => "installing a modpack",
+            Self::Updating => "updating",
+            Self::Launching => "launching",
+            Self::Migrating => "migrating",
+            Self::RestoringSnapshot => "restoring a snapshot",
+            Self::SyncingProjects => "syncing projects",
+            Self::CleaningUp => "removing unused files",
+            Self::Verifying => "verifying files",
+        })
+    }
+}
+
+type Operations = Arc<Mutex<HashMap<Uuid, ProfileOperation>>>;
+
+// Profiles are locked by UUID, with the operation holding each lock kept
+// alongside so busy errors can say what the profile is doing
+#[derive(Default)]
+pub struct ProfileLocks {
+    locks: Mutex<HashMap<Uuid, Arc<AsyncMutex<()>>>>,
+    operations: Operations,
+}
+
+/// Held for the duration of an operation. The profile is unlocked on drop
+pub struct ProfileLock {
+    uuid: Uuid,
+    operations: Operations,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl Drop for ProfileLock {
+    fn drop(&mut self) {
+        if let Ok(mut operations) = self.operations.lock() {
+            operations.remove(&self.uuid);
+        }
+    }
+}
+
+impl ProfileLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get_lock(&self, uuid: Uuid) -> Arc<AsyncMutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(|x| x.into_inner());
+        locks.entry(uuid).or_default().clone()
+    }
+
+    fn set_operation(
+        &self,
+        uuid: Uuid,
+        operation: ProfileOperation,
+        guard: OwnedMutexGuard<()>,
+    ) -> ProfileLock {
+        self.operations
+            .lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .insert(uuid, operation);
+
+        ProfileLock {
+            uuid,
+            operations: self.operations.clone(),
+            _guard: guard,
+        }
+    }
+
+    /// Locks a profile for an operation, failing if another operation is
+    /// already running on it
+    pub fn try_lock(
+        &self,
+        uuid: Uuid,
+        profile_name: &str,
+        operation: ProfileOperation,
+    ) -> crate::Result<ProfileLock> {
+        match self.get_lock(uuid).try_lock_owned() {
+            Ok(guard) => Ok(self.set_operation(uuid, operation, guard)),
+            Err(_) => {
+                let current = self.get_operation(uuid).unwrap_or(operation);
+                Err(crate::ErrorKind::ProfileBusyError(
+                    profile_name.to_string(),
+                    current,
+                )
+                .into())
+            }
+        }
+    }
+
+    /// Locks a profile for an operation, waiting for any other operation
+    /// running on it to finish first
+    pub async fn lock(
+        &self,
+        uuid: Uuid,
+        operation: ProfileOperation,
+    ) -> ProfileLock {
+        let guard = self.get_lock(uuid).lock_owned().await;
+        self.set_operation(uuid, operation, guard)
+    }
+
+    /// Gets the operation currently running on a profile, if any
+    pub fn get_operation(&self, uuid: Uuid) -> Option<ProfileOperation> {
+        self.operations
+            .lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .get(&uuid)
+            .copied()
+    }
+}
@@ -1,14 +1,18 @@
-use super::settings::{Hooks, MemorySettings, WindowSize};
+use super::settings::{Hooks, MemorySettings, Settings, WindowSize};
 use crate::config::MODRINTH_API_URL;
 use crate::data::DirectoryInfo;
 use crate::event::emit::{emit_profile, emit_warning};
 use crate::event::ProfilePayloadType;
 use crate::prelude::JavaVersion;
 use crate::state::projects::Project;
-use crate::state::{ModrinthVersion, ProjectMetadata, ProjectType};
+use crate::state::{
+    sync_shared_folders, ModrinthVersion, ModrinthVersionFile,
+    ProfileOperation, ProjectMetadata, ProjectType,
+};
 use crate::util::fetch::{
     fetch, fetch_json, write, write_cached_icon, IoSemaphore,
 };
+use crate::util::io::{backup_path, read_with_backup, write_atomic};
 use crate::State;
 use chrono::{DateTime, Utc};
 use daedalus::get_hash;
@@ -27,9 +31,37 @@ use std::{
 use tokio::fs;
 use uuid::Uuid;
 
-const PROFILE_JSON_PATH: &str = "profile.json";
+pub(crate) const PROFILE_JSON_PATH: &str = "profile.json";
+const QUARANTINE_JSON_PATH: &str = "quarantine.json";
+
+/// The current version of the profile.json format. Bump this and add a step
+/// to migrate_profile_json whenever the format changes
+pub const CURRENT_PROFILE_SCHEMA_VERSION: u32 = 1;
+
+// Profiles are keyed by UUID. Paths are still accepted everywhere for
+// compatibility, so an index from canonical profile paths to UUIDs is kept
+// alongside them
+pub(crate) struct Profiles {
+    profiles: HashMap<Uuid, Profile>,
+    paths: HashMap<PathBuf, Uuid>,
+    pub broken: Vec<BrokenProfile>,
+}
 
-pub(crate) struct Profiles(pub HashMap<PathBuf, Profile>);
+/// A profile that could not be loaded
+/// Profiles that fail to parse are moved out of the profiles directory into
+/// the broken profiles directory, so they can be inspected or retried
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BrokenProfile {
+    /// The folder name of the profile
+    pub name: String,
+    /// Where the profile currently is
+    pub path: PathBuf,
+    pub error: String,
+    pub date: DateTime<Utc>,
+    /// Whether the profile was moved to the broken profiles directory
+    /// Profiles from a newer version of the launcher are left in place
+    pub quarantined: bool,
+}
 
 #[derive(
     Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq,
@@ -50,7 +82,9 @@ pub enum ProfileInstallStage {
 // Represent a Minecraft instance.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Profile {
-    pub uuid: Uuid, // todo: will be used in restructure to refer to profiles
+    pub uuid: Uuid,
+    #[serde(default)]
+    pub schema_version: u32,
     #[serde(default)]
     pub install_stage: ProfileInstallStage,
     pub path: PathBuf,
@@ -63,6 +97,9 @@ pub struct Profile {
     pub resolution: Option<WindowSize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hooks: Option<Hooks>,
+    /// Overrides the shared folders set in the settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_folders: Option<Vec<String>>,
     pub projects: HashMap<PathBuf, Project>,
 }
 
@@ -160,6 +197,7 @@ impl Profile {
 
         Ok(Self {
             uuid,
+            schema_version: CURRENT_PROFILE_SCHEMA_VERSION,
             install_stage: ProfileInstallStage::NotInstalled,
             path: canonicalize(path)?,
             metadata: ProfileMetadata {
@@ -180,6 +218,7 @@ impl Profile {
             memory: None,
             resolution: None,
             hooks: None,
+            shared_folders: None,
         })
     }
 
@@ -198,6 +237,64 @@ impl Profile {
         Ok(())
     }
 
+    /// Gets the folders this profile shares, from its own list or the
+    /// settings
+    pub fn shared_folders<'a>(
+        &'a self,
+        settings: &'a Settings,
+    ) -> &'a [String] {
+        self.shared_folders
+            .as_deref()
+            .unwrap_or(&settings.shared_folders)
+    }
+
+    /// Links this profile's shared folders, watching the folders that are no
+    /// longer shared again
+    pub async fn apply_shared_folders(
+        &self,
+        shared: &[String],
+    ) -> crate::Result<()> {
+        let state = State::get().await?;
+        sync_shared_folders(&state.directories, &self.path, shared).await?;
+
+        let mut file_watcher = state.file_watcher.write().await;
+        Profile::watch_fs(&self.path, &mut file_watcher).await?;
+        Profile::sync_projects_task(self.path.clone());
+
+        Ok(())
+    }
+
+    // Syncs the projects of every profile sharing a folder, as its changes
+    // are only seen at the shared folder itself
+    pub fn sync_shared_folder_task(folder: String) {
+        tokio::task::spawn(async move {
+            let res = async {
+                let state = State::get().await?;
+                let default_folders =
+                    state.settings.read().await.shared_folders.clone();
+                let profiles = state.profiles.read().await;
+
+                for profile in profiles.values() {
+                    if profile
+                        .shared_folders
+                        .as_ref()
+                        .unwrap_or(&default_folders)
+                        .contains(&folder)
+                    {
+                        Profile::sync_projects_task(profile.path.clone());
+                    }
+                }
+
+                Ok::<(), crate::Error>(())
+            }
+            .await;
+
+            if let Err(err) = res {
+                tracing::warn!("Unable to sync shared folder {folder}: {err}");
+            }
+        });
+    }
+
     pub fn crash_task(path: PathBuf) {
         tokio::task::spawn(async move {
             let res = async {
@@ -223,27 +320,94 @@ impl Profile {
     }
 
     pub fn sync_projects_task(path: PathBuf) {
+        Self::sync_projects_in_task(path, None)
+    }
+
+    /// Syncs only the projects under the changed paths, which are relative
+    /// to the profile's folder, and projects that are new. Other projects
+    /// keep what was read of them before
+    pub fn sync_changed_projects_task(path: PathBuf, changed: Vec<PathBuf>) {
+        Self::sync_projects_in_task(path, Some(changed))
+    }
+
+    fn sync_projects_in_task(path: PathBuf, changed: Option<Vec<PathBuf>>) {
         tokio::task::spawn(async move {
             let res = async {
                 let state = State::get().await?;
+
+                // Wait for anything changing the profile's files to finish,
+                // so the projects are read once they're settled. The lock is
+                // only held while reading the profile, so launching or
+                // installing isn't refused while files are hashed and looked
+                // up
+                let lock =
+                    match crate::api::profile::get_uuid(&path).await? {
+                        Some(uuid) => Some(
+                            state
+                                .profile_locks
+                                .lock(uuid, ProfileOperation::SyncingProjects)
+                                .await,
+                        ),
+                        None => None,
+                    };
                 let profile = crate::api::profile::get(&path, None).await?;
+                let paths = match &profile {
+                    Some(profile) => profile.get_profile_project_paths()?,
+                    None => Vec::new(),
+                };
+                drop(lock);
 
                 if let Some(profile) = profile {
-                    let paths = profile.get_profile_project_paths()?;
-
-                    let projects = crate::state::infer_data_from_files(
-                        profile.clone(),
-                        paths,
-                        state.directories.caches_dir(),
-                        &state.io_semaphore,
-                        &state.fetch_semaphore,
-                    )
-                    .await?;
+                    // Projects of files that are gone are removed, rather
+                    // than replacing every project, so those added while
+                    // syncing are kept
+                    let removed = profile
+                        .projects
+                        .keys()
+                        .filter(|x| !paths.contains(x))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    let paths = match &changed {
+                        Some(changed) => paths
+                            .into_iter()
+                            .filter(|x| {
+                                if !profile.projects.contains_key(x) {
+                                    return true;
+                                }
+                                match x.strip_prefix(&profile.path) {
+                                    Ok(x) => changed
+                                        .iter()
+                                        .any(|c| x.starts_with(c)),
+                                    Err(_) => true,
+                                }
+                            })
+                            .collect(),
+                        None => paths,
+                    };
+
+                    let projects = if paths.is_empty() {
+                        HashMap::new()
+                    } else {
+                        crate::state::infer_data_from_files(
+                            profile.clone(),
+                            paths,
+                            state.directories.caches_dir(),
+                            &state.io_semaphore,
+                            &state.fetch_semaphore,
+                        )
+                        .await?
+                    };
 
                     let mut new_profiles = state.profiles.write().await;
-                    if let Some(profile) = new_profiles.0.get_mut(&path) {
-                        profile.projects = projects;
+                    if let Some(profile) =
+                        new_profiles.get_mut_by_uuid(&profile.uuid)
+                    {
+                        for path in &removed {
+                            profile.projects.remove(path);
+                        }
+                        profile.projects.extend(projects);
                     }
+                    drop(new_profiles);
                     emit_profile(
                         profile.uuid,
                         profile.path,
@@ -305,6 +469,16 @@ impl Profile {
         ) -> crate::Result<()> {
             let path = profile_path.join(path);
 
+            // Shared folders are the same folder in every profile sharing
+            // them, so they're watched once where they're stored instead
+            if fs::symlink_metadata(&path)
+                .await
+                .map(|x| x.file_type().is_symlink())
+                .unwrap_or(false)
+            {
+                return Ok(());
+            }
+
             fs::create_dir_all(&path).await?;
 
             watcher
@@ -348,34 +522,53 @@ impl Profile {
         )
         .await?;
 
-        let file = if let Some(file) = version.files.iter().find(|x| x.primary)
-        {
-            file
-        } else if let Some(file) = version.files.first() {
-            file
-        } else {
-            return Err(crate::ErrorKind::InputError(
-                "No files for input version present!".to_string(),
-            )
-            .into());
-        };
+        let path = self.add_project_version_file(&version).await?;
+        Ok((path, version))
+    }
+
+    /// Downloads a version's primary file into the profile
+    #[tracing::instrument(skip(self, version))]
+    #[theseus_macros::debug_pin]
+    pub async fn add_project_version_file(
+        &self,
+        version: &ModrinthVersion,
+    ) -> crate::Result<PathBuf> {
+        let bytes = Self::fetch_version_file(version).await?;
+        self.add_project_version_bytes(version, bytes).await
+    }
+
+    /// Downloads a version's primary file
+    #[tracing::instrument(skip(version))]
+    pub async fn fetch_version_file(
+        version: &ModrinthVersion,
+    ) -> crate::Result<bytes::Bytes> {
+        let state = State::get().await?;
+        let file = primary_file(version)?;
 
-        let bytes = fetch(
+        fetch(
             &file.url,
             file.hashes.get("sha1").map(|x| &**x),
             &state.fetch_semaphore,
         )
-        .await?;
+        .await
+    }
 
-        let path = self
-            .add_project_bytes(
-                &file.filename,
-                bytes,
-                ProjectType::get_from_loaders(version.loaders.clone()),
-            )
-            .await?;
+    /// Adds a downloaded primary file of a version to the profile
+    #[tracing::instrument(skip(self, version, bytes))]
+    #[theseus_macros::debug_pin]
+    pub async fn add_project_version_bytes(
+        &self,
+        version: &ModrinthVersion,
+        bytes: bytes::Bytes,
+    ) -> crate::Result<PathBuf> {
+        let file = primary_file(version)?;
 
-        Ok((path, version))
+        self.add_project_bytes(
+            &file.filename,
+            bytes,
+            ProjectType::get_from_loaders(version.loaders.clone()),
+        )
+        .await
     }
 
     #[tracing::instrument(skip(self, bytes))]
@@ -424,7 +617,7 @@ impl Profile {
         {
             let mut profiles = state.profiles.write().await;
 
-            if let Some(profile) = profiles.0.get_mut(&self.path) {
+            if let Some(profile) = profiles.get_mut_by_uuid(&self.uuid) {
                 profile.projects.insert(
                     path.clone(),
                     Project {
@@ -451,7 +644,7 @@ impl Profile {
         if let Some(mut project) = {
             let mut profiles = state.profiles.write().await;
 
-            if let Some(profile) = profiles.0.get_mut(&self.path) {
+            if let Some(profile) = profiles.get_mut_by_uuid(&self.uuid) {
                 profile.projects.remove(path)
             } else {
                 None
@@ -479,7 +672,7 @@ impl Profile {
             fs::rename(path, &new_path).await?;
 
             let mut profiles = state.profiles.write().await;
-            if let Some(profile) = profiles.0.get_mut(&self.path) {
+            if let Some(profile) = profiles.get_mut_by_uuid(&self.uuid) {
                 profile.projects.insert(new_path.clone(), project);
                 profile.metadata.date_modified = Utc::now();
             }
@@ -505,7 +698,7 @@ impl Profile {
             if !dont_remove_arr.unwrap_or(false) {
                 let mut profiles = state.profiles.write().await;
 
-                if let Some(profile) = profiles.0.get_mut(&self.path) {
+                if let Some(profile) = profiles.get_mut_by_uuid(&self.uuid) {
                     profile.projects.remove(path);
                     profile.metadata.date_modified = Utc::now();
                 }
@@ -529,7 +722,11 @@ impl Profiles {
         dirs: &DirectoryInfo,
         file_watcher: &mut Debouncer<RecommendedWatcher>,
     ) -> crate::Result<Self> {
-        let mut profiles = HashMap::new();
+        let mut profiles = Self {
+            profiles: HashMap::new(),
+            paths: HashMap::new(),
+            broken: Self::read_broken_profiles(dirs).await?,
+        };
         fs::create_dir_all(dirs.profiles_dir()).await?;
         let mut entries = fs::read_dir(dirs.profiles_dir()).await?;
         while let Some(entry) = entries.next_entry().await? {
@@ -537,22 +734,89 @@ impl Profiles {
             if path.is_dir() {
                 let prof = match Self::read_profile_from_dir(&path).await {
                     Ok(prof) => Some(prof),
+                    // Folders without a profile.json aren't profiles
+                    Err(_) if !is_profile_dir(&path) => None,
                     Err(err) => {
-                        tracing::warn!(
-                            "Error loading profile: {err}. Skipping..."
-                        );
+                        tracing::warn!("Error loading profile: {err}");
+                        if is_unparsable(&path).await {
+                            profiles
+                                .quarantine_or_skip(dirs, &path, &err)
+                                .await;
+                        }
                         None
                     }
                 };
-                if let Some(profile) = prof {
+                if let Some(mut profile) = prof {
+                    // Nothing is installing on startup, so these stages were
+                    // left behind by a crash or a closed launcher
+                    if matches!(
+                        profile.install_stage,
+                        ProfileInstallStage::Installing
+                            | ProfileInstallStage::PackInstalling
+                    ) {
+                        tracing::warn!(
+                            "Profile {} was left mid-install, resetting it",
+                            profile.metadata.name
+                        );
+                        profile.install_stage =
+                            ProfileInstallStage::NotInstalled;
+                    }
+
                     let path = canonicalize(path)?;
                     Profile::watch_fs(&path, file_watcher).await?;
-                    profiles.insert(path, profile);
+                    profiles.index(path, profile);
                 }
             }
         }
 
-        Ok(Self(profiles))
+        Ok(profiles)
+    }
+
+    // Inserts a profile without emitting events or watching it
+    fn index(&mut self, path: PathBuf, profile: Profile) {
+        self.paths.retain(|_, uuid| *uuid != profile.uuid);
+        self.paths.insert(path, profile.uuid);
+        self.paths.insert(profile.path.clone(), profile.uuid);
+        self.profiles.insert(profile.uuid, profile);
+    }
+
+    /// Gets the UUID of the profile at a path
+    /// Paths are matched as given first, then canonicalized, so symlinks and
+    /// differently spelled paths to the same profile resolve to it
+    pub fn get_uuid(&self, path: &Path) -> Option<Uuid> {
+        if let Some(uuid) = self.paths.get(path) {
+            return Some(*uuid);
+        }
+
+        canonicalize(path)
+            .ok()
+            .and_then(|path| self.paths.get(&path).copied())
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Profile> {
+        self.get_uuid(path)
+            .and_then(|uuid| self.profiles.get(&uuid))
+    }
+
+    pub fn get_mut(&mut self, path: &Path) -> Option<&mut Profile> {
+        self.get_uuid(path)
+            .and_then(move |uuid| self.profiles.get_mut(&uuid))
+    }
+
+    pub fn get_by_uuid(&self, uuid: &Uuid) -> Option<&Profile> {
+        self.profiles.get(uuid)
+    }
+
+    pub fn get_mut_by_uuid(&mut self, uuid: &Uuid) -> Option<&mut Profile> {
+        self.profiles.get_mut(uuid)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Profile> {
+        self.profiles.values()
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Profile> {
+        self.profiles.values_mut()
     }
 
     #[tracing::instrument]
@@ -565,7 +829,7 @@ impl Profiles {
             let mut files: Vec<(Profile, Vec<PathBuf>)> = Vec::new();
             {
                 let profiles = state.profiles.read().await;
-                for (_profile_path, profile) in profiles.0.iter() {
+                for profile in profiles.values() {
                     let paths = profile.get_profile_project_paths()?;
 
                     files.push((profile.clone(), paths));
@@ -574,7 +838,7 @@ impl Profiles {
 
             future::try_join_all(files.into_iter().map(
                 |(profile, files)| async {
-                    let profile_path = profile.path.clone();
+                    let profile_uuid = profile.uuid;
                     let inferred = super::projects::infer_data_from_files(
                         profile,
                         files,
@@ -585,7 +849,8 @@ impl Profiles {
                     .await?;
 
                     let mut new_profiles = state.profiles.write().await;
-                    if let Some(profile) = new_profiles.0.get_mut(&profile_path)
+                    if let Some(profile) =
+                        new_profiles.get_mut_by_uuid(&profile_uuid)
                     {
                         profile.projects = inferred;
                     }
@@ -601,6 +866,15 @@ impl Profiles {
                 profiles.sync().await?;
             }
 
+            // Every project was just hashed, so hashes of files that are
+            // gone aren't needed anymore
+            state
+                .file_hashes
+                .write()
+                .await
+                .prune(&state.directories)
+                .await?;
+
             Ok::<(), crate::Error>(())
         }
         .await;
@@ -625,18 +899,24 @@ impl Profiles {
         .await?;
 
         let state = State::get().await?;
+        {
+            let settings = state.settings.read().await;
+            sync_shared_folders(
+                &state.directories,
+                &profile.path,
+                profile.shared_folders(&settings),
+            )
+            .await?;
+        }
+
         let mut file_watcher = state.file_watcher.write().await;
         Profile::watch_fs(&profile.path, &mut file_watcher).await?;
 
-        self.0.insert(
-            canonicalize(&profile.path)?
-                .to_str()
-                .ok_or(
-                    crate::ErrorKind::UTFError(profile.path.clone()).as_error(),
-                )?
-                .into(),
-            profile,
-        );
+        let path: PathBuf = canonicalize(&profile.path)?
+            .to_str()
+            .ok_or(crate::ErrorKind::UTFError(profile.path.clone()).as_error())?
+            .into();
+        self.index(path, profile);
         Ok(self)
     }
 
@@ -645,28 +925,56 @@ impl Profiles {
         &mut self,
         path: &Path,
     ) -> crate::Result<Option<Profile>> {
+        if let Some(uuid) = self.get_uuid(path) {
+            return self.remove_by_uuid(&uuid).await;
+        }
+
+        // Unmanaged profile folders are still removed
         let path =
             PathBuf::from(&canonicalize(path)?.to_string_lossy().to_string());
-        let profile = self.0.remove(&path);
-
         if path.exists() {
             fs::remove_dir_all(path).await?;
         }
 
+        Ok(None)
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_by_uuid(
+        &mut self,
+        uuid: &Uuid,
+    ) -> crate::Result<Option<Profile>> {
+        let profile = self.profiles.remove(uuid);
+        self.paths.retain(|_, x| x != uuid);
+
+        if let Some(ref profile) = profile {
+            if profile.path.exists() {
+                fs::remove_dir_all(&profile.path).await?;
+            }
+
+            let state = State::get().await?;
+            let sessions_file =
+                state.directories.profile_sessions_file(profile.uuid);
+            for path in [backup_path(&sessions_file), sessions_file] {
+                if path.exists() {
+                    fs::remove_file(path).await?;
+                }
+            }
+        }
+
         Ok(profile)
     }
 
     #[tracing::instrument(skip_all)]
     pub async fn sync(&self) -> crate::Result<&Self> {
-        stream::iter(self.0.iter())
+        stream::iter(self.profiles.values())
             .map(Ok::<_, crate::Error>)
-            .try_for_each_concurrent(None, |(path, profile)| async move {
+            .try_for_each_concurrent(None, |profile| async move {
                 let json = serde_json::to_vec(&profile)?;
 
-                let json_path = Path::new(&path.to_string_lossy().to_string())
-                    .join(PROFILE_JSON_PATH);
+                let json_path = profile.path.join(PROFILE_JSON_PATH);
 
-                fs::write(json_path, json).await?;
+                write_atomic(&json_path, &json).await?;
                 Ok::<_, crate::Error>(())
             })
             .await?;
@@ -674,10 +982,290 @@ impl Profiles {
         Ok(self)
     }
 
-    async fn read_profile_from_dir(path: &Path) -> crate::Result<Profile> {
-        let json = fs::read(path.join(PROFILE_JSON_PATH)).await?;
-        let mut profile = serde_json::from_slice::<Profile>(&json)?;
+    pub(crate) async fn read_profile_from_dir(
+        path: &Path,
+    ) -> crate::Result<Profile> {
+        let json_path = path.join(PROFILE_JSON_PATH);
+        let ((mut profile, old_version), json) =
+            read_with_backup(&json_path, |json| {
+                Ok((read_profile_json(json)?, json.to_vec()))
+            })
+            .await?;
         profile.path = PathBuf::from(path);
+
+        // The original file is kept so a bad migration can be undone
+        if let Some(old_version) = old_version {
+            tracing::info!(
+                "Migrated profile {} from schema version {old_version}",
+                path.display()
+            );
+            fs::write(
+                path.join(format!("{PROFILE_JSON_PATH}.v{old_version}.bak")),
+                &json,
+            )
+            .await?;
+            write_atomic(&json_path, &serde_json::to_vec(&profile)?).await?;
+        }
+
         Ok(profile)
     }
+
+    // Quarantines a profile that failed to load. If it can't be moved, it is
+    // left in place to be loaded again next time
+    async fn quarantine_or_skip(
+        &mut self,
+        dirs: &DirectoryInfo,
+        path: &Path,
+        error: &crate::Error,
+    ) {
+        match Self::quarantine(dirs, path, error).await {
+            Ok(broken) => self.broken.push(broken),
+            Err(err) => tracing::error!(
+                "Unable to quarantine profile {}: {err}",
+                path.display()
+            ),
+        }
+    }
+
+    // Moves a profile that failed to load into the broken profiles directory
+    // Profiles from a newer launcher version are only reported
+    async fn quarantine(
+        dirs: &DirectoryInfo,
+        path: &Path,
+        error: &crate::Error,
+    ) -> crate::Result<BrokenProfile> {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let quarantined = !is_from_newer_launcher(path).await;
+
+        let mut broken = BrokenProfile {
+            name: name.clone(),
+            path: path.to_path_buf(),
+            error: error.to_string(),
+            date: Utc::now(),
+            quarantined,
+        };
+
+        if quarantined {
+            fs::create_dir_all(dirs.broken_profiles_dir()).await?;
+
+            let mut dest = dirs.broken_profiles_dir().join(&name);
+            if dest.exists() {
+                dest = dirs
+                    .broken_profiles_dir()
+                    .join(format!("{name}-{}", broken.date.timestamp_millis()));
+            }
+
+            fs::rename(path, &dest).await?;
+            broken.path = dest;
+            fs::write(
+                broken.path.join(QUARANTINE_JSON_PATH),
+                serde_json::to_vec(&broken)?,
+            )
+            .await?;
+        }
+
+        Ok(broken)
+    }
+
+    async fn read_broken_profiles(
+        dirs: &DirectoryInfo,
+    ) -> crate::Result<Vec<BrokenProfile>> {
+        let mut broken = Vec::new();
+        if !dirs.broken_profiles_dir().is_dir() {
+            return Ok(broken);
+        }
+
+        let mut entries = fs::read_dir(dirs.broken_profiles_dir()).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path().join(QUARANTINE_JSON_PATH);
+            if !path.exists() {
+                continue;
+            }
+
+            match serde_json::from_slice::<BrokenProfile>(
+                &fs::read(&path).await?,
+            ) {
+                Ok(mut profile) => {
+                    profile.path = entry.path();
+                    broken.push(profile);
+                }
+                Err(err) => {
+                    tracing::warn!("Unable to read {}: {err}", path.display())
+                }
+            }
+        }
+
+        Ok(broken)
+    }
+
+    /// Moves a quarantined profile back into the profiles directory and tries
+    /// to load it again. If it still fails, it is quarantined again
+    #[tracing::instrument(skip(self))]
+    pub async fn retry_broken(
+        &mut self,
+        dirs: &DirectoryInfo,
+        name: &str,
+    ) -> crate::Result<Option<Profile>> {
+        let index =
+            self.broken.iter().position(|x| x.name == name).ok_or_else(
+                || {
+                    crate::ErrorKind::InputError(format!(
+                        "No broken profile named {name}"
+                    ))
+                },
+            )?;
+        let broken = self.broken.remove(index);
+
+        let path = if broken.quarantined {
+            let dest = dirs.profiles_dir().join(&broken.name);
+            if dest.exists() {
+                self.broken.push(broken);
+                return Err(crate::ErrorKind::InputError(format!(
+                    "A profile folder named {name} already exists"
+                ))
+                .into());
+            }
+
+            fs::rename(&broken.path, &dest).await?;
+            let _ = fs::remove_file(dest.join(QUARANTINE_JSON_PATH)).await;
+            dest
+        } else {
+            broken.path
+        };
+
+        match Self::read_profile_from_dir(&path).await {
+            Ok(profile) => {
+                self.insert(profile.clone()).await?;
+                Ok(Some(profile))
+            }
+            Err(err) => {
+                // Profiles that couldn't be read are left in the profiles
+                // directory, to be loaded again on the next start
+                if !is_unparsable(&path).await {
+                    return Err(err);
+                }
+
+                self.quarantine_or_skip(dirs, &path, &err).await;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Deletes a broken profile
+    #[tracing::instrument(skip(self))]
+    pub async fn remove_broken(&mut self, name: &str) -> crate::Result<()> {
+        if let Some(index) = self.broken.iter().position(|x| x.name == name) {
+            let broken = self.broken.remove(index);
+            if broken.path.exists() {
+                fs::remove_dir_all(broken.path).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a profile.json, migrating it to the current schema version first
+/// Returns the profile, and the version it was migrated from if it was
+pub(crate) fn read_profile_json(
+    json: &[u8],
+) -> crate::Result<(Profile, Option<u32>)> {
+    let mut value = serde_json::from_slice::<serde_json::Value>(json)?;
+    let version = value
+        .get("schema_version")
+        .and_then(|x| x.as_u64())
+        .unwrap_or(0) as u32;
+
+    if version > CURRENT_PROFILE_SCHEMA_VERSION {
+        return Err(crate::ErrorKind::ProfileSchemaError(format!(
+            "schema version {version} is from a newer launcher"
+        ))
+        .into());
+    }
+
+    for from in version..CURRENT_PROFILE_SCHEMA_VERSION {
+        value = migrate_profile_json(from, value)?;
+        value["schema_version"] = serde_json::json!(from + 1);
+    }
+
+    let profile = serde_json::from_value::<Profile>(value)?;
+    let migrated_from =
+        Some(version).filter(|x| *x != CURRENT_PROFILE_SCHEMA_VERSION);
+
+    Ok((profile, migrated_from))
+}
+
+fn primary_file(
+    version: &ModrinthVersion,
+) -> crate::Result<&ModrinthVersionFile> {
+    version.primary_file().ok_or_else(|| {
+        crate::ErrorKind::InputError(
+            "No files for input version present!".to_string(),
+        )
+        .into()
+    })
+}
+
+fn is_profile_dir(path: &Path) -> bool {
+    let json_path = path.join(PROFILE_JSON_PATH);
+    json_path.exists() || backup_path(&json_path).exists()
+}
+
+// Whether a profile folder failed to load because neither its profile.json
+// nor its backup can be parsed, rather than because they couldn't be read.
+// Only the former means the profile is broken
+async fn is_unparsable(path: &Path) -> bool {
+    let json_path = path.join(PROFILE_JSON_PATH);
+    let mut unparsable = false;
+    for path in [backup_path(&json_path), json_path] {
+        match fs::read(&path).await {
+            Ok(json) if read_profile_json(&json).is_ok() => return false,
+            Ok(_) => unparsable = true,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(_) => return false,
+        }
+    }
+
+    unparsable
+}
+
+/// Whether a path relative to a profile is its profile.json, or one of the
+/// backup and temporary files kept alongside it. These belong to the profile
+/// itself, so they're never copied to another one
+pub(crate) fn is_profile_json(relative_path: &Path) -> bool {
+    relative_path.components().count() == 1
+        && relative_path
+            .to_str()
+            .map(|x| x.starts_with(PROFILE_JSON_PATH))
+            .unwrap_or(false)
+}
+
+async fn is_from_newer_launcher(path: &Path) -> bool {
+    fs::read(path.join(PROFILE_JSON_PATH))
+        .await
+        .ok()
+        .and_then(|x| serde_json::from_slice::<serde_json::Value>(&x).ok())
+        .and_then(|x| x.get("schema_version").and_then(|x| x.as_u64()))
+        .map(|x| x > CURRENT_PROFILE_SCHEMA_VERSION as u64)
+        .unwrap_or(false)
+}
+
+// Migrates a profile.json from one schema version to the next
+fn migrate_profile_json(
+    from: u32,
+    value: serde_json::Value,
+) -> crate::Result<serde_json::Value> {
+    match from {
+        // Profiles before schema versions only gained fields that have serde
+        // defaults, so they parse as they are
+        0 => Ok(value),
+        _ => Err(crate::ErrorKind::ProfileSchemaError(format!(
+            "No migration from profile schema version {from}"
+        ))
+        .into()),
+    }
 }
@@ -1,7 +1,10 @@
 //! Project management + inference
 
 use crate::config::MODRINTH_API_URL;
-use crate::state::Profile;
+use crate::state::{
+    get_file_hashes, read_mod_metadata, ModDependency, ModLoader, NestedJar,
+    Profile,
+};
 use crate::util::fetch::{
     fetch_json, write_cached_icon, FetchSemaphore, IoSemaphore,
 };
@@ -10,10 +13,8 @@ use chrono::{DateTime, Utc};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
-use sha2::Digest;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tokio::io::AsyncReadExt;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "lowercase")]
@@ -116,6 +117,42 @@ pub struct ModrinthVersion {
     pub loaders: Vec<String>,
 }
 
+/// Whether a project made for some loaders runs on a loader. Quilt loads
+/// Fabric mods too
+pub fn loaders_support(loaders: &[String], loader: ModLoader) -> bool {
+    loaders.iter().any(|x| {
+        x == loader.as_api_str()
+            || (loader == ModLoader::Quilt && x == "fabric")
+    })
+}
+
+impl ModrinthVersion {
+    /// The file to download for this version: its primary file, or else its
+    /// first one
+    pub fn primary_file(&self) -> Option<&ModrinthVersionFile> {
+        self.files
+            .iter()
+            .find(|x| x.primary)
+            .or_else(|| self.files.first())
+    }
+
+    /// Whether this version runs on a loader. Only mods need a loader
+    pub fn supports_loader(&self, loader: ModLoader) -> bool {
+        if !matches!(
+            ProjectType::get_from_loaders(self.loaders.clone()),
+            Some(ProjectType::Mod)
+        ) {
+            return true;
+        }
+
+        loaders_support(&self.loaders, loader)
+    }
+
+    pub fn supports_game_version(&self, game_version: &str) -> bool {
+        self.game_versions.iter().any(|x| x == game_version)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ModrinthVersionFile {
     pub hashes: HashMap<String, String>,
@@ -189,6 +226,12 @@ pub enum ProjectMetadata {
         members: Vec<ModrinthTeamMember>,
         update_version: Option<Box<ModrinthVersion>>,
         incompatible: bool,
+        /// Ids of the mods in the file, as loaders know them. Modrinth
+        /// doesn't know these, so they're read from the file
+        #[serde(default)]
+        mod_ids: Vec<String>,
+        #[serde(default)]
+        nested_jars: Vec<NestedJar>,
     },
     Inferred {
         title: Option<String>,
@@ -197,6 +240,23 @@ pub enum ProjectMetadata {
         version: Option<String>,
         icon: Option<PathBuf>,
         project_type: Option<String>,
+        /// Ids of the mods in the file, as loaders know them
+        #[serde(default)]
+        mod_ids: Vec<String>,
+        /// Loaders the file is made for
+        #[serde(default)]
+        loaders: Vec<String>,
+        #[serde(default)]
+        dependencies: Vec<ModDependency>,
+        /// Minecraft versions the file supports, in its loader's range
+        /// syntax
+        #[serde(default)]
+        game_versions: Option<String>,
+        /// Loader versions the file supports, in its loader's range syntax
+        #[serde(default)]
+        loader_versions: Option<String>,
+        #[serde(default)]
+        nested_jars: Vec<NestedJar>,
     },
     Unknown,
 }
@@ -252,6 +312,40 @@ async fn read_icon_from_file(
     Ok(None)
 }
 
+// Mod ids and nested jars already read from a file, if it hasn't changed
+// since. Mods without mod ids may have been synced before those were kept,
+// so they're read again
+fn known_mod_ids(
+    previous: Option<&Project>,
+    hash: &str,
+    version: &ModrinthVersion,
+) -> Option<(Vec<String>, Vec<NestedJar>)> {
+    let previous = previous.filter(|x| x.sha512 == hash)?;
+    let (mod_ids, nested_jars) = match &previous.metadata {
+        ProjectMetadata::Modrinth {
+            mod_ids,
+            nested_jars,
+            ..
+        }
+        | ProjectMetadata::Inferred {
+            mod_ids,
+            nested_jars,
+            ..
+        } => (mod_ids, nested_jars),
+        ProjectMetadata::Unknown => return None,
+    };
+
+    let is_mod = matches!(
+        ProjectType::get_from_loaders(version.loaders.clone()),
+        Some(ProjectType::Mod)
+    );
+    if mod_ids.is_empty() && is_mod {
+        return None;
+    }
+
+    Some((mod_ids.clone(), nested_jars.clone()))
+}
+
 #[tracing::instrument(skip(profile, io_semaphore, fetch_semaphore))]
 #[theseus_macros::debug_pin]
 pub async fn infer_data_from_files(
@@ -261,18 +355,11 @@ pub async fn infer_data_from_files(
     io_semaphore: &IoSemaphore,
     fetch_semaphore: &FetchSemaphore,
 ) -> crate::Result<HashMap<PathBuf, Project>> {
-    let mut file_path_hashes = HashMap::new();
-
-    // TODO: Make this concurrent and use progressive hashing to avoid loading each JAR in memory
-    for path in paths {
-        let mut file = tokio::fs::File::open(path.clone()).await?;
-
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).await?;
-
-        let hash = format!("{:x}", sha2::Sha512::digest(&buffer));
-        file_path_hashes.insert(hash, path.clone());
-    }
+    let file_path_hashes = get_file_hashes(&paths, io_semaphore)
+        .await?
+        .into_iter()
+        .map(|(path, hashes)| (hashes.sha512, path))
+        .collect::<HashMap<_, _>>();
 
     let files_url = format!("{}version_files", MODRINTH_API_URL);
     let updates_url = format!("{}version_files/update", MODRINTH_API_URL);
@@ -352,6 +439,20 @@ pub async fn infer_data_from_files(
                     .unwrap_or_default()
                     .to_string_lossy()
                     .to_string();
+                // Jars are only read when they're new or changed, as the
+                // mod ids of the rest are already known
+                let (mod_ids, nested_jars) = match known_mod_ids(
+                    profile.projects.get(&path),
+                    &hash,
+                    version,
+                ) {
+                    Some(known) => known,
+                    None => {
+                        let mod_metadata =
+                            read_mod_metadata(&path).await?.unwrap_or_default();
+                        (mod_metadata.mod_ids, mod_metadata.nested_jars)
+                    }
+                };
 
                 return_projects.insert(
                     path,
@@ -376,15 +477,13 @@ pub async fn infer_data_from_files(
                             } else {
                                 None
                             },
-                            incompatible: !version.loaders.contains(
-                                &profile
-                                    .metadata
-                                    .loader
-                                    .as_api_str()
-                                    .to_string(),
-                            ) || version
-                                .game_versions
-                                .contains(&profile.metadata.game_version),
+                            incompatible: !version
+                                .supports_loader(profile.metadata.loader)
+                                || !version.supports_game_version(
+                                    &profile.metadata.game_version,
+                                ),
+                            mod_ids,
+                            nested_jars,
                         },
                         sha512: hash,
                         file_name,
@@ -420,284 +519,38 @@ pub async fn infer_data_from_files(
             );
             continue;
         };
-        let zip_index_option = zip_file_reader
-            .file()
-            .entries()
-            .iter()
-            .position(|f| f.entry().filename() == "META-INF/mods.toml");
-        if let Some(index) = zip_index_option {
-            let file = zip_file_reader.file().entries().get(index).unwrap();
-            #[derive(Deserialize)]
-            #[serde(rename_all = "camelCase")]
-            struct ForgeModInfo {
-                pub mods: Vec<ForgeMod>,
-            }
-            #[derive(Deserialize)]
-            #[serde(rename_all = "camelCase")]
-            struct ForgeMod {
-                mod_id: String,
-                version: Option<String>,
-                display_name: Option<String>,
-                description: Option<String>,
-                logo_file: Option<String>,
-                authors: Option<String>,
-            }
+        if let Some(mod_metadata) = read_mod_metadata(&path).await? {
+            let icon = read_icon_from_file(
+                mod_metadata.icon,
+                &cache_dir,
+                &path,
+                io_semaphore,
+            )
+            .await?;
 
-            let mut file_str = String::new();
-            if zip_file_reader
-                .entry(index)
-                .await?
-                .read_to_string_checked(&mut file_str, file.entry())
-                .await
-                .is_ok()
-            {
-                if let Ok(pack) =
-                    serde_json::from_str::<ForgeModInfo>(&file_str)
-                {
-                    if let Some(pack) = pack.mods.first() {
-                        let icon = read_icon_from_file(
-                            pack.logo_file.clone(),
-                            &cache_dir,
-                            &path,
-                            io_semaphore,
-                        )
-                        .await?;
-
-                        return_projects.insert(
-                            path.clone(),
-                            Project {
-                                sha512: hash,
-                                disabled: file_name.ends_with(".disabled"),
-                                file_name,
-                                metadata: ProjectMetadata::Inferred {
-                                    title: Some(
-                                        pack.display_name
-                                            .clone()
-                                            .unwrap_or_else(|| {
-                                                pack.mod_id.clone()
-                                            }),
-                                    ),
-                                    description: pack.description.clone(),
-                                    authors: pack
-                                        .authors
-                                        .clone()
-                                        .map(|x| vec![x])
-                                        .unwrap_or_default(),
-                                    version: pack.version.clone(),
-                                    icon,
-                                    project_type: Some("mod".to_string()),
-                                },
-                            },
-                        );
-                        continue;
-                    }
-                }
-            }
-        }
-
-        let zip_index_option = zip_file_reader
-            .file()
-            .entries()
-            .iter()
-            .position(|f| f.entry().filename() == "mcmod.info");
-        if let Some(index) = zip_index_option {
-            let file = zip_file_reader.file().entries().get(index).unwrap();
-            #[derive(Deserialize)]
-            #[serde(rename_all = "camelCase")]
-            struct ForgeMod {
-                modid: String,
-                name: String,
-                description: Option<String>,
-                version: Option<String>,
-                author_list: Option<Vec<String>>,
-                logo_file: Option<String>,
-            }
-
-            let mut file_str = String::new();
-            if zip_file_reader
-                .entry(index)
-                .await?
-                .read_to_string_checked(&mut file_str, file.entry())
-                .await
-                .is_ok()
-            {
-                if let Ok(pack) = serde_json::from_str::<ForgeMod>(&file_str) {
-                    let icon = read_icon_from_file(
-                        pack.logo_file,
-                        &cache_dir,
-                        &path,
-                        io_semaphore,
-                    )
-                    .await?;
-
-                    return_projects.insert(
-                        path.clone(),
-                        Project {
-                            sha512: hash,
-                            disabled: file_name.ends_with(".disabled"),
-                            file_name,
-                            metadata: ProjectMetadata::Inferred {
-                                title: Some(if pack.name.is_empty() {
-                                    pack.modid
-                                } else {
-                                    pack.name
-                                }),
-                                description: pack.description,
-                                authors: pack.author_list.unwrap_or_default(),
-                                version: pack.version,
-                                icon,
-                                project_type: Some("mod".to_string()),
-                            },
-                        },
-                    );
-                    continue;
-                }
-            }
-        }
-
-        let zip_index_option = zip_file_reader
-            .file()
-            .entries()
-            .iter()
-            .position(|f| f.entry().filename() == "fabric.mod.json");
-        if let Some(index) = zip_index_option {
-            let file = zip_file_reader.file().entries().get(index).unwrap();
-            #[derive(Deserialize)]
-            #[serde(untagged)]
-            enum FabricAuthor {
-                String(String),
-                Object { name: String },
-            }
-            #[derive(Deserialize)]
-            #[serde(rename_all = "camelCase")]
-            struct FabricMod {
-                id: String,
-                version: String,
-                name: Option<String>,
-                description: Option<String>,
-                authors: Vec<FabricAuthor>,
-                icon: Option<String>,
-            }
-
-            let mut file_str = String::new();
-            if zip_file_reader
-                .entry(index)
-                .await?
-                .read_to_string_checked(&mut file_str, file.entry())
-                .await
-                .is_ok()
-            {
-                if let Ok(pack) = serde_json::from_str::<FabricMod>(&file_str) {
-                    let icon = read_icon_from_file(
-                        pack.icon,
-                        &cache_dir,
-                        &path,
-                        io_semaphore,
-                    )
-                    .await?;
-
-                    return_projects.insert(
-                        path.clone(),
-                        Project {
-                            sha512: hash,
-                            disabled: file_name.ends_with(".disabled"),
-                            file_name,
-                            metadata: ProjectMetadata::Inferred {
-                                title: Some(pack.name.unwrap_or(pack.id)),
-                                description: pack.description,
-                                authors: pack
-                                    .authors
-                                    .into_iter()
-                                    .map(|x| match x {
-                                        FabricAuthor::String(name) => name,
-                                        FabricAuthor::Object { name } => name,
-                                    })
-                                    .collect(),
-                                version: Some(pack.version),
-                                icon,
-                                project_type: Some("mod".to_string()),
-                            },
-                        },
-                    );
-                    continue;
-                }
-            }
-        }
-
-        let zip_index_option = zip_file_reader
-            .file()
-            .entries()
-            .iter()
-            .position(|f| f.entry().filename() == "quilt.mod.json");
-        if let Some(index) = zip_index_option {
-            let file = zip_file_reader.file().entries().get(index).unwrap();
-            #[derive(Deserialize)]
-            struct QuiltMetadata {
-                pub name: Option<String>,
-                pub description: Option<String>,
-                pub contributors: Option<HashMap<String, String>>,
-                pub icon: Option<String>,
-            }
-            #[derive(Deserialize)]
-            struct QuiltMod {
-                id: String,
-                version: String,
-                metadata: Option<QuiltMetadata>,
-            }
-
-            let mut file_str = String::new();
-            if zip_file_reader
-                .entry(index)
-                .await?
-                .read_to_string_checked(&mut file_str, file.entry())
-                .await
-                .is_ok()
-            {
-                if let Ok(pack) = serde_json::from_str::<QuiltMod>(&file_str) {
-                    let icon = read_icon_from_file(
-                        pack.metadata.as_ref().and_then(|x| x.icon.clone()),
-                        &cache_dir,
-                        &path,
-                        io_semaphore,
-                    )
-                    .await?;
-
-                    return_projects.insert(
-                        path.clone(),
-                        Project {
-                            sha512: hash,
-                            disabled: file_name.ends_with(".disabled"),
-                            file_name,
-                            metadata: ProjectMetadata::Inferred {
-                                title: Some(
-                                    pack.metadata
-                                        .as_ref()
-                                        .and_then(|x| x.name.clone())
-                                        .unwrap_or(pack.id),
-                                ),
-                                description: pack
-                                    .metadata
-                                    .as_ref()
-                                    .and_then(|x| x.description.clone()),
-                                authors: pack
-                                    .metadata
-                                    .map(|x| {
-                                        x.contributors
-                                            .unwrap_or_default()
-                                            .keys()
-                                            .cloned()
-                                            .collect()
-                                    })
-                                    .unwrap_or_default(),
-                                version: Some(pack.version),
-                                icon,
-                                project_type: Some("mod".to_string()),
-                            },
-                        },
-                    );
-                    continue;
-                }
-            }
+            return_projects.insert(
+                path.clone(),
+                Project {
+                    sha512: hash,
+                    disabled: file_name.ends_with(".disabled"),
+                    file_name,
+                    metadata: ProjectMetadata::Inferred {
+                        title: mod_metadata.title,
+                        description: mod_metadata.description,
+                        authors: mod_metadata.authors,
+                        version: mod_metadata.version,
+                        icon,
+                        project_type: Some("mod".to_string()),
+                        mod_ids: mod_metadata.mod_ids,
+                        loaders: mod_metadata.loaders,
+                        dependencies: mod_metadata.dependencies,
+                        game_versions: mod_metadata.game_versions,
+                        loader_versions: mod_metadata.loader_versions,
+                        nested_jars: mod_metadata.nested_jars,
+                    },
+                },
+            );
+            continue;
         }
 
         let zip_index_option = zip_file_reader
@@ -741,6 +594,12 @@ pub async fn infer_data_from_files(
                                 version: None,
                                 icon,
                                 project_type: None,
+                                mod_ids: Vec::new(),
+                                loaders: Vec::new(),
+                                dependencies: Vec::new(),
+                                game_versions: None,
+                                loader_versions: None,
+                                nested_jars: Vec::new(),
                             },
                         },
                     );
@@ -0,0 +1,85 @@
+//! Play sessions of profiles, used to track playtime
+use crate::data::DirectoryInfo;
+use crate::util::io::{backup_path, read_json_with_backup, write_atomic};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+// A single run of Minecraft for a profile
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Session {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub exit_code: Option<i32>,
+    /// The account the game was launched with
+    pub user: Uuid,
+}
+
+impl Session {
+    // Time played within a window, so sessions that started before it only
+    // count the part that overlaps it
+    fn played_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+        let start = self.start.max(from);
+        let end = self.end.min(to);
+
+        (end - start).num_seconds().max(0)
+    }
+}
+
+/// Aggregated playtime of a profile, in seconds
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Playtime {
+    pub total: i64,
+    pub last_week: i64,
+    pub per_account: HashMap<Uuid, i64>,
+}
+
+impl Playtime {
+    pub fn from_sessions(sessions: &[Session]) -> Self {
+        let now = Utc::now();
+        let week_ago = now - Duration::days(7);
+
+        let mut playtime = Playtime::default();
+        for session in sessions {
+            let played = (session.end - session.start).num_seconds().max(0);
+
+            playtime.total += played;
+            playtime.last_week += session.played_between(week_ago, now);
+            *playtime.per_account.entry(session.user).or_insert(0) += played;
+        }
+
+        playtime
+    }
+}
+
+/// Reads the recorded sessions of a profile, oldest first
+pub async fn read_sessions(
+    dirs: &DirectoryInfo,
+    profile: Uuid,
+) -> crate::Result<Vec<Session>> {
+    let path = dirs.profile_sessions_file(profile);
+    if !path.exists() && !backup_path(&path).exists() {
+        return Ok(Vec::new());
+    }
+
+    read_json_with_backup(&path).await
+}
+
+/// Appends a session to the recorded sessions of a profile
+pub async fn record_session(
+    dirs: &DirectoryInfo,
+    profile: Uuid,
+    session: Session,
+) -> crate::Result<()> {
+    let mut sessions = read_sessions(dirs, profile).await?;
+    sessions.push(session);
+
+    write_atomic(
+        &dirs.profile_sessions_file(profile),
+        &serde_json::to_vec(&sessions)?,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -1,11 +1,12 @@
 //! Theseus settings file
 use crate::{
     jre::{self, autodetect_java_globals, find_filtered_jres},
+    util::io::{backup_path, read_json_with_backup, write_atomic},
     State,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
-use tokio::fs;
 
 use super::JavaGlobals;
 
@@ -41,6 +42,18 @@ pub struct Settings {
     pub advanced_rendering: bool,
     #[serde(default)]
     pub onboarded: bool,
+    #[serde(default)]
+    pub curseforge_api_key: Option<String>,
+    #[serde(default)]
+    pub snapshot_retention: SnapshotRetention,
+    /// Top-level folders (such as `screenshots`) shared by every profile
+    /// that doesn't set its own
+    #[serde(default)]
+    pub shared_folders: Vec<String>,
+    /// Options written to the options.txt of newly created profiles, such
+    /// as keybinds or the language
+    #[serde(default)]
+    pub default_options: BTreeMap<String, String>,
 }
 
 impl Default for Settings {
@@ -64,6 +77,10 @@ impl Default for Settings {
             opt_out_analytics: false,
             advanced_rendering: true,
             onboarded: false,
+            curseforge_api_key: None,
+            snapshot_retention: SnapshotRetention::default(),
+            shared_folders: Vec::new(),
+            default_options: BTreeMap::new(),
         }
     }
 }
@@ -71,8 +88,8 @@ impl Default for Settings {
 impl Settings {
     #[tracing::instrument]
     pub async fn init(file: &Path) -> crate::Result<Self> {
-        if file.exists() {
-            fs::read(&file)
+        if file.exists() || backup_path(file).exists() {
+            read_json_with_backup::<Settings>(file)
                 .await
                 .map_err(|err| {
                     crate::ErrorKind::FSError(format!(
@@ -80,10 +97,6 @@ impl Settings {
                     ))
                     .as_error()
                 })
-                .and_then(|it| {
-                    serde_json::from_slice::<Settings>(&it)
-                        .map_err(crate::Error::from)
-                })
         } else {
             Ok(Settings::default())
         }
@@ -125,7 +138,7 @@ impl Settings {
 
     #[tracing::instrument(skip(self))]
     pub async fn sync(&self, to: &Path) -> crate::Result<()> {
-        fs::write(to, serde_json::to_vec(self)?)
+        write_atomic(to, &serde_json::to_vec(self)?)
             .await
             .map_err(|err| {
                 crate::ErrorKind::FSError(format!(
@@ -169,6 +182,10 @@ impl Default for WindowSize {
 }
 
 /// Game initialization hooks
+/// Hooks are split into arguments like a shell command, and can refer to
+/// `${PROFILE_PATH}`, `${PROFILE_NAME}`, `${GAME_VERSION}`, `${LOADER}`,
+/// `${JAVA_PATH}` and (after the game exits) `${EXIT_CODE}`, which are also
+/// set as environment variables
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(default)]
 pub struct Hooks {
@@ -178,6 +195,32 @@ pub struct Hooks {
     pub wrapper: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub post_exit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_install: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_install: Option<String>,
+    /// Ran instead of the post-exit hook when the game exits with an error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_crash: Option<String>,
+}
+
+/// How many profile snapshots are kept
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct SnapshotRetention {
+    /// Maximum number of snapshots per profile
+    pub max_snapshots: Option<usize>,
+    /// Maximum age of a snapshot in days
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for SnapshotRetention {
+    fn default() -> Self {
+        Self {
+            max_snapshots: Some(5),
+            max_age_days: None,
+        }
+    }
 }
 
 /// Opening window to start with
@@ -0,0 +1,232 @@
+//! Folders shared between profiles
+//! A shared folder lives once under the shared directory, and each profile
+//! sharing it has a link (a symlink, or a junction on Windows) in its place
+use super::{DirectoryInfo, PROFILE_JSON_PATH};
+use dunce::canonicalize;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+
+/// Checks that a shared folder is a single top-level folder of a profile
+pub fn validate_shared_folder(folder: &str) -> crate::Result<()> {
+    let mut components = Path::new(folder).components();
+    let valid = matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && folder != PROFILE_JSON_PATH
+        && folder != "modrinth_logs";
+
+    if !valid {
+        return Err(crate::ErrorKind::InputError(format!(
+            "{folder} can't be shared, only top-level folders of a profile can"
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Whether a path is a link to a shared folder
+pub fn is_shared_link(dirs: &DirectoryInfo, path: &Path) -> bool {
+    let is_link = std::fs::symlink_metadata(path)
+        .map(|x| x.file_type().is_symlink())
+        .unwrap_or(false);
+
+    is_link
+        && match (canonicalize(path), canonicalize(dirs.shared_dir())) {
+            (Ok(target), Ok(shared_dir)) => target.starts_with(shared_dir),
+            _ => false,
+        }
+}
+
+/// Makes a profile's shared folders match the given list: newly shared
+/// folders have their content moved to the shared folder and are replaced
+/// with links, and folders no longer shared get a copy of the shared content
+#[tracing::instrument(skip(dirs))]
+pub async fn sync_shared_folders(
+    dirs: &DirectoryInfo,
+    profile_path: &Path,
+    shared: &[String],
+) -> crate::Result<()> {
+    for folder in shared {
+        validate_shared_folder(folder)?;
+        share_folder(dirs, profile_path, folder).await?;
+    }
+
+    let mut entries = fs::read_dir(profile_path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !shared.contains(&name) && is_shared_link(dirs, &entry.path()) {
+            unshare_folder(dirs, profile_path, &name).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn share_folder(
+    dirs: &DirectoryInfo,
+    profile_path: &Path,
+    folder: &str,
+) -> crate::Result<()> {
+    let store = dirs.shared_folder_dir(folder);
+    let path = profile_path.join(folder);
+    fs::create_dir_all(&store).await?;
+
+    if let Ok(metadata) = fs::symlink_metadata(&path).await {
+        if metadata.file_type().is_symlink() {
+            // Links made by the user are left alone, only links to another
+            // shared folder are replaced
+            if !is_shared_link(dirs, &path) {
+                return Err(crate::ErrorKind::InputError(format!(
+                    "{} is a link outside the shared folders, so it can't be \
+                     shared",
+                    path.display()
+                ))
+                .into());
+            }
+            if canonicalize(&path)? == canonicalize(&store)? {
+                return Ok(());
+            }
+            remove_link(&path).await?;
+        } else if metadata.is_dir() {
+            // Existing content joins the shared folder, renamed on conflict
+            let mut entries = fs::read_dir(&path).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let dest = unique_path(store.join(entry.file_name()));
+                move_entry(&entry.path(), &dest).await?;
+            }
+            fs::remove_dir(&path).await?;
+        } else {
+            return Err(crate::ErrorKind::InputError(format!(
+                "{} is a file, so it can't be shared",
+                path.display()
+            ))
+            .into());
+        }
+    }
+
+    create_link(&store, &path).await
+}
+
+async fn unshare_folder(
+    dirs: &DirectoryInfo,
+    profile_path: &Path,
+    folder: &str,
+) -> crate::Result<()> {
+    let path = profile_path.join(folder);
+    let target = canonicalize(&path)?;
+    remove_link(&path).await?;
+
+    // Other profiles may still share the folder, so it's copied rather
+    // than moved back
+    if target.starts_with(canonicalize(dirs.shared_dir())?) {
+        copy_dir(&target, &path).await?;
+    } else {
+        fs::create_dir_all(&path).await?;
+    }
+
+    Ok(())
+}
+
+// Adds " (n)" to a file name until it doesn't exist
+fn unique_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|x| format!(".{}", x.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|i| path.with_file_name(format!("{stem} ({i}){extension}")))
+        .find(|x| !x.exists())
+        .unwrap_or(path)
+}
+
+// Renames a file or folder, copying it instead if it's on another drive
+async fn move_entry(src: &Path, dest: &Path) -> crate::Result<()> {
+    if fs::rename(src, dest).await.is_err() {
+        if src.is_dir() {
+            copy_dir(src, dest).await?;
+            fs::remove_dir_all(src).await?;
+        } else {
+            fs::copy(src, dest).await?;
+            fs::remove_file(src).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[async_recursion::async_recursion]
+async fn copy_dir(src: &Path, dest: &Path) -> crate::Result<()> {
+    fs::create_dir_all(dest).await?;
+
+    let mut entries = fs::read_dir(src).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let dest = dest.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &dest).await?;
+        } else {
+            fs::copy(&path, &dest).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn create_link(target: &Path, link: &Path) -> crate::Result<()> {
+    fs::symlink(target, link).await?;
+    Ok(())
+}
+
+#[cfg(windows)]
+async fn create_link(target: &Path, link: &Path) -> crate::Result<()> {
+    // Symlinks need developer mode or administrator rights on Windows, but
+    // junctions don't
+    if fs::symlink_dir(target, link).await.is_ok() {
+        return Ok(());
+    }
+
+    let output = tokio::process::Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(link)
+        .arg(target)
+        .output()
+        .await?;
+
+    if !output.status.success() {
+        return Err(crate::ErrorKind::FSError(format!(
+            "Could not link {} to the shared folder: {}",
+            link.display(),
+            String::from_utf8_lossy(&output.stderr)
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+async fn remove_link(link: &Path) -> crate::Result<()> {
+    fs::remove_file(link).await?;
+    Ok(())
+}
+
+#[cfg(windows)]
+async fn remove_link(link: &Path) -> crate::Result<()> {
+    // Directory symlinks and junctions are removed as folders on Windows,
+    // which leaves their target untouched
+    fs::remove_dir(link).await?;
+    Ok(())
+}
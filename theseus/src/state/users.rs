@@ -1,7 +1,8 @@
 //! User login info
 use crate::auth::Credentials;
 use crate::data::DirectoryInfo;
-use crate::util::fetch::{read_json, write, IoSemaphore};
+use crate::util::fetch::IoSemaphore;
+use crate::util::io::{read_json_with_backup, write_atomic};
 use crate::State;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -18,7 +19,11 @@ impl Users {
         io_semaphore: &IoSemaphore,
     ) -> crate::Result<Self> {
         let users_path = dirs.caches_meta_dir().join(USERS_JSON);
-        let users = read_json(&users_path, io_semaphore).await.ok();
+        let users = {
+            let io_semaphore = io_semaphore.0.read().await;
+            let _permit = io_semaphore.acquire().await?;
+            read_json_with_backup(&users_path).await.ok()
+        };
 
         if let Some(users) = users {
             Ok(Self(users))
@@ -30,12 +35,9 @@ impl Users {
     pub async fn save(&self) -> crate::Result<()> {
         let state = State::get().await?;
         let users_path = state.directories.caches_meta_dir().join(USERS_JSON);
-        write(
-            &users_path,
-            &serde_json::to_vec(&self.0)?,
-            &state.io_semaphore,
-        )
-        .await?;
+        let io_semaphore = state.io_semaphore.0.read().await;
+        let _permit = io_semaphore.acquire().await?;
+        write_atomic(&users_path, &serde_json::to_vec(&self.0)?).await?;
 
         Ok(())
     }
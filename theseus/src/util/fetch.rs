@@ -218,6 +218,49 @@ pub async fn write<'a>(
     Ok(())
 }
 
+#[tracing::instrument(skip(semaphore))]
+pub async fn copy(
+    src: &Path,
+    dest: &Path,
+    semaphore: &IoSemaphore,
+) -> crate::Result<()> {
+    let io_semaphore = semaphore.0.read().await;
+    let _permit = io_semaphore.acquire().await?;
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    fs::copy(src, dest).await?;
+    tracing::trace!("Done copying file {}", dest.display());
+    Ok(())
+}
+
+/// Hard links a file, falling back to copying it if the file system
+/// doesn't support links (ie: across drives)
+#[tracing::instrument(skip(semaphore))]
+pub async fn link_or_copy(
+    src: &Path,
+    dest: &Path,
+    semaphore: &IoSemaphore,
+) -> crate::Result<()> {
+    let io_semaphore = semaphore.0.read().await;
+    let _permit = io_semaphore.acquire().await?;
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    if let Err(err) = fs::hard_link(src, dest).await {
+        tracing::debug!(
+            "Unable to link {}, copying instead: {err}",
+            dest.display()
+        );
+        fs::copy(src, dest).await?;
+    }
+    Ok(())
+}
+
 #[tracing::instrument(skip(bytes, semaphore))]
 pub async fn write_cached_icon(
     icon_path: &str,
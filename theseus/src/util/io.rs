@@ -0,0 +1,166 @@
+//! Crash-safe persistence of launcher state
+use std::ffi::OsString;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+use tokio::fs;
+
+// Appends an extension to a path, so `profile.json` becomes `profile.json.bak`
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name =
+        path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// Gets the path of the backup kept of a file written by write_atomic
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+/// Writes a file so that a crash at any point leaves either the old or the
+/// new contents on disk, never a partial file
+/// The bytes are written and flushed to a temporary file, which then replaces
+/// the file. Each write has its own temporary file, so concurrent writes to
+/// the same file can't interleave. The previous contents are kept as a
+/// backup (see backup_path)
+#[tracing::instrument(skip(bytes))]
+pub async fn write_atomic(path: &Path, bytes: &[u8]) -> crate::Result<()> {
+    let parent = path.parent().ok_or_else(|| {
+        crate::ErrorKind::FSError(format!(
+            "Cannot write {}, as it has no parent folder",
+            path.display()
+        ))
+    })?;
+    fs::create_dir_all(parent).await?;
+
+    let path = path.to_path_buf();
+    let parent = parent.to_path_buf();
+    let bytes = bytes.to_vec();
+    tokio::task::spawn_blocking(move || {
+        let mut file = NamedTempFile::new_in(&parent)?;
+        file.write_all(&bytes)?;
+        file.as_file().sync_all()?;
+
+        // A concurrent write may have moved the file to its backup already
+        match std::fs::rename(&path, backup_path(&path)) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err.into()),
+        }
+        file.persist(&path).map_err(|err| err.error)?;
+
+        // Renames are only durable once the directory itself is flushed.
+        // This isn't possible on Windows, where renames are already flushed
+        #[cfg(unix)]
+        std::fs::File::open(&parent)?.sync_all()?;
+
+        tracing::trace!("Done writing file {}", path.display());
+        Ok::<(), crate::Error>(())
+    })
+    .await?
+}
+
+/// Reads and parses a file written by write_atomic
+/// If the file is missing or fails to parse, its backup is used instead. The
+/// error of the file itself is returned if both fail
+#[tracing::instrument(skip(parse))]
+pub async fn read_with_backup<T>(
+    path: &Path,
+    parse: impl Fn(&[u8]) -> crate::Result<T>,
+) -> crate::Result<T> {
+    let err = match fs::read(path).await {
+        Ok(bytes) => match parse(&bytes) {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        },
+        Err(err) => err.into(),
+    };
+
+    let backup = backup_path(path);
+    if backup.exists() {
+        if let Ok(value) = parse(&fs::read(&backup).await?) {
+            tracing::warn!(
+                "Unable to read {}, recovered it from backup: {err}",
+                path.display()
+            );
+            return Ok(value);
+        }
+    }
+
+    Err(err)
+}
+
+/// Reads a JSON file written by write_atomic, recovering it from its backup
+/// if necessary
+pub async fn read_json_with_backup<T>(path: &Path) -> crate::Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    read_with_backup(path, |bytes| Ok(serde_json::from_slice(bytes)?)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_number(bytes: &[u8]) -> crate::Result<u32> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+
+    #[tokio::test]
+    async fn keeps_previous_contents_as_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        write_atomic(&path, b"1").await.unwrap();
+        write_atomic(&path, b"2").await.unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), b"2");
+        assert_eq!(fs::read(backup_path(&path)).await.unwrap(), b"1");
+        // Only the file and its backup are left behind
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn concurrent_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        futures::future::try_join_all((0..16u32).map(|x| {
+            let path = path.clone();
+            async move { write_atomic(&path, x.to_string().as_bytes()).await }
+        }))
+        .await
+        .unwrap();
+
+        let value = read_with_backup(&path, parse_number).await.unwrap();
+        assert!(value < 16);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn recovers_from_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        write_atomic(&path, b"1").await.unwrap();
+        write_atomic(&path, b"2").await.unwrap();
+        fs::write(&path, b"{ truncated").await.unwrap();
+        assert_eq!(read_with_backup(&path, parse_number).await.unwrap(), 1);
+
+        fs::remove_file(&path).await.unwrap();
+        assert_eq!(read_with_backup(&path, parse_number).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn fails_without_a_valid_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert!(read_with_backup(&path, parse_number).await.is_err());
+
+        fs::write(&path, b"{ truncated").await.unwrap();
+        fs::write(backup_path(&path), b"[]").await.unwrap();
+        assert!(read_with_backup(&path, parse_number).await.is_err());
+    }
+}
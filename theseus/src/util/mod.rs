@@ -1,6 +1,8 @@
 //! Theseus utility functions
 pub mod fetch;
+pub mod io;
 pub mod jre;
+pub mod nbt;
 pub mod platform;
 
 /// Wrap a builder which uses a mut reference into one which outputs an owned value
@@ -0,0 +1,440 @@
+//! Reading and writing of Minecraft's NBT format
+use flate2::read::GzDecoder;
+use std::convert::TryFrom;
+use std::io::Read;
+
+pub const TAG_END: u8 = 0;
+pub const TAG_BYTE: u8 = 1;
+pub const TAG_SHORT: u8 = 2;
+pub const TAG_INT: u8 = 3;
+pub const TAG_LONG: u8 = 4;
+pub const TAG_FLOAT: u8 = 5;
+pub const TAG_DOUBLE: u8 = 6;
+pub const TAG_BYTE_ARRAY: u8 = 7;
+pub const TAG_STRING: u8 = 8;
+pub const TAG_LIST: u8 = 9;
+pub const TAG_COMPOUND: u8 = 10;
+pub const TAG_INT_ARRAY: u8 = 11;
+pub const TAG_LONG_ARRAY: u8 = 12;
+
+// Nesting deeper than this is refused, so a broken file can't overflow the
+// stack
+const MAX_DEPTH: usize = 512;
+
+/// A single NBT tag. Compounds keep their keys in file order, so a file can
+/// be written back as it was read
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nbt {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// The element tag type, then the elements
+    List(u8, Vec<Nbt>),
+    Compound(Vec<(String, Nbt)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Nbt {
+    fn tag_type(&self) -> u8 {
+        match self {
+            Nbt::Byte(_) => TAG_BYTE,
+            Nbt::Short(_) => TAG_SHORT,
+            Nbt::Int(_) => TAG_INT,
+            Nbt::Long(_) => TAG_LONG,
+            Nbt::Float(_) => TAG_FLOAT,
+            Nbt::Double(_) => TAG_DOUBLE,
+            Nbt::ByteArray(_) => TAG_BYTE_ARRAY,
+            Nbt::String(_) => TAG_STRING,
+            Nbt::List(..) => TAG_LIST,
+            Nbt::Compound(_) => TAG_COMPOUND,
+            Nbt::IntArray(_) => TAG_INT_ARRAY,
+            Nbt::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// Gets a tag from a compound by key
+    pub fn get(&self, key: &str) -> Option<&Nbt> {
+        match self {
+            Nbt::Compound(entries) => {
+                entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            _ => None,
+        }
+    }
+
+    /// Sets a key of a compound, keeping its position if it already exists
+    pub fn insert(&mut self, key: &str, value: Nbt) {
+        if let Nbt::Compound(entries) = self {
+            match entries.iter_mut().find(|(k, _)| k == key) {
+                Some((_, v)) => *v = value,
+                None => entries.push((key.to_string(), value)),
+            }
+        }
+    }
+
+    /// Removes a key from a compound
+    pub fn remove(&mut self, key: &str) {
+        if let Nbt::Compound(entries) = self {
+            entries.retain(|(k, _)| k != key);
+        }
+    }
+
+    /// Gets any integer tag as an i64
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Nbt::Byte(x) => Some(x as i64),
+            Nbt::Short(x) => Some(x as i64),
+            Nbt::Int(x) => Some(x as i64),
+            Nbt::Long(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Nbt::String(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Nbt]> {
+        match self {
+            Nbt::List(_, x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+fn nbt_error(message: &str) -> crate::Error {
+    crate::ErrorKind::NBTError(message.to_string()).into()
+}
+
+/// Reads an uncompressed NBT file, returning the name and tag of its root
+pub fn read(bytes: &[u8]) -> crate::Result<(String, Nbt)> {
+    let mut reader = bytes;
+    let tag_type = read_u8(&mut reader)?;
+    if tag_type != TAG_COMPOUND {
+        return Err(nbt_error("root tag is not a compound"));
+    }
+
+    let name = read_string(&mut reader)?;
+    let root = read_payload(&mut reader, tag_type, 0)?;
+    Ok((name, root))
+}
+
+/// Reads a gzip compressed NBT file, such as level.dat
+pub fn read_gzip(bytes: &[u8]) -> crate::Result<(String, Nbt)> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+    read(&decompressed)
+}
+
+/// Writes an uncompressed NBT file with the given root
+pub fn write(name: &str, root: &Nbt) -> crate::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    bytes.push(root.tag_type());
+    write_string(&mut bytes, name)?;
+    write_payload(&mut bytes, root)?;
+    Ok(bytes)
+}
+
+fn read_bytes<const N: usize>(reader: &mut &[u8]) -> crate::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader
+        .read_exact(&mut bytes)
+        .map_err(|_| nbt_error("unexpected end of file"))?;
+    Ok(bytes)
+}
+
+fn read_u8(reader: &mut &[u8]) -> crate::Result<u8> {
+    Ok(read_bytes::<1>(reader)?[0])
+}
+
+fn read_len(reader: &mut &[u8]) -> crate::Result<usize> {
+    let len = i32::from_be_bytes(read_bytes(reader)?);
+    // Every element takes at least a byte, which bounds allocations by the
+    // size of the file
+    if len < 0 || len as usize > reader.len() {
+        return Err(nbt_error("invalid length"));
+    }
+    Ok(len as usize)
+}
+
+// Strings are Java's modified UTF-8, which matches UTF-8 except that null
+// characters take two bytes, and characters outside the BMP are written as
+// the two 3 byte encodings of their UTF-16 surrogates
+fn read_string(reader: &mut &[u8]) -> crate::Result<String> {
+    let len = u16::from_be_bytes(read_bytes(reader)?) as usize;
+    if len > reader.len() {
+        return Err(nbt_error("unexpected end of file"));
+    }
+    let (string, rest) = reader.split_at(len);
+    *reader = rest;
+
+    let mut units = Vec::with_capacity(string.len());
+    let mut bytes = string.iter().copied();
+    while let Some(first) = bytes.next() {
+        let mut continuation = || match bytes.next() {
+            Some(byte) if byte & 0xC0 == 0x80 => Ok((byte & 0x3F) as u16),
+            _ => Err(nbt_error("invalid modified UTF-8 string")),
+        };
+
+        units.push(match first {
+            0x00..=0x7F => first as u16,
+            0xC0..=0xDF => ((first & 0x1F) as u16) << 6 | continuation()?,
+            0xE0..=0xEF => {
+                ((first & 0x0F) as u16) << 12
+                    | continuation()? << 6
+                    | continuation()?
+            }
+            _ => return Err(nbt_error("invalid modified UTF-8 string")),
+        });
+    }
+
+    String::from_utf16(&units)
+        .map_err(|_| nbt_error("invalid modified UTF-8 string"))
+}
+
+fn read_payload(
+    reader: &mut &[u8],
+    tag_type: u8,
+    depth: usize,
+) -> crate::Result<Nbt> {
+    if depth > MAX_DEPTH {
+        return Err(nbt_error("tags are nested too deeply"));
+    }
+
+    Ok(match tag_type {
+        TAG_BYTE => Nbt::Byte(i8::from_be_bytes(read_bytes(reader)?)),
+        TAG_SHORT => Nbt::Short(i16::from_be_bytes(read_bytes(reader)?)),
+        TAG_INT => Nbt::Int(i32::from_be_bytes(read_bytes(reader)?)),
+        TAG_LONG => Nbt::Long(i64::from_be_bytes(read_bytes(reader)?)),
+        TAG_FLOAT => Nbt::Float(f32::from_be_bytes(read_bytes(reader)?)),
+        TAG_DOUBLE => Nbt::Double(f64::from_be_bytes(read_bytes(reader)?)),
+        TAG_BYTE_ARRAY => {
+            let len = read_len(reader)?;
+            Nbt::ByteArray(
+                (0..len)
+                    .map(|_| Ok(i8::from_be_bytes(read_bytes(reader)?)))
+                    .collect::<crate::Result<_>>()?,
+            )
+        }
+        TAG_STRING => Nbt::String(read_string(reader)?),
+        TAG_LIST => {
+            let element_type = read_u8(reader)?;
+            let len = read_len(reader)?;
+            Nbt::List(
+                element_type,
+                (0..len)
+                    .map(|_| read_payload(reader, element_type, depth + 1))
+                    .collect::<crate::Result<_>>()?,
+            )
+        }
+        TAG_COMPOUND => {
+            let mut entries = Vec::new();
+            loop {
+                let tag_type = read_u8(reader)?;
+                if tag_type == TAG_END {
+                    break;
+                }
+                let name = read_string(reader)?;
+                entries
+                    .push((name, read_payload(reader, tag_type, depth + 1)?));
+            }
+            Nbt::Compound(entries)
+        }
+        TAG_INT_ARRAY => {
+            let len = read_len(reader)?;
+            Nbt::IntArray(
+                (0..len)
+                    .map(|_| Ok(i32::from_be_bytes(read_bytes(reader)?)))
+                    .collect::<crate::Result<_>>()?,
+            )
+        }
+        TAG_LONG_ARRAY => {
+            let len = read_len(reader)?;
+            Nbt::LongArray(
+                (0..len)
+                    .map(|_| Ok(i64::from_be_bytes(read_bytes(reader)?)))
+                    .collect::<crate::Result<_>>()?,
+            )
+        }
+        _ => return Err(nbt_error(&format!("unknown tag type {tag_type}"))),
+    })
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) -> crate::Result<()> {
+    let len = i32::try_from(len).map_err(|_| nbt_error("value is too long"))?;
+    bytes.extend(len.to_be_bytes());
+    Ok(())
+}
+
+// Encodes a string as modified UTF-8 (see read_string)
+fn write_string(bytes: &mut Vec<u8>, string: &str) -> crate::Result<()> {
+    let mut encoded = Vec::with_capacity(string.len());
+    for unit in string.encode_utf16() {
+        match unit {
+            0x01..=0x7F => encoded.push(unit as u8),
+            0x00 | 0x80..=0x7FF => encoded
+                .extend([0xC0 | (unit >> 6) as u8, 0x80 | (unit & 0x3F) as u8]),
+            _ => encoded.extend([
+                0xE0 | (unit >> 12) as u8,
+                0x80 | ((unit >> 6) & 0x3F) as u8,
+                0x80 | (unit & 0x3F) as u8,
+            ]),
+        }
+    }
+
+    let len = u16::try_from(encoded.len())
+        .map_err(|_| nbt_error("string is too long"))?;
+    bytes.extend(len.to_be_bytes());
+    bytes.extend(encoded);
+    Ok(())
+}
+
+fn write_payload(bytes: &mut Vec<u8>, tag: &Nbt) -> crate::Result<()> {
+    match tag {
+        Nbt::Byte(x) => bytes.extend(x.to_be_bytes()),
+        Nbt::Short(x) => bytes.extend(x.to_be_bytes()),
+        Nbt::Int(x) => bytes.extend(x.to_be_bytes()),
+        Nbt::Long(x) => bytes.extend(x.to_be_bytes()),
+        Nbt::Float(x) => bytes.extend(x.to_be_bytes()),
+        Nbt::Double(x) => bytes.extend(x.to_be_bytes()),
+        Nbt::ByteArray(values) => {
+            write_len(bytes, values.len())?;
+            bytes.extend(values.iter().flat_map(|x| x.to_be_bytes()));
+        }
+        Nbt::String(x) => write_string(bytes, x)?,
+        Nbt::List(element_type, values) => {
+            bytes.push(*element_type);
+            write_len(bytes, values.len())?;
+            for value in values {
+                if value.tag_type() != *element_type {
+                    return Err(nbt_error("list elements differ in type"));
+                }
+                write_payload(bytes, value)?;
+            }
+        }
+        Nbt::Compound(entries) => {
+            for (name, value) in entries {
+                bytes.push(value.tag_type());
+                write_string(bytes, name)?;
+                write_payload(bytes, value)?;
+            }
+            bytes.push(TAG_END);
+        }
+        Nbt::IntArray(values) => {
+            write_len(bytes, values.len())?;
+            bytes.extend(values.iter().flat_map(|x| x.to_be_bytes()));
+        }
+        Nbt::LongArray(values) => {
+            write_len(bytes, values.len())?;
+            bytes.extend(values.iter().flat_map(|x| x.to_be_bytes()));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string_tag(bytes: &[u8]) -> Vec<u8> {
+        let mut tag = (bytes.len() as u16).to_be_bytes().to_vec();
+        tag.extend(bytes);
+        tag
+    }
+
+    #[test]
+    fn reads_modified_utf8() {
+        // A null character, then U+1F600 as its two surrogates
+        let tag = string_tag(&[
+            b'a', 0xC0, 0x80, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80, 0xC3, 0xA9,
+        ]);
+        let string = read_string(&mut tag.as_slice()).unwrap();
+        assert_eq!(string, "a\0\u{1F600}\u{E9}");
+    }
+
+    #[test]
+    fn writes_modified_utf8() {
+        let mut bytes = Vec::new();
+        write_string(&mut bytes, "a\0\u{1F600}").unwrap();
+        assert_eq!(
+            bytes,
+            string_tag(&[b'a', 0xC0, 0x80, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80])
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let root = Nbt::Compound(vec![
+            (
+                "LevelName".to_string(),
+                Nbt::String("World \u{1F600}".into()),
+            ),
+            (
+                "Null\0Key".to_string(),
+                Nbt::String("a\0b\u{E9}\u{20AC}".into()),
+            ),
+            ("Version".to_string(), Nbt::Int(19133)),
+            ("Seed".to_string(), Nbt::Long(-42)),
+            (
+                "Tags".to_string(),
+                Nbt::List(
+                    TAG_STRING,
+                    vec![
+                        Nbt::String("\u{10FFFF}".into()),
+                        Nbt::String(String::new()),
+                    ],
+                ),
+            ),
+            (
+                "Data".to_string(),
+                Nbt::Compound(vec![
+                    ("Bytes".to_string(), Nbt::ByteArray(vec![-1, 0, 1])),
+                    (
+                        "Ints".to_string(),
+                        Nbt::IntArray(vec![i32::MIN, i32::MAX]),
+                    ),
+                    ("Longs".to_string(), Nbt::LongArray(vec![i64::MIN])),
+                    ("Short".to_string(), Nbt::Short(-2)),
+                    ("Float".to_string(), Nbt::Float(0.5)),
+                    ("Double".to_string(), Nbt::Double(-0.25)),
+                    ("Byte".to_string(), Nbt::Byte(1)),
+                ]),
+            ),
+        ]);
+
+        let bytes = write("root \u{1F600}", &root).unwrap();
+        assert_eq!(read(&bytes).unwrap(), ("root \u{1F600}".to_string(), root));
+    }
+
+    #[test]
+    fn rejects_long_strings() {
+        // Each of these characters takes 3 bytes in modified UTF-8
+        let string = "\u{20AC}".repeat(u16::MAX as usize / 3 + 1);
+        assert!(write_string(&mut Vec::new(), &string).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_strings() {
+        // A lone surrogate
+        let tag = string_tag(&[0xED, 0xA0, 0xBD]);
+        assert!(read_string(&mut tag.as_slice()).is_err());
+
+        // A truncated 3 byte sequence
+        let tag = string_tag(&[0xE2, 0x82]);
+        assert!(read_string(&mut tag.as_slice()).is_err());
+
+        // A 4 byte UTF-8 sequence, which modified UTF-8 doesn't use
+        let tag = string_tag("\u{1F600}".as_bytes());
+        assert!(read_string(&mut tag.as_slice()).is_err());
+    }
+}
@@ -335,7 +335,12 @@ impl ProfileRun {
             .await?;
         let credentials = auth::refresh(id).await?;
 
-        let proc_lock = profile::run_credentials(&path, &credentials).await?;
+        let proc_lock = profile::run_credentials(
+            &path,
+            &credentials,
+            &LaunchOptions::default(),
+        )
+        .await?;
         let mut proc = proc_lock.write().await;
         process::wait_for(&mut proc).await?;
 
@@ -0,0 +1,26 @@
+use crate::api::Result;
+use std::path::Path;
+use theseus::disk_usage::{self, DiskUsageReport, ProfileUsage};
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("disk_usage")
+        .invoke_handler(tauri::generate_handler![
+            disk_usage_get_report,
+            disk_usage_get_profile_usage,
+        ])
+        .build()
+}
+
+// Gets the disk usage of every profile and launcher store
+// invoke('plugin:disk_usage|disk_usage_get_report')
+#[tauri::command]
+pub async fn disk_usage_get_report() -> Result<DiskUsageReport> {
+    Ok(disk_usage::get_report().await?)
+}
+
+// Gets the disk usage of a profile, by top-level folder
+// invoke('plugin:disk_usage|disk_usage_get_profile_usage', {path})
+#[tauri::command]
+pub async fn disk_usage_get_profile_usage(path: &Path) -> Result<ProfileUsage> {
+    Ok(disk_usage::get_profile_usage(path).await?)
+}
@@ -0,0 +1,22 @@
+use crate::api::Result;
+use theseus::gc::{self, GcPlan};
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("gc")
+        .invoke_handler(tauri::generate_handler![gc_plan, gc_run])
+        .build()
+}
+
+// Lists the files no profile uses, without removing anything
+// invoke('plugin:gc|gc_plan')
+#[tauri::command]
+pub async fn gc_plan() -> Result<GcPlan> {
+    Ok(gc::plan().await?)
+}
+
+// Removes the files no profile uses, or moves them to a quarantine folder
+// invoke('plugin:gc|gc_run', {quarantine})
+#[tauri::command]
+pub async fn gc_run(quarantine: bool) -> Result<GcPlan> {
+    Ok(gc::run(quarantine).await?)
+}
@@ -0,0 +1,63 @@
+use crate::api::Result;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use theseus::pack::import::minecraft_launcher::{self, LauncherProfile};
+use theseus::pack::import::mmc;
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("import")
+        .invoke_handler(tauri::generate_handler![
+            import_get_importable_instances,
+            import_mmc,
+            import_get_default_dotminecraft,
+            import_get_launcher_profiles,
+            import_minecraft_launcher,
+        ])
+        .build()
+}
+
+/// Lists the instances in a MultiMC or Prism Launcher data directory
+#[tauri::command]
+pub async fn import_get_importable_instances(
+    base_path: PathBuf,
+) -> Result<Vec<String>> {
+    Ok(mmc::get_importable_instances(base_path).await?)
+}
+
+/// Imports a MultiMC or Prism Launcher instance as a new profile
+#[tauri::command]
+pub async fn import_mmc(
+    base_path: PathBuf,
+    instance_folder: String,
+) -> Result<PathBuf> {
+    Ok(mmc::import_mmc(base_path, instance_folder).await?)
+}
+
+/// Gets the official launcher's game directory, if it exists
+#[tauri::command]
+pub async fn import_get_default_dotminecraft() -> Result<Option<PathBuf>> {
+    Ok(minecraft_launcher::get_default_dotminecraft())
+}
+
+/// Lists the profiles of the official launcher, keyed by their ID
+#[tauri::command]
+pub async fn import_get_launcher_profiles(
+    dotminecraft: PathBuf,
+) -> Result<HashMap<String, LauncherProfile>> {
+    Ok(minecraft_launcher::get_importable_profiles(dotminecraft).await?)
+}
+
+/// Imports profiles from the official launcher
+#[tauri::command]
+pub async fn import_minecraft_launcher(
+    dotminecraft: PathBuf,
+    profile_ids: Vec<String>,
+) -> Result<Vec<PathBuf>> {
+    Ok(
+        minecraft_launcher::import_minecraft_launcher(
+            dotminecraft,
+            profile_ids,
+        )
+        .await?,
+    )
+}
@@ -3,16 +3,22 @@ use serde::{Serialize, Serializer};
 use thiserror::Error;
 
 pub mod auth;
+pub mod disk_usage;
+pub mod gc;
+pub mod import;
 pub mod jre;
 pub mod logs;
 pub mod metadata;
 pub mod pack;
 pub mod process;
+pub mod servers;
 pub mod profile;
 pub mod profile_create;
 pub mod settings;
+pub mod snapshot;
 pub mod tags;
 pub mod utils;
+pub mod worlds;
 
 pub type Result<T> = std::result::Result<T, TheseusSerializableError>;
 
@@ -12,7 +12,9 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
             process_get_all_uuids,
             process_get_all_running_uuids,
             process_get_uuids_by_profile_path,
+            process_get_uuids_by_profile_uuid,
             process_get_all_running_profile_paths,
+            process_get_all_running_profile_uuids,
             process_get_all_running_profiles,
             process_get_output_by_uuid,
             process_kill_by_uuid,
@@ -55,12 +57,26 @@ pub async fn process_get_uuids_by_profile_path(
     Ok(process::get_uuids_by_profile_path(profile_path).await?)
 }
 
+// Gets all process UUIDs by profile UUID
+#[tauri::command]
+pub async fn process_get_uuids_by_profile_uuid(
+    profile_uuid: Uuid,
+) -> Result<Vec<Uuid>> {
+    Ok(process::get_uuids_by_profile_uuid(&profile_uuid).await?)
+}
+
 // Gets the Profile paths of each *running* stored process in the state
 #[tauri::command]
 pub async fn process_get_all_running_profile_paths() -> Result<Vec<PathBuf>> {
     Ok(process::get_all_running_profile_paths().await?)
 }
 
+// Gets the Profile UUIDs of each *running* stored process in the state
+#[tauri::command]
+pub async fn process_get_all_running_profile_uuids() -> Result<Vec<Uuid>> {
+    Ok(process::get_all_running_profile_uuids().await?)
+}
+
 // Gets the Profiles (cloned) of each *running* stored process in the state
 #[tauri::command]
 pub async fn process_get_all_running_profiles() -> Result<Vec<Profile>> {
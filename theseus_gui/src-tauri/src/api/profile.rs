@@ -10,23 +10,52 @@ pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
     tauri::plugin::Builder::new("profile")
         .invoke_handler(tauri::generate_handler![
             profile_remove,
+            profile_remove_by_uuid,
             profile_get,
+            profile_get_by_uuid,
+            profile_get_uuid,
             profile_get_optimal_jre_key,
             profile_list,
+            profile_list_by_uuid,
+            profile_list_by_group,
+            profile_list_groups,
+            profile_list_broken,
+            profile_retry_broken,
+            profile_remove_broken,
+            profile_get_sessions,
+            profile_get_playtime,
+            profile_get_operation,
+            profile_set_shared_folders,
+            profile_get_options,
+            profile_set_options,
+            profile_copy_options,
+            profile_rename_group,
+            profile_remove_group,
+            profile_add_to_group,
+            profile_remove_from_group,
             profile_check_installed,
             profile_install,
+            profile_verify,
             profile_update_all,
             profile_update_project,
+            profile_migrate,
+            profile_apply_migration,
             profile_add_project_from_version,
+            profile_plan_add_project_from_version,
+            profile_apply_add_project,
+            profile_check_health,
             profile_add_project_from_path,
             profile_toggle_disable_project,
             profile_remove_project,
             profile_run,
+            profile_run_by_uuid,
             profile_run_wait,
             profile_run_credentials,
             profile_run_wait_credentials,
             profile_edit,
+            profile_edit_by_uuid,
             profile_edit_icon,
+            profile_duplicate,
             profile_export_mrpack,
             profile_get_potential_override_folders,
         ])
@@ -52,6 +81,32 @@ pub async fn profile_get(
     Ok(res)
 }
 
+// Remove a profile by UUID
+// invoke('plugin:profile|profile_remove_by_uuid', {uuid})
+#[tauri::command]
+pub async fn profile_remove_by_uuid(uuid: Uuid) -> Result<()> {
+    profile::remove_by_uuid(&uuid).await?;
+    Ok(())
+}
+
+// Get a profile by UUID
+// invoke('plugin:profile|profile_get_by_uuid', {uuid, clearProjects})
+#[tauri::command]
+pub async fn profile_get_by_uuid(
+    uuid: Uuid,
+    clear_projects: Option<bool>,
+) -> Result<Option<Profile>> {
+    let res = profile::get_by_uuid(&uuid, clear_projects).await?;
+    Ok(res)
+}
+
+// Get the UUID of a profile by path
+// invoke('plugin:profile|profile_get_uuid', {path})
+#[tauri::command]
+pub async fn profile_get_uuid(path: &Path) -> Result<Option<Uuid>> {
+    Ok(profile::get_uuid(path).await?)
+}
+
 // Get optimal java version from profile
 #[tauri::command]
 pub async fn profile_get_optimal_jre_key(
@@ -71,6 +126,75 @@ pub async fn profile_list(
     Ok(res)
 }
 
+// Get a copy of the profile set, keyed by UUID
+// invoke('plugin:profile|profile_list_by_uuid', {clearProjects})
+#[tauri::command]
+pub async fn profile_list_by_uuid(
+    clear_projects: Option<bool>,
+) -> Result<HashMap<Uuid, Profile>> {
+    let res = profile::list_by_uuid(clear_projects).await?;
+    Ok(res)
+}
+
+// Get a copy of the profiles in a group
+// invoke('plugin:profile|profile_list_by_group', {group, clearProjects})
+#[tauri::command]
+pub async fn profile_list_by_group(
+    group: &str,
+    clear_projects: Option<bool>,
+) -> Result<HashMap<PathBuf, Profile>> {
+    let res = profile::list_by_group(group, clear_projects).await?;
+    Ok(res)
+}
+
+// Lists every group with the number of profiles in it
+// invoke('plugin:profile|profile_list_groups')
+#[tauri::command]
+pub async fn profile_list_groups() -> Result<HashMap<String, usize>> {
+    Ok(profile::list_groups().await?)
+}
+
+// Renames a group in every profile
+// invoke('plugin:profile|profile_rename_group', {oldName, newName})
+#[tauri::command]
+pub async fn profile_rename_group(
+    old_name: &str,
+    new_name: &str,
+) -> Result<()> {
+    profile::rename_group(old_name, new_name).await?;
+    Ok(())
+}
+
+// Removes a group from every profile
+// invoke('plugin:profile|profile_remove_group', {name})
+#[tauri::command]
+pub async fn profile_remove_group(name: &str) -> Result<()> {
+    profile::remove_group(name).await?;
+    Ok(())
+}
+
+// Adds profiles to a group
+// invoke('plugin:profile|profile_add_to_group', {paths, group})
+#[tauri::command]
+pub async fn profile_add_to_group(
+    paths: Vec<PathBuf>,
+    group: &str,
+) -> Result<()> {
+    profile::add_to_group(&paths, group).await?;
+    Ok(())
+}
+
+// Removes profiles from a group
+// invoke('plugin:profile|profile_remove_from_group', {paths, group})
+#[tauri::command]
+pub async fn profile_remove_from_group(
+    paths: Vec<PathBuf>,
+    group: &str,
+) -> Result<()> {
+    profile::remove_from_group(&paths, group).await?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn profile_check_installed(
     path: &Path,
@@ -91,6 +215,91 @@ pub async fn profile_check_installed(
     }
 }
 
+// Gets the recorded play sessions of a profile
+// invoke('plugin:profile|profile_get_sessions', {path})
+#[tauri::command]
+pub async fn profile_get_sessions(path: &Path) -> Result<Vec<Session>> {
+    Ok(profile::get_sessions(path).await?)
+}
+
+// Gets the aggregate playtime of a profile
+// invoke('plugin:profile|profile_get_playtime', {path})
+#[tauri::command]
+pub async fn profile_get_playtime(path: &Path) -> Result<Playtime> {
+    Ok(profile::get_playtime(path).await?)
+}
+
+// Gets the operation currently running on a profile, if any
+// invoke('plugin:profile|profile_get_operation', {path})
+#[tauri::command]
+pub async fn profile_get_operation(
+    path: &Path,
+) -> Result<Option<ProfileOperation>> {
+    Ok(profile::get_operation(path).await?)
+}
+
+// Sets the top-level folders a profile shares with other profiles
+// invoke('plugin:profile|profile_set_shared_folders', {path, sharedFolders})
+#[tauri::command]
+pub async fn profile_set_shared_folders(
+    path: &Path,
+    shared_folders: Option<Vec<String>>,
+) -> Result<()> {
+    Ok(profile::set_shared_folders(path, shared_folders).await?)
+}
+
+// Gets the game options of a profile from its options.txt
+// invoke('plugin:profile|profile_get_options', {path})
+#[tauri::command]
+pub async fn profile_get_options(
+    path: &Path,
+) -> Result<HashMap<String, String>> {
+    Ok(profile::get_options(path).await?)
+}
+
+// Sets game options of a profile, keeping the ones that aren't given
+// invoke('plugin:profile|profile_set_options', {path, options})
+#[tauri::command]
+pub async fn profile_set_options(
+    path: &Path,
+    options: HashMap<String, String>,
+) -> Result<()> {
+    Ok(profile::set_options(path, options).await?)
+}
+
+// Copies the game options in the given categories to another profile
+// invoke('plugin:profile|profile_copy_options', {path, destPath, categories})
+#[tauri::command]
+pub async fn profile_copy_options(
+    path: &Path,
+    dest_path: &Path,
+    categories: Vec<OptionCategory>,
+) -> Result<()> {
+    Ok(profile::copy_options(path, dest_path, &categories).await?)
+}
+
+// Lists the profiles that failed to load
+// invoke('plugin:profile|profile_list_broken')
+#[tauri::command]
+pub async fn profile_list_broken() -> Result<Vec<BrokenProfile>> {
+    Ok(profile::list_broken().await?)
+}
+
+// Tries to load a broken profile again
+// invoke('plugin:profile|profile_retry_broken', {name})
+#[tauri::command]
+pub async fn profile_retry_broken(name: &str) -> Result<Option<PathBuf>> {
+    Ok(profile::retry_broken(name).await?)
+}
+
+// Deletes a broken profile
+// invoke('plugin:profile|profile_remove_broken', {name})
+#[tauri::command]
+pub async fn profile_remove_broken(name: &str) -> Result<()> {
+    profile::remove_broken(name).await?;
+    Ok(())
+}
+
 /// Installs/Repairs a profile
 /// invoke('plugin:profile|profile_install')
 #[tauri::command]
@@ -99,6 +308,16 @@ pub async fn profile_install(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Checks a profile's game files against their hashes, downloading the bad ones again if repair is set
+/// invoke('plugin:profile|profile_verify', { path, repair })
+#[tauri::command]
+pub async fn profile_verify(
+    path: &Path,
+    repair: bool,
+) -> Result<profile::VerifyReport> {
+    Ok(profile::verify(path, repair).await?)
+}
+
 /// Updates all of the profile's projects
 /// invoke('plugin:profile|profile_update_all')
 #[tauri::command]
@@ -118,6 +337,28 @@ pub async fn profile_update_project(
     Ok(profile::update_project(path, project_path, None).await?)
 }
 
+// Plans moving a profile to a different game version or mod loader
+// invoke('plugin:profile|profile_migrate', {path, gameVersion, loader, loaderVersion})
+#[tauri::command]
+pub async fn profile_migrate(
+    path: &Path,
+    game_version: String,
+    loader: ModLoader,
+    loader_version: Option<String>,
+) -> Result<profile::MigrationPlan> {
+    Ok(profile::migrate(path, game_version, loader, loader_version).await?)
+}
+
+// Applies a migration plan from profile_migrate
+// invoke('plugin:profile|profile_apply_migration', {plan})
+#[tauri::command]
+pub async fn profile_apply_migration(
+    plan: profile::MigrationPlan,
+) -> Result<()> {
+    profile::apply_migration(plan).await?;
+    Ok(())
+}
+
 // Adds a project to a profile from a version ID
 // invoke('plugin:profile|profile_add_project_from_version')
 #[tauri::command]
@@ -128,6 +369,34 @@ pub async fn profile_add_project_from_version(
     Ok(profile::add_project_from_version(path, version_id).await?)
 }
 
+// Plans adding a project to a profile from a version ID, with its required dependencies
+// invoke('plugin:profile|profile_plan_add_project_from_version', { path, versionId })
+#[tauri::command]
+pub async fn profile_plan_add_project_from_version(
+    path: &Path,
+    version_id: String,
+) -> Result<profile::AddProjectPlan> {
+    Ok(profile::plan_add_project_from_version(path, version_id).await?)
+}
+
+// Adds the projects of a plan from profile_plan_add_project_from_version
+// invoke('plugin:profile|profile_apply_add_project', { plan })
+#[tauri::command]
+pub async fn profile_apply_add_project(
+    plan: profile::AddProjectPlan,
+) -> Result<Vec<PathBuf>> {
+    Ok(profile::apply_add_project(plan).await?)
+}
+
+// Checks a profile's projects for missing dependencies, conflicts and duplicates
+// invoke('plugin:profile|profile_check_health', { path })
+#[tauri::command]
+pub async fn profile_check_health(
+    path: &Path,
+) -> Result<profile::HealthReport> {
+    Ok(profile::check_health(path).await?)
+}
+
 // Adds a project to a profile from a path
 // invoke('plugin:profile|profile_add_project_from_path')
 #[tauri::command]
@@ -200,19 +469,41 @@ pub async fn profile_get_potential_override_folders(
 // Run minecraft using a profile using the default credentials
 // Returns the UUID, which can be used to poll
 // for the actual Child in the state.
-// invoke('plugin:profile|profile_run', path)
+// Launch options can start the game straight into a world or server
+// invoke('plugin:profile|profile_run', {path, options})
+#[tauri::command]
+pub async fn profile_run(
+    path: &Path,
+    options: Option<LaunchOptions>,
+) -> Result<Uuid> {
+    let minecraft_child =
+        profile::run(path, &options.unwrap_or_default()).await?;
+    let uuid = minecraft_child.read().await.uuid;
+    Ok(uuid)
+}
+
+// Run Minecraft using a profile by UUID using the default credentials
+// Returns the UUID of the process
+// invoke('plugin:profile|profile_run_by_uuid', {uuid, options})
 #[tauri::command]
-pub async fn profile_run(path: &Path) -> Result<Uuid> {
-    let minecraft_child = profile::run(path).await?;
+pub async fn profile_run_by_uuid(
+    uuid: Uuid,
+    options: Option<LaunchOptions>,
+) -> Result<Uuid> {
+    let minecraft_child =
+        profile::run_by_uuid(&uuid, &options.unwrap_or_default()).await?;
     let uuid = minecraft_child.read().await.uuid;
     Ok(uuid)
 }
 
 // Run Minecraft using a profile using the default credentials, and wait for the result
-// invoke('plugin:profile|profile_run_wait', path)
+// invoke('plugin:profile|profile_run_wait', {path, options})
 #[tauri::command]
-pub async fn profile_run_wait(path: &Path) -> Result<()> {
-    let proc_lock = profile::run(path).await?;
+pub async fn profile_run_wait(
+    path: &Path,
+    options: Option<LaunchOptions>,
+) -> Result<()> {
+    let proc_lock = profile::run(path, &options.unwrap_or_default()).await?;
     let mut proc = proc_lock.write().await;
     Ok(process::wait_for(&mut proc).await?)
 }
@@ -220,25 +511,37 @@ pub async fn profile_run_wait(path: &Path) -> Result<()> {
 // Run Minecraft using a profile using chosen credentials
 // Returns the UUID, which can be used to poll
 // for the actual Child in the state.
-// invoke('plugin:profile|profile_run_credentials', {path, credentials})')
+// invoke('plugin:profile|profile_run_credentials', {path, credentials, options})')
 #[tauri::command]
 pub async fn profile_run_credentials(
     path: &Path,
     credentials: Credentials,
+    options: Option<LaunchOptions>,
 ) -> Result<Uuid> {
-    let minecraft_child = profile::run_credentials(path, &credentials).await?;
+    let minecraft_child = profile::run_credentials(
+        path,
+        &credentials,
+        &options.unwrap_or_default(),
+    )
+    .await?;
     let uuid = minecraft_child.read().await.uuid;
     Ok(uuid)
 }
 
 // Run Minecraft using a profile using the chosen credentials, and wait for the result
-// invoke('plugin:profile|profile_run_wait', {path, credentials)
+// invoke('plugin:profile|profile_run_wait', {path, credentials, options)
 #[tauri::command]
 pub async fn profile_run_wait_credentials(
     path: &Path,
     credentials: Credentials,
+    options: Option<LaunchOptions>,
 ) -> Result<()> {
-    let proc_lock = profile::run_credentials(path, &credentials).await?;
+    let proc_lock = profile::run_credentials(
+        path,
+        &credentials,
+        &options.unwrap_or_default(),
+    )
+    .await?;
     let mut proc = proc_lock.write().await;
     Ok(process::wait_for(&mut proc).await?)
 }
@@ -261,15 +564,9 @@ pub struct EditProfileMetadata {
     pub groups: Option<Vec<String>>,
 }
 
-// Edits a profile
-// invoke('plugin:profile|profile_edit', {path, editProfile})
-#[tauri::command]
-pub async fn profile_edit(
-    path: &Path,
-    edit_profile: EditProfile,
-) -> Result<()> {
-    profile::edit(path, |prof| {
-        if let Some(metadata) = edit_profile.metadata.clone() {
+impl EditProfile {
+    fn apply(&self, prof: &mut Profile) {
+        if let Some(metadata) = self.metadata.clone() {
             if let Some(name) = metadata.name {
                 prof.metadata.name = name;
             }
@@ -286,12 +583,42 @@ pub async fn profile_edit(
             }
         }
 
-        prof.java = edit_profile.java.clone();
-        prof.memory = edit_profile.memory;
-        prof.resolution = edit_profile.resolution;
-        prof.hooks = edit_profile.hooks.clone();
+        prof.java = self.java.clone();
+        prof.memory = self.memory;
+        prof.resolution = self.resolution;
+        prof.hooks = self.hooks.clone();
 
         prof.metadata.date_modified = chrono::Utc::now();
+    }
+}
+
+// Edits a profile
+// invoke('plugin:profile|profile_edit', {path, editProfile})
+#[tauri::command]
+pub async fn profile_edit(
+    path: &Path,
+    edit_profile: EditProfile,
+) -> Result<()> {
+    profile::edit(path, |prof| {
+        edit_profile.apply(prof);
+
+        async { Ok(()) }
+    })
+    .await?;
+    State::sync().await?;
+
+    Ok(())
+}
+
+// Edits a profile by UUID
+// invoke('plugin:profile|profile_edit_by_uuid', {uuid, editProfile})
+#[tauri::command]
+pub async fn profile_edit_by_uuid(
+    uuid: Uuid,
+    edit_profile: EditProfile,
+) -> Result<()> {
+    profile::edit_by_uuid(&uuid, |prof| {
+        edit_profile.apply(prof);
 
         async { Ok(()) }
     })
@@ -311,3 +638,14 @@ pub async fn profile_edit_icon(
     profile::edit_icon(path, icon_path).await?;
     Ok(())
 }
+
+// Duplicates a profile under a new name, returning the new profile's path
+// invoke('plugin:profile|profile_duplicate')
+#[tauri::command]
+pub async fn profile_duplicate(
+    path: &Path,
+    new_name: String,
+) -> Result<PathBuf> {
+    let res = profile::duplicate(path, new_name).await?;
+    Ok(res)
+}
@@ -0,0 +1,69 @@
+use crate::api::Result;
+use std::path::{Path, PathBuf};
+use theseus::servers::{self, Server};
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("servers")
+        .invoke_handler(tauri::generate_handler![
+            servers_list,
+            servers_add,
+            servers_remove,
+            servers_reorder,
+            servers_add_to_profiles,
+            servers_add_to_group,
+        ])
+        .build()
+}
+
+// Lists the servers of a profile, in the order Minecraft shows them
+// invoke('plugin:servers|servers_list')
+#[tauri::command]
+pub async fn servers_list(path: &Path) -> Result<Vec<Server>> {
+    Ok(servers::list(path).await?)
+}
+
+// Adds a server to a profile, at the given index or else at the end
+// invoke('plugin:servers|servers_add')
+#[tauri::command]
+pub async fn servers_add(
+    path: &Path,
+    server: Server,
+    index: Option<usize>,
+) -> Result<()> {
+    Ok(servers::add(path, server, index).await?)
+}
+
+// Removes the server at an index from a profile
+// invoke('plugin:servers|servers_remove')
+#[tauri::command]
+pub async fn servers_remove(path: &Path, index: usize) -> Result<()> {
+    Ok(servers::remove(path, index).await?)
+}
+
+// Moves a server of a profile from one index to another
+// invoke('plugin:servers|servers_reorder')
+#[tauri::command]
+pub async fn servers_reorder(
+    path: &Path,
+    from: usize,
+    to: usize,
+) -> Result<()> {
+    Ok(servers::reorder(path, from, to).await?)
+}
+
+// Pushes a server to several profiles, updating it where it's already listed
+// invoke('plugin:servers|servers_add_to_profiles')
+#[tauri::command]
+pub async fn servers_add_to_profiles(
+    paths: Vec<PathBuf>,
+    server: Server,
+) -> Result<()> {
+    Ok(servers::add_to_profiles(&paths, server).await?)
+}
+
+// Pushes a server to every profile in a group
+// invoke('plugin:servers|servers_add_to_group')
+#[tauri::command]
+pub async fn servers_add_to_group(group: String, server: Server) -> Result<()> {
+    Ok(servers::add_to_group(&group, server).await?)
+}
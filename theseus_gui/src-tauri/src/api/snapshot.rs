@@ -0,0 +1,58 @@
+use crate::api::Result;
+use std::path::{Path, PathBuf};
+use theseus::snapshot::{self, Snapshot};
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("snapshot")
+        .invoke_handler(tauri::generate_handler![
+            snapshot_create,
+            snapshot_list,
+            snapshot_remove,
+            snapshot_restore,
+            snapshot_restore_as_new,
+        ])
+        .build()
+}
+
+// Creates a snapshot of a profile
+// invoke('plugin:snapshot|snapshot_create')
+#[tauri::command]
+pub async fn snapshot_create(
+    path: &Path,
+    exclude_saves: bool,
+    exclude_logs: bool,
+) -> Result<Snapshot> {
+    Ok(snapshot::create(path, exclude_saves, exclude_logs).await?)
+}
+
+// Lists the snapshots of a profile, newest first
+// invoke('plugin:snapshot|snapshot_list')
+#[tauri::command]
+pub async fn snapshot_list(path: &Path) -> Result<Vec<Snapshot>> {
+    Ok(snapshot::list(path).await?)
+}
+
+// Removes a snapshot of a profile
+// invoke('plugin:snapshot|snapshot_remove')
+#[tauri::command]
+pub async fn snapshot_remove(path: &Path, snapshot_id: String) -> Result<()> {
+    Ok(snapshot::remove(path, &snapshot_id).await?)
+}
+
+// Restores a snapshot into the profile it was taken from
+// invoke('plugin:snapshot|snapshot_restore')
+#[tauri::command]
+pub async fn snapshot_restore(path: &Path, snapshot_id: String) -> Result<()> {
+    Ok(snapshot::restore(path, &snapshot_id).await?)
+}
+
+// Restores a snapshot into a new profile
+// invoke('plugin:snapshot|snapshot_restore_as_new')
+#[tauri::command]
+pub async fn snapshot_restore_as_new(
+    path: &Path,
+    snapshot_id: String,
+    new_name: String,
+) -> Result<PathBuf> {
+    Ok(snapshot::restore_as_new(path, &snapshot_id, new_name).await?)
+}
@@ -0,0 +1,84 @@
+use crate::api::Result;
+use std::path::Path;
+use theseus::worlds::{self, World, WorldBackup};
+
+pub fn init<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri::plugin::Builder::new("worlds")
+        .invoke_handler(tauri::generate_handler![
+            worlds_list,
+            worlds_get,
+            worlds_delete,
+            worlds_copy,
+            worlds_backup,
+            worlds_list_backups,
+            worlds_remove_backup,
+            worlds_restore_backup,
+        ])
+        .build()
+}
+
+// Lists the worlds of a profile, most recently played first
+// invoke('plugin:worlds|worlds_list')
+#[tauri::command]
+pub async fn worlds_list(path: &Path) -> Result<Vec<World>> {
+    Ok(worlds::list(path).await?)
+}
+
+// Gets a world of a profile by its folder name
+// invoke('plugin:worlds|worlds_get')
+#[tauri::command]
+pub async fn worlds_get(path: &Path, folder: String) -> Result<World> {
+    Ok(worlds::get_world(path, &folder).await?)
+}
+
+// Deletes a world from a profile
+// invoke('plugin:worlds|worlds_delete')
+#[tauri::command]
+pub async fn worlds_delete(path: &Path, folder: String) -> Result<()> {
+    Ok(worlds::delete(path, &folder).await?)
+}
+
+// Copies a world into another profile
+// invoke('plugin:worlds|worlds_copy')
+#[tauri::command]
+pub async fn worlds_copy(
+    path: &Path,
+    folder: String,
+    dest_path: &Path,
+) -> Result<String> {
+    Ok(worlds::copy(path, &folder, dest_path).await?)
+}
+
+// Backs up a world of a profile to a zip archive
+// invoke('plugin:worlds|worlds_backup')
+#[tauri::command]
+pub async fn worlds_backup(path: &Path, folder: String) -> Result<WorldBackup> {
+    Ok(worlds::backup(path, &folder).await?)
+}
+
+// Lists the world backups of a profile, newest first
+// invoke('plugin:worlds|worlds_list_backups')
+#[tauri::command]
+pub async fn worlds_list_backups(path: &Path) -> Result<Vec<WorldBackup>> {
+    Ok(worlds::list_backups(path).await?)
+}
+
+// Removes a world backup of a profile
+// invoke('plugin:worlds|worlds_remove_backup')
+#[tauri::command]
+pub async fn worlds_remove_backup(
+    path: &Path,
+    backup_id: String,
+) -> Result<()> {
+    Ok(worlds::remove_backup(path, &backup_id).await?)
+}
+
+// Restores a world backup, replacing the world it was taken from
+// invoke('plugin:worlds|worlds_restore_backup')
+#[tauri::command]
+pub async fn worlds_restore_backup(
+    path: &Path,
+    backup_id: String,
+) -> Result<()> {
+    Ok(worlds::restore_backup(path, &backup_id).await?)
+}
@@ -118,16 +118,22 @@ fn main() {
     }
     let builder = builder
         .plugin(api::auth::init())
+        .plugin(api::disk_usage::init())
+        .plugin(api::gc::init())
+        .plugin(api::import::init())
         .plugin(api::logs::init())
         .plugin(api::jre::init())
         .plugin(api::metadata::init())
         .plugin(api::pack::init())
         .plugin(api::process::init())
+        .plugin(api::servers::init())
         .plugin(api::profile::init())
         .plugin(api::profile_create::init())
         .plugin(api::settings::init())
+        .plugin(api::snapshot::init())
         .plugin(api::tags::init())
         .plugin(api::utils::init())
+        .plugin(api::worlds::init())
         .invoke_handler(tauri::generate_handler![initialize_state, is_dev]);
 
     builder
@@ -0,0 +1,19 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Gets the disk usage of every profile and launcher store, in bytes
+// Progress is reported through a 'disk_usage' loading bar
+// Returns a DiskUsageReport with profiles (largest first), stores and size
+export async function get_report() {
+  return await invoke('plugin:disk_usage|disk_usage_get_report')
+}
+
+// Gets the disk usage of a profile, in bytes
+// Returns a ProfileUsage with path, name, size, folders ([{ name, size }], largest first) and files_size
+export async function get_profile_usage(path) {
+  return await invoke('plugin:disk_usage|disk_usage_get_profile_usage', { path })
+}
@@ -70,6 +70,17 @@ export async function profile_listener(callback) {
   return await listen('profile', (event) => callback(event.payload))
 }
 
+/// Payload for the 'profiles' event, sent once for a change to several profiles
+/*
+    ProfilesPayload {
+        uuids: unique identifications of the changed profiles
+        event: event type ("Edited")
+    }
+*/
+export async function profiles_listener(callback) {
+  return await listen('profiles', (event) => callback(event.payload))
+}
+
 /// Payload for the 'command' event
 /*
   CommandPayload {
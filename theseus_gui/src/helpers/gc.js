@@ -0,0 +1,19 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Lists the versions, natives, libraries, assets and icons no profile uses, without removing anything
+// Returns a GcPlan with entries ([{ kind, path, size }]), size (the bytes that would be freed) and quarantine_path
+export async function plan() {
+  return await invoke('plugin:gc|gc_plan')
+}
+
+// Removes the files no profile uses, or moves them to a new quarantine folder
+// Fails while Minecraft is running or a profile is busy
+// Returns the GcPlan of what was removed
+export async function run(quarantine) {
+  return await invoke('plugin:gc|gc_run', { quarantine })
+}
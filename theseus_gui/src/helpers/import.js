@@ -0,0 +1,34 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Lists the instance folders in a MultiMC or Prism Launcher data directory
+// eg: getImportableInstances("C:/Users/Steve/AppData/Roaming/PrismLauncher")
+export async function getImportableInstances(basePath) {
+  return await invoke('plugin:import|import_get_importable_instances', { basePath })
+}
+
+// Imports a MultiMC or Prism Launcher instance as a new profile
+// Returns the path of the created profile
+export async function importMmc(basePath, instanceFolder) {
+  return await invoke('plugin:import|import_mmc', { basePath, instanceFolder })
+}
+
+// Gets the official launcher's .minecraft folder, or null if it doesn't exist
+export async function getDefaultDotminecraft() {
+  return await invoke('plugin:import|import_get_default_dotminecraft')
+}
+
+// Lists the profiles of the official launcher, keyed by their ID
+export async function getLauncherProfiles(dotminecraft) {
+  return await invoke('plugin:import|import_get_launcher_profiles', { dotminecraft })
+}
+
+// Imports profiles from the official launcher, reusing its downloaded libraries and assets
+// Returns the paths of the created profiles
+export async function importMinecraftLauncher(dotminecraft, profileIds) {
+  return await invoke('plugin:import|import_minecraft_launcher', { dotminecraft, profileIds })
+}
@@ -43,3 +43,20 @@ export async function install_from_file(path) {
   )
   return await invoke('plugin:pack|pack_install', { location, profile })
 }
+
+// Installs a CurseForge modpack zip from a path
+export async function install_from_curseforge_file(path) {
+  const location = {
+    type: 'fromCurseForgeFile',
+    path: path,
+  }
+  const profile_creator = await invoke('plugin:pack|pack_get_profile_from_pack', { location })
+  const profile = await create(
+    profile_creator.name,
+    profile_creator.gameVersion,
+    profile_creator.modloader,
+    profile_creator.loaderVersion,
+    profile_creator.icon
+  )
+  return await invoke('plugin:pack|pack_install', { location, profile })
+}
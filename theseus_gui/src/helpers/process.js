@@ -35,6 +35,18 @@ export async function get_uuids_by_profile_path(profilePath) {
   return await invoke('plugin:process|process_get_uuids_by_profile_path', { profilePath })
 }
 
+/// Gets all running process IDs with a given profile UUID
+/// Returns [u32]
+export async function get_uuids_by_profile_uuid(profileUuid) {
+  return await invoke('plugin:process|process_get_uuids_by_profile_uuid', { profileUuid })
+}
+
+/// Gets the profile UUIDs of all running processes
+/// Returns [Uuid]
+export async function get_all_running_profile_uuids() {
+  return await invoke('plugin:process|process_get_all_running_profile_uuids')
+}
+
 /// Gets all running process IDs with a given profile path
 /// Returns [u32]
 export async function get_all_running_profile_paths(profilePath) {
@@ -37,6 +37,22 @@ export async function get(path, clearProjects) {
   return await invoke('plugin:profile|profile_get', { path, clearProjects })
 }
 
+// Remove a profile by UUID
+export async function remove_by_uuid(uuid) {
+  return await invoke('plugin:profile|profile_remove_by_uuid', { uuid })
+}
+
+// Get a profile by UUID
+// Returns a Profile
+export async function get_by_uuid(uuid, clearProjects) {
+  return await invoke('plugin:profile|profile_get_by_uuid', { uuid, clearProjects })
+}
+
+// Get the UUID of a profile by path
+export async function get_uuid(path) {
+  return await invoke('plugin:profile|profile_get_uuid', { path })
+}
+
 // Get optimal java version from profile
 // Returns a java version
 export async function get_optimal_jre_key(path) {
@@ -49,15 +65,117 @@ export async function list(clearProjects) {
   return await invoke('plugin:profile|profile_list', { clearProjects })
 }
 
+// Get a copy of the profile set
+// Returns hashmap of uuid -> Profile
+export async function list_by_uuid(clearProjects) {
+  return await invoke('plugin:profile|profile_list_by_uuid', { clearProjects })
+}
+
+// Get a copy of the profiles in a group
+// Returns hashmap of path -> Profile
+export async function list_by_group(group, clearProjects) {
+  return await invoke('plugin:profile|profile_list_by_group', { group, clearProjects })
+}
+
+// Lists every group
+// Returns hashmap of group -> number of profiles in it
+export async function list_groups() {
+  return await invoke('plugin:profile|profile_list_groups')
+}
+
+// Renames a group in every profile
+export async function rename_group(oldName, newName) {
+  return await invoke('plugin:profile|profile_rename_group', { oldName, newName })
+}
+
+// Removes a group from every profile
+export async function remove_group(name) {
+  return await invoke('plugin:profile|profile_remove_group', { name })
+}
+
+// Adds profiles to a group
+export async function add_to_group(paths, group) {
+  return await invoke('plugin:profile|profile_add_to_group', { paths, group })
+}
+
+// Removes profiles from a group
+export async function remove_from_group(paths, group) {
+  return await invoke('plugin:profile|profile_remove_from_group', { paths, group })
+}
+
 export async function check_installed(path, projectId) {
   return await invoke('plugin:profile|profile_check_installed', { path, projectId })
 }
 
+// Gets the recorded play sessions of a profile
+// Returns [Session] with start, end, exit_code and user
+export async function get_sessions(path) {
+  return await invoke('plugin:profile|profile_get_sessions', { path })
+}
+
+// Gets the aggregate playtime of a profile, in seconds
+// Returns { total, last_week, per_account }
+export async function get_playtime(path) {
+  return await invoke('plugin:profile|profile_get_playtime', { path })
+}
+
+// Gets the operation currently running on a profile, or null if it is idle
+// One of installing, installing_pack, updating, launching, migrating, restoring_snapshot,
+// syncing_projects, cleaning_up and verifying
+export async function get_operation(path) {
+  return await invoke('plugin:profile|profile_get_operation', { path })
+}
+
+// Sets the top-level folders a profile shares with other profiles
+// sharedFolders is a list of folder names, or null to use the ones from the settings
+export async function set_shared_folders(path, sharedFolders) {
+  return await invoke('plugin:profile|profile_set_shared_folders', { path, sharedFolders })
+}
+
+// Gets the game options of a profile from its options.txt, as an object of option keys to values
+export async function get_options(path) {
+  return await invoke('plugin:profile|profile_get_options', { path })
+}
+
+// Sets game options of a profile, keeping the ones that aren't given
+export async function set_options(path, options) {
+  return await invoke('plugin:profile|profile_set_options', { path, options })
+}
+
+// Copies the game options in the given categories to another profile
+// Categories are 'keybinds', 'video', 'audio', 'resource_packs' and 'language'
+export async function copy_options(path, destPath, categories) {
+  return await invoke('plugin:profile|profile_copy_options', { path, destPath, categories })
+}
+
+// Lists the profiles that failed to load
+// Returns [BrokenProfile] with name, path, error, date and quarantined
+export async function list_broken() {
+  return await invoke('plugin:profile|profile_list_broken')
+}
+
+// Tries to load a broken profile again
+// Returns the path of the profile if it loaded
+export async function retry_broken(name) {
+  return await invoke('plugin:profile|profile_retry_broken', { name })
+}
+
+// Deletes a broken profile
+export async function remove_broken(name) {
+  return await invoke('plugin:profile|profile_remove_broken', { name })
+}
+
 // Installs/Repairs a profile
 export async function install(path) {
   return await invoke('plugin:profile|profile_install', { path })
 }
 
+// Checks a profile's game files against their hashes, redownloading the bad ones if repair is true
+// Returns { checked, mismatches: [{ kind, path, missing }], repaired }
+export async function verify(path, repair) {
+  return await invoke('plugin:profile|profile_verify', { path, repair })
+}
+
 // Updates all of a profile's projects
 export async function update_all(path) {
   return await invoke('plugin:profile|profile_update_all', { path })
@@ -68,12 +186,48 @@ export async function update_project(path, projectPath) {
   return await invoke('plugin:profile|profile_update_project', { path, projectPath })
 }
 
+// Plans moving a profile to a different game version or mod loader
+// Returns a MigrationPlan of upgrades, unchanged, no_compatible_version and unknown projects
+export async function migrate(path, gameVersion, loader, loaderVersion) {
+  return await invoke('plugin:profile|profile_migrate', {
+    path,
+    gameVersion,
+    loader,
+    loaderVersion,
+  })
+}
+
+// Applies a MigrationPlan from migrate
+export async function apply_migration(plan) {
+  return await invoke('plugin:profile|profile_apply_migration', { plan })
+}
+
 // Add a project to a profile from a version
 // Returns a path to the new project file
 export async function add_project_from_version(path, versionId) {
   return await invoke('plugin:profile|profile_add_project_from_version', { path, versionId })
 }
 
+// Plans adding a project to a profile from a version, with its required dependencies
+// Returns { profile_path, projects: [{ version, required_by }], already_installed, unresolved }
+export async function plan_add_project_from_version(path, versionId) {
+  return await invoke('plugin:profile|profile_plan_add_project_from_version', { path, versionId })
+}
+
+// Adds the projects of a plan from plan_add_project_from_version
+// Returns the paths to the new project files
+export async function apply_add_project(plan) {
+  return await invoke('plugin:profile|profile_apply_add_project', { plan })
+}
+
+// Checks a profile's projects for missing dependencies, conflicts and duplicates
+// Returns { profile_path, issues: [{ type, ... }] }, where type is one of missing_dependency,
+// disabled_dependency, incompatible, unsupported_loader, unsupported_game_version,
+// missing_mod_dependency and duplicate_mod
+export async function check_health(path) {
+  return await invoke('plugin:profile|profile_check_health', { path })
+}
+
 // Add a project to a profile from a path + project_type
 // Returns a path to the new project file
 export async function add_project_from_path(path, projectPath, projectType) {
@@ -119,15 +273,23 @@ export async function get_potential_override_folders(profilePath) {
 }
 
 // Run Minecraft using a pathed profile
+// Options can launch straight into a world or server, as
+// { quick_play: { singleplayer: worldFolder } }, { quick_play: { multiplayer: address } } or { quick_play: { realms: realmId } }
+// Returns PID of child
+export async function run(path, options) {
+  return await invoke('plugin:profile|profile_run', { path, options })
+}
+
+// Run Minecraft using a profile by UUID
 // Returns PID of child
-export async function run(path) {
-  return await invoke('plugin:profile|profile_run', { path })
+export async function run_by_uuid(uuid, options) {
+  return await invoke('plugin:profile|profile_run_by_uuid', { uuid, options })
 }
 
 // Run Minecraft using a pathed profile
 // Waits for end
-export async function run_wait(path) {
-  return await invoke('plugin:profile|profile_run_wait', { path })
+export async function run_wait(path, options) {
+  return await invoke('plugin:profile|profile_run_wait', { path, options })
 }
 
 // Edits a profile
@@ -135,7 +297,18 @@ export async function edit(path, editProfile) {
   return await invoke('plugin:profile|profile_edit', { path, editProfile })
 }
 
+// Edits a profile by UUID
+export async function edit_by_uuid(uuid, editProfile) {
+  return await invoke('plugin:profile|profile_edit_by_uuid', { uuid, editProfile })
+}
+
 // Edits a profile's icon
 export async function edit_icon(path, iconPath) {
   return await invoke('plugin:profile|profile_edit_icon', { path, iconPath })
 }
+
+// Duplicates a profile under a new name
+// Returns the path of the new profile
+export async function duplicate(path, newName) {
+  return await invoke('plugin:profile|profile_duplicate', { path, newName })
+}
@@ -0,0 +1,38 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Lists the servers of a profile, in the order Minecraft shows them
+// Returns [Server] with name, ip, icon (a base64 encoded PNG), accept_textures and hidden
+export async function list(path) {
+  return await invoke('plugin:servers|servers_list', { path })
+}
+
+// Adds a server to a profile, at the given index or else at the end
+// Server is { name, ip, icon, accept_textures, hidden }, where only name and ip are required
+export async function add(path, server, index) {
+  return await invoke('plugin:servers|servers_add', { path, server, index })
+}
+
+// Removes the server at an index from a profile
+export async function remove(path, index) {
+  return await invoke('plugin:servers|servers_remove', { path, index })
+}
+
+// Moves a server of a profile from one index to another
+export async function reorder(path, from, to) {
+  return await invoke('plugin:servers|servers_reorder', { path, from, to })
+}
+
+// Pushes a server to several profiles, updating it where it's already listed
+export async function add_to_profiles(paths, server) {
+  return await invoke('plugin:servers|servers_add_to_profiles', { paths, server })
+}
+
+// Pushes a server to every profile in a group
+export async function add_to_group(group, server) {
+  return await invoke('plugin:servers|servers_add_to_group', { group, server })
+}
@@ -0,0 +1,33 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Creates a snapshot of a profile, optionally leaving out its saves and logs
+// Returns a Snapshot
+export async function create(path, excludeSaves, excludeLogs) {
+  return await invoke('plugin:snapshot|snapshot_create', { path, excludeSaves, excludeLogs })
+}
+
+// Lists the snapshots of a profile, newest first
+export async function list(path) {
+  return await invoke('plugin:snapshot|snapshot_list', { path })
+}
+
+// Removes a snapshot of a profile
+export async function remove(path, snapshotId) {
+  return await invoke('plugin:snapshot|snapshot_remove', { path, snapshotId })
+}
+
+// Restores a snapshot into the profile it was taken from, replacing its contents
+export async function restore(path, snapshotId) {
+  return await invoke('plugin:snapshot|snapshot_restore', { path, snapshotId })
+}
+
+// Restores a snapshot into a new profile
+// Returns the path of the new profile
+export async function restore_as_new(path, snapshotId, newName) {
+  return await invoke('plugin:snapshot|snapshot_restore_as_new', { path, snapshotId, newName })
+}
@@ -0,0 +1,49 @@
+/**
+ * All theseus API calls return serialized values (both return values and errors);
+ * So, for example, addDefaultInstance creates a blank Profile object, where the Rust struct is serialized,
+ *  and deserialized into a usable JS object.
+ */
+import { invoke } from '@tauri-apps/api/tauri'
+
+// Lists the worlds of a profile, most recently played first
+// Returns [World] with folder, name, game_mode, hardcore, last_played, version, seed and icon
+export async function list(path) {
+  return await invoke('plugin:worlds|worlds_list', { path })
+}
+
+// Gets a world of a profile by its folder name
+export async function get(path, folder) {
+  return await invoke('plugin:worlds|worlds_get', { path, folder })
+}
+
+// Deletes a world from a profile
+export async function remove(path, folder) {
+  return await invoke('plugin:worlds|worlds_delete', { path, folder })
+}
+
+// Copies a world into another profile
+// Returns the folder name of the copy
+export async function copy(path, folder, destPath) {
+  return await invoke('plugin:worlds|worlds_copy', { path, folder, destPath })
+}
+
+// Backs up a world of a profile to a zip archive
+// Returns a WorldBackup
+export async function backup(path, folder) {
+  return await invoke('plugin:worlds|worlds_backup', { path, folder })
+}
+
+// Lists the world backups of a profile, newest first
+export async function list_backups(path) {
+  return await invoke('plugin:worlds|worlds_list_backups', { path })
+}
+
+// Removes a world backup of a profile
+export async function remove_backup(path, backupId) {
+  return await invoke('plugin:worlds|worlds_remove_backup', { path, backupId })
+}
+
+// Restores a world backup, replacing the world it was taken from
+export async function restore_backup(path, backupId) {
+  return await invoke('plugin:worlds|worlds_restore_backup', { path, backupId })
+}
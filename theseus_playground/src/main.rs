@@ -98,7 +98,9 @@ async fn main() -> theseus::Result<()> {
 
     println!("running");
     // Run a profile, running minecraft and store the RwLock to the process
-    let proc_lock = profile::run(&canonicalize(&profile_path)?).await?;
+    let proc_lock =
+        profile::run(&canonicalize(&profile_path)?, &LaunchOptions::default())
+            .await?;
     let uuid = proc_lock.read().await.uuid;
     let pid = proc_lock.read().await.current_child.read().await.id();
 